@@ -1,14 +1,16 @@
 use clap::{CommandFactory, Parser, Subcommand};
 use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
 use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs::{self, File};
-use std::io::{self, IsTerminal, Read, Write};
+use std::io::{self, BufRead, IsTerminal, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    mpsc, Arc, Mutex,
 };
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
@@ -192,6 +194,9 @@ enum Commands {
     #[command(alias = "ctx", alias = "c", about = "Run this first, every session. Shows what's being built, active tasks, recent decisions, and direction. Use when: starting a session, picking up where we left off, or answering 'what should we work on today'.")]
     Context {
         goal_id: Option<String>,
+        /// Approximate token budget; sections are filled by priority and the rest is reported as truncated
+        #[arg(long)]
+        budget: Option<usize>,
     },
     #[command(
         alias = "n",
@@ -202,16 +207,27 @@ enum Commands {
         #[arg(long)]
         agent: Option<String>,
         goal_id: Option<String>,
+        /// How long the lock holds without a ping, e.g. 45m or 2h (default: locks.lease config, 30m)
+        #[arg(long)]
+        lease: Option<String>,
+        /// Approximate token budget for the context handed out with the task
+        #[arg(long)]
+        budget: Option<usize>,
     },
     #[command(alias = "st", hide = true, about = "Lock a specific task for this agent")]
     Start {
         #[arg(long)]
         agent: Option<String>,
         task_id: String,
+        #[arg(long)]
+        lease: Option<String>,
+        /// Start even if prerequisite tasks are not done yet
+        #[arg(long)]
+        force: bool,
     },
     #[command(
         alias = "done",
-        about = "Use when: a task or piece of work is finished. Always call this after completing work — it marks the task done AND stores your summary as a persistent memory so the next session knows what was built and why. Never skip this. The summary is how context compounds across sessions."
+        about = "Use when: a task or piece of work is finished. Always call this after completing work — it marks the task done (or 🔎 review when its goal or task requires review) AND stores your summary as a persistent memory so the next session knows what was built and why. Never skip this. The summary is how context compounds across sessions."
     )]
     Complete {
         #[arg(long)]
@@ -227,22 +243,54 @@ enum Commands {
         /// Did it actually work? e.g. "deployed successfully, no issues" or "failed — auth bug introduced". Captures real-world outcome, not just what was built.
         #[arg(long)]
         outcome: Option<String>,
+        /// Run the task's acceptance checks first and refuse to complete if any fail
+        #[arg(long)]
+        verify: bool,
     },
-    #[command(about = "Run hankweave for a task and auto-complete on success")]
+    #[command(about = "Run a worker backend for a task and auto-complete on success")]
     Run {
         task_id: String,
         model: Option<String>,
+        /// Worker backend: hankweave, claude, codex, opencode, copilot, auto, or one declared under [backends.<name>] in config.toml (default: worker.backend)
+        #[arg(long)]
+        backend: Option<String>,
+        /// Kill the worker and fail its task after this long, e.g. 45m (default: worker.task_timeout;
+        /// a task's own max_runtime wins)
+        #[arg(long)]
+        task_timeout: Option<String>,
+        /// CPU time the worker may use, e.g. 10m (ulimit -t)
+        #[arg(long)]
+        max_cpu: Option<String>,
+        /// Heap memory the worker may use, e.g. 2G (ulimit -d, RLIMIT_DATA)
+        #[arg(long)]
+        max_memory: Option<String>,
     },
     #[command(hide = true, about = "Run any command under IMI lifecycle automation")]
     Wrap {
         #[arg(long)]
         agent: Option<String>,
         task_id: String,
-        #[arg(long, default_value_t = 300)]
-        ping_secs: u64,
-        #[arg(long, default_value_t = 900)]
-        checkpoint_secs: u64,
-        #[arg(last = true, num_args = 1.., allow_hyphen_values = true)]
+        /// Heartbeat interval in seconds, 0 to disable (default: worker.ping_secs, 300)
+        #[arg(long)]
+        ping_secs: Option<u64>,
+        /// Checkpoint interval in seconds, 0 to disable (default: worker.checkpoint_secs, 900)
+        #[arg(long)]
+        checkpoint_secs: Option<u64>,
+        /// Worker backend to run when no command is given (default: worker.backend)
+        #[arg(long)]
+        backend: Option<String>,
+        /// Kill the worker and fail its task after this long, e.g. 45m (default: worker.task_timeout;
+        /// a task's own max_runtime wins)
+        #[arg(long)]
+        task_timeout: Option<String>,
+        /// CPU time the worker may use, e.g. 10m (ulimit -t)
+        #[arg(long)]
+        max_cpu: Option<String>,
+        /// Heap memory the worker may use, e.g. 2G (ulimit -d, RLIMIT_DATA; address space is left
+        /// alone so Node/Bun agents, which reserve far more than they touch, still start)
+        #[arg(long)]
+        max_memory: Option<String>,
+        #[arg(last = true, num_args = 0.., allow_hyphen_values = true)]
         command: Vec<String>,
     },
     #[command(
@@ -252,20 +300,39 @@ enum Commands {
     )]
     Orchestrate {
         goal_id: Option<String>,
-        #[arg(long, default_value_t = 4)]
-        workers: usize,
+        /// Parallel workers (default: orchestrate.workers, 4)
+        #[arg(long)]
+        workers: Option<usize>,
         #[arg(long)]
         agent_prefix: Option<String>,
-        #[arg(long, default_value_t = 300)]
-        ping_secs: u64,
-        #[arg(long, default_value_t = 900)]
-        checkpoint_secs: u64,
+        #[arg(long)]
+        ping_secs: Option<u64>,
+        #[arg(long)]
+        checkpoint_secs: Option<u64>,
         #[arg(long)]
         max_tasks: Option<usize>,
-        /// Which CLI to use for workers: auto, claude, opencode, codex, or hankweave (default).
+        /// Worker backend when no command is given: hankweave, claude, codex, opencode, copilot,
+        /// auto, or one declared in config.toml (default: worker.backend).
         /// 'auto' detects the current environment from env vars.
+        #[arg(long, alias = "cli")]
+        backend: Option<String>,
+        /// One status line per worker instead of streaming its output; logs are kept either way (`imi logs`)
+        #[arg(long)]
+        quiet: bool,
+        /// Live dashboard of workers, heartbeats and latest output; keys kill a worker or pause claiming
+        #[arg(long, conflicts_with = "quiet")]
+        tui: bool,
+        /// Kill the worker and fail its task after this long, e.g. 45m (default: worker.task_timeout;
+        /// a task's own max_runtime wins)
+        #[arg(long)]
+        task_timeout: Option<String>,
+        /// CPU time the worker may use, e.g. 10m (ulimit -t)
+        #[arg(long)]
+        max_cpu: Option<String>,
+        /// Heap memory the worker may use, e.g. 2G (ulimit -d, RLIMIT_DATA; address space is left
+        /// alone so Node/Bun agents, which reserve far more than they touch, still start)
         #[arg(long)]
-        cli: Option<String>,
+        max_memory: Option<String>,
         #[arg(last = true, num_args = 0.., allow_hyphen_values = true)]
         command: Vec<String>,
     },
@@ -279,15 +346,59 @@ enum Commands {
     #[command(hide = true, about = "Heartbeat to keep a task locked (~every 10 min)")]
     Ping {
         task_id: String,
+        /// Replace the lease length (e.g. 2h) before a long step, instead of renewing the current one
+        #[arg(long)]
+        extend: Option<String>,
+    },
+    #[command(about = "Use when: a task in 🔎 review has been checked and is good. Marks it done with you recorded as the reviewer, and archives the goal if it was the last open task.")]
+    Approve {
+        #[arg(long)]
+        agent: Option<String>,
+        task_id: String,
+        /// Optional review note kept with the task
+        note: Vec<String>,
+    },
+    #[command(about = "Use when: a task in 🔎 review doesn't meet the bar. Sends it back to todo with your reason, which the next agent that claims it sees as the last failure.")]
+    Reject {
+        #[arg(long)]
+        agent: Option<String>,
+        task_id: String,
+        reason: Vec<String>,
+    },
+    #[command(about = "Use when: a task hit its retry budget and was blocked for a human. Record what you changed or decided, and the task goes back to todo with a fresh attempt count.")]
+    Unblock {
+        task_id: String,
+        /// What a human changed so the next attempt can succeed
+        note: Vec<String>,
     },
     #[command(hide = true, about = "Save mid-task progress and refresh heartbeat")]
     Checkpoint {
         task_id: String,
         note: Vec<String>,
     },
-    #[command(alias = "add-goal", alias = "ag", about = "Use when: a new initiative, area of work, or product direction is being committed to. Goals must trace back to a decision or direction note — if you can't point to one, use imi log first to capture the thinking, then create the goal once it's clear. A goal is a bet: we believe this is worth building. Name it like an outcome, fill in why it matters now, and set success_signal to something observable. Run imi plan first to check it doesn't already exist.")]
+    #[command(about = "Use when: a task keeps bouncing between agents or a goal stalled and you need to see why. Merges claims, pings, checkpoints, failures, lock expiries and completion for a task (or every task in a goal) into one chronological timeline.")]
+    History {
+        /// Task or goal ID (prefix ok)
+        id: String,
+        /// Only entries at/after this point: unix timestamp, YYYY-MM-DD, or a span like 24h / 7d
+        #[arg(long)]
+        since: Option<String>,
+    },
+    #[command(about = "Use when: an orchestrated worker failed or is still running and you need to see what it printed. Shows the timestamped stdout and stderr `imi orchestrate` kept for a task's latest attempt (or --attempt n); --follow keeps printing until the task leaves in_progress.")]
+    Logs {
+        /// Task ID (prefix ok)
+        task_id: String,
+        /// Attempt number (default: the latest)
+        #[arg(long)]
+        attempt: Option<u32>,
+        #[arg(long, short)]
+        follow: bool,
+    },
+    #[command(alias = "add-goal", alias = "ag", about = "Use when: a new initiative, area of work, or product direction is being committed to. Goals must trace back to a decision or direction note — if you can't point to one, use imi log first to capture the thinking, then create the goal once it's clear. A goal is a bet: we believe this is worth building. Name it like an outcome, fill in why it matters now, and set success_signal to something observable. Run imi plan first to check it doesn't already exist. `imi goal review <id> hit|miss|partial \"evidence\"` records whether an archived goal's success signal actually happened; `imi goal due` lists archived goals waiting for that check (name a goal review or due with `imi goal -- review`).", args_conflicts_with_subcommands = true)]
     Goal {
-        name: String,
+        #[command(subcommand)]
+        action: Option<GoalAction>,
+        name: Option<String>,
         desc: Option<String>,
         priority: Option<String>,
         why: Option<String>,
@@ -301,6 +412,9 @@ enum Commands {
         context: Option<String>,
         #[arg(long)]
         workspace: Option<String>,
+        /// Completed tasks wait in 🔎 review until `imi approve`
+        #[arg(long)]
+        requires_review: bool,
     },
     #[command(alias = "add-task", alias = "at", about = "Use when: something specific needs to be built or done and should be tracked ('add this to the backlog', 'we need to do X'). Always attach to a goal_id from imi context.")]
     Task {
@@ -321,11 +435,39 @@ enum Commands {
         acceptance_criteria: Option<String>,
         #[arg(long)]
         workspace: Option<String>,
+        /// Tasks that must be done before this one can be claimed (comma-separated IDs)
+        #[arg(long, value_delimiter = ',')]
+        depends_on: Vec<String>,
+        /// Failures allowed before the task is blocked for a human (default: the max_attempts setting, 3)
+        #[arg(long)]
+        max_attempts: Option<i64>,
+        /// Wall-clock limit for a wrapped or orchestrated worker, e.g. 45m; overrides --task-timeout
+        #[arg(long)]
+        max_runtime: Option<String>,
+        /// Wait in 🔎 review after `imi complete` even if the goal doesn't require it
+        #[arg(long)]
+        requires_review: bool,
+        /// Machine-checkable acceptance check (repeatable): run:<cmd>, run=<exit>:<cmd>, exists:<path>, contains:<path>:<text>, grep:<path>:<regex>
+        #[arg(long = "check")]
+        checks: Vec<String>,
+    },
+    #[command(about = "Use when: a goal, task, decision or lesson has a typo, the wrong goal, or out-of-date criteria. Changes fields in place so IDs and memories survive; every change is kept as a revision. Run without field flags to list past revisions.")]
+    Edit {
+        #[command(subcommand)]
+        target: EditTarget,
+    },
+    #[command(about = "Use when: one task can't start until another is done. Records that <task_id> waits on <depends_on>; `imi next` won't hand it out until every prerequisite is done.")]
+    Depend {
+        task_id: String,
+        depends_on: String,
+        /// Remove the dependency instead of adding it
+        #[arg(long)]
+        remove: bool,
     },
     #[command(
         alias = "mem",
         hide = true,
-        about = "View or add persistent memories"
+        about = "View, add or compact persistent memories"
     )]
     Memory {
         #[arg(long, help = "List human-verified lessons")]
@@ -349,6 +491,23 @@ enum Commands {
         why: String,
         /// What else in the codebase or product changes because of this. Example: 'auth design, session handling, all DB queries'
         affects: Option<String>,
+        /// Goal(s) this decision governs (repeatable or comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        goal: Vec<String>,
+        /// Task(s) this decision governs (repeatable or comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        task: Vec<String>,
+        /// Earlier decision this one replaces; it is marked superseded and hidden from context
+        #[arg(long)]
+        supersedes: Option<String>,
+        /// Date to re-examine this call (YYYY-MM-DD, or 30d / 6w from today); context flags it once due
+        #[arg(long)]
+        revisit_after: Option<String>,
+    },
+    #[command(about = "Use when: a decision should show up in the context of the goals and tasks it governs. `imi decision link <id> --goal/--task` attaches it explicitly; `imi decision backfill` proposes links from the free-text `affects` of older decisions (add --apply to save them). `imi decision revoke <id> \"why\"` withdraws a call that no longer holds.")]
+    Decision {
+        #[command(subcommand)]
+        action: DecisionAction,
     },
     #[command(alias = "l", about = "Use when: something important came up that isn't a firm decision yet — a direction, an instinct, a concern, something to revisit. Human thinking that should be preserved but isn't ready to be a decision. Captures the reasoning as it evolves. Write it the way you'd explain it to a colleague: what you noticed, why it matters, what you're uncertain about. If it becomes a firm call later, promote it to imi decide. Examples: 'the onboarding flow feels too long — users might drop off before seeing value', 'not sure if we should build this ourselves or use an existing library, leaning toward building'.")]
     Log {
@@ -358,20 +517,92 @@ enum Commands {
     Delete {
         id: String,
     },
-    #[command(hide = true, about = "Wipe all state (destructive — use with caution)")]
+    #[command(hide = true, about = "Wipe all state (destructive — use with caution). Snapshots the database to .imi/backups/ first.")]
     Reset {
         #[arg(short, long)]
         force: bool,
     },
+    #[command(about = "Use when: a command just did the wrong thing — deleted the wrong prefix match, completed the wrong task, reset too much. Reverts your last n journaled commands (default 1), newest first, and refuses if something else has changed those rows since. `imi journal` shows what would be undone.")]
+    Undo {
+        #[arg(default_value_t = 1)]
+        n: usize,
+        /// Whose commands to undo (defaults to the current agent)
+        #[arg(long)]
+        agent: Option<String>,
+        /// Restore the before-images even if the rows changed after the command ran
+        #[arg(long)]
+        force: bool,
+    },
+    #[command(about = "Use when: checking what recent commands changed before running `imi undo`. Lists journal entries newest first with the rows each one touched.")]
+    Journal {
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Include entries that were already undone
+        #[arg(long)]
+        all: bool,
+    },
+    #[command(about = "Use when: backing up state before `imi reset`, moving a project to another machine, or seeding a new repo from a template project. Writes every goal, task, decision, note, lesson and memory as portable JSON or NDJSON.")]
+    Export {
+        /// json (one document) or ndjson (one row per line)
+        #[arg(long, default_value = "json")]
+        format: String,
+        /// Only rows created or updated at/after this unix timestamp
+        #[arg(long)]
+        since: Option<i64>,
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+    #[command(about = "Use when: restoring a backup or seeding this repo from another project's `imi export`. Keeps IDs stable; --merge (default) skips rows that already exist and reports conflicts, --replace wipes local state first (per-machine settings such as install_id are kept).")]
+    Import {
+        /// Export file to read (`-` for stdin)
+        file: String,
+        #[arg(long, conflicts_with = "replace")]
+        merge: bool,
+        #[arg(long)]
+        replace: bool,
+    },
+    #[command(about = "Use when: decisions, goals and tasks should be visible in code review. `imi mirror enable` keeps a sorted, diff-friendly JSON copy of them under .imi/mirror/ that you can commit; `imi mirror rebuild` loads it back into the DB after a pull.")]
+    Mirror {
+        #[command(subcommand)]
+        action: MirrorAction,
+    },
+    #[command(about = "Use when: a default (model, worker count, lock lease, retry budget, context sizes) should change for this project or for you. Layers: built-in < ~/.config/imi/config.toml < .imi/config.toml < IMI_* env vars < flags; `imi config list --show-origin` shows which one won.")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    #[command(about = "Use when: you need to know whether something was already decided, tried or learned before acting — 'did we decide anything about postgres?'. Ranked full-text search across goals, tasks, decisions, direction notes, memories and lessons.")]
+    Search {
+        /// Words to look for; results matching more of them rank higher
+        query: Vec<String>,
+        /// Restrict to these kinds: goal, task, decision, note, memory, lesson (repeatable or comma-separated)
+        #[arg(long = "type", value_delimiter = ',')]
+        kinds: Vec<String>,
+        /// Restrict to rows attached to this goal (linked decisions included; notes and lessons are project-wide and always kept)
+        #[arg(long)]
+        goal: Option<String>,
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+    },
     #[command(alias = "stat", hide = true, about = "Show usage statistics")]
     Stats,
     #[command(hide = true, about = "Print agent instructions for a given target")]
     Instructions {
         target: Option<String>,
     },
-    #[command(hide = true, about = "Verify whether a task's acceptance criteria is actually met")]
+    #[command(hide = true, about = "Verify whether a task's acceptance criteria is actually met. --run executes its acceptance checks in the task's workspace and records the results.")]
     Verify {
         task_id: String,
+        /// Execute the task's acceptance checks and store the results as a memory
+        #[arg(long)]
+        run: bool,
+        /// Attach a check: run:<cmd>, run=<exit>:<cmd>, exists:<path>, contains:<path>:<text>, grep:<path>:<regex>
+        #[arg(long = "add")]
+        add: Vec<String>,
+        /// Remove every acceptance check from the task
+        #[arg(long)]
+        clear: bool,
     },
     #[command(hide = true, about = "Audit done tasks — flags those with no acceptance criteria or no completion summary")]
     Audit,
@@ -387,15 +618,229 @@ enum Commands {
     Ops {
         args: Vec<String>,
     },
+    #[command(hide = true, about = "Inspect or apply schema migrations")]
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
 }
 
 #[derive(Subcommand, Debug)]
 enum MemoryAction {
-    List,
+    List {
+        /// Only memories of this type (learning, completion, failure, outcome, ...)
+        #[arg(long = "type")]
+        kind: Option<String>,
+        #[arg(long)]
+        goal: Option<String>,
+        #[arg(long)]
+        task: Option<String>,
+        /// Only memories written by this agent (or `imi`)
+        #[arg(long)]
+        source: Option<String>,
+        /// Include memories whose --ttl has passed
+        #[arg(long)]
+        expired: bool,
+        /// Show rows moved to the archive by `imi memory compact`
+        #[arg(long)]
+        archived: bool,
+    },
     Add {
         goal_id: String,
         key: String,
         value: String,
+        /// learning (default), failure, outcome, completion, ...
+        #[arg(long = "type")]
+        kind: Option<String>,
+        /// Hide the memory from context after this long, e.g. 14d for "staging is down this week"
+        #[arg(long)]
+        ttl: Option<String>,
+    },
+    /// Fold lifecycle and checkpoint rows into one digest per task and drop duplicate
+    /// values; everything removed is kept in the memory archive
+    Compact {
+        /// Only compact memories of this goal's tasks
+        #[arg(long)]
+        goal: Option<String>,
+        /// Only touch rows older than this, e.g. 30d (default: memory.compact_after config, 30d)
+        #[arg(long)]
+        older_than: Option<String>,
+        /// Report what would be compacted without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DecisionAction {
+    /// Attach a decision to goals and/or tasks
+    Link {
+        decision_id: String,
+        #[arg(long, value_delimiter = ',')]
+        goal: Vec<String>,
+        #[arg(long, value_delimiter = ',')]
+        task: Vec<String>,
+        /// Remove these links instead of adding them
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Propose links for decisions whose `affects` names a goal or task
+    Backfill {
+        /// Save the proposed links instead of only listing them
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Withdraw a decision without replacing it
+    Revoke {
+        decision_id: String,
+        /// Why it no longer holds
+        why: Vec<String>,
+    },
+    /// List decisions with their status and revisit date
+    List {
+        /// Include superseded and revoked decisions
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DbAction {
+    /// Show the schema version and any pending migrations
+    Status,
+    /// Apply pending migrations
+    Migrate {
+        /// List what would run without touching the DB
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum MirrorAction {
+    /// Write the mirror now and refresh it after every change
+    Enable,
+    /// Stop refreshing the mirror (files already written are left in place)
+    Disable,
+    /// Rewrite the mirror from the DB now
+    Sync,
+    /// Load goals, tasks, decisions and lessons from the mirror back into the DB
+    Rebuild,
+}
+
+#[derive(Subcommand, Debug)]
+enum GoalAction {
+    /// Record whether the goal's success signal happened (hit, miss or partial)
+    Review {
+        goal_id: String,
+        /// hit | miss | partial — omit to show the success signal and past reviews
+        outcome: Option<String>,
+        /// What you observed
+        evidence: Vec<String>,
+        #[arg(long)]
+        agent: Option<String>,
+    },
+    /// Archived goals whose success signal has not been checked yet
+    Due {
+        /// Only goals archived at least this long ago (default: the goals.review_after setting, 14d)
+        #[arg(long)]
+        after: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum EditTarget {
+    Task {
+        task_id: String,
+        #[arg(long)]
+        title: Option<String>,
+        #[arg(long)]
+        desc: Option<String>,
+        #[arg(long)]
+        why: Option<String>,
+        #[arg(long)]
+        priority: Option<String>,
+        #[arg(long)]
+        context: Option<String>,
+        #[arg(long)]
+        acceptance_criteria: Option<String>,
+        /// Move the task (with its memories) to another goal
+        #[arg(long)]
+        goal: Option<String>,
+        /// Replaces the whole list (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        relevant_files: Option<Vec<String>>,
+        #[arg(long, value_delimiter = ',')]
+        tools: Option<Vec<String>>,
+        #[arg(long)]
+        workspace: Option<String>,
+        #[arg(long)]
+        requires_review: Option<bool>,
+        /// Wall-clock limit for wrapped or orchestrated workers, e.g. 45m
+        #[arg(long)]
+        max_runtime: Option<String>,
+    },
+    Goal {
+        goal_id: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        desc: Option<String>,
+        #[arg(long)]
+        why: Option<String>,
+        #[arg(long)]
+        for_who: Option<String>,
+        #[arg(long)]
+        success_signal: Option<String>,
+        #[arg(long)]
+        priority: Option<String>,
+        #[arg(long)]
+        context: Option<String>,
+        #[arg(long, value_delimiter = ',')]
+        relevant_files: Option<Vec<String>>,
+        #[arg(long)]
+        requires_review: Option<bool>,
+    },
+    Decision {
+        decision_id: String,
+        #[arg(long)]
+        what: Option<String>,
+        #[arg(long)]
+        why: Option<String>,
+        #[arg(long)]
+        affects: Option<String>,
+    },
+    Lesson {
+        lesson_id: String,
+        #[arg(long)]
+        what_went_wrong: Option<String>,
+        #[arg(long)]
+        correct_behavior: Option<String>,
+        #[arg(long)]
+        verified_by: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Print the effective value of one key
+    Get {
+        key: String,
+        /// Also say which layer the value came from
+        #[arg(long)]
+        show_origin: bool,
+    },
+    /// Write a key to .imi/config.toml, or to the user config with --global
+    Set {
+        key: String,
+        value: String,
+        #[arg(long)]
+        global: bool,
+    },
+    /// Print every key with its effective value
+    List {
+        #[arg(long)]
+        show_origin: bool,
     },
 }
 
@@ -435,6 +880,7 @@ struct MemoryRow {
     typ: String,
     source: String,
     created_at: i64,
+    expires_at: Option<i64>,
 }
 
 #[derive(Debug, Clone)]
@@ -460,6 +906,7 @@ struct TaskClaim {
     workspace_path: String,
 }
 
+#[allow(clippy::large_enum_variant)]
 enum ClaimResult {
     NoTasks,
     RaceLost,
@@ -518,12 +965,35 @@ fn main() {
         }
     };
 
-    if let Err(e) = run_schema(&conn) {
-        emit_error(out, &format!("schema error: {e}"));
-        std::process::exit(1);
+    // `imi db` manages migrations itself so it can report or dry-run them.
+    if !matches!(command, Commands::Db { .. }) {
+        if let Err(e) = run_schema(&conn) {
+            emit_error(out, &format!("schema error: {e}"));
+            std::process::exit(1);
+        }
+    }
+
+    let cfg = Config::load(&conn);
+    let journaled = is_journaled(&command);
+    if journaled {
+        if let Err(e) = begin_journal(&conn) {
+            eprintln!("⚠ undo journal unavailable: {e}");
+        }
     }
 
-    let result = dispatch(&mut conn, &db_path, out, command);
+    let changes_before = conn.total_changes();
+    let result = dispatch(&mut conn, &db_path, out, &cfg, command);
+    if journaled {
+        let args = parsed_args.iter().skip(1).cloned().collect::<Vec<_>>().join(" ");
+        if let Err(e) = finish_journal(&mut conn, &cfg, &command_name, &args) {
+            eprintln!("⚠ undo journal not written: {e}");
+        }
+    }
+    if result.is_ok() && conn.total_changes() > changes_before && mirror_enabled(&conn) {
+        if let Err(e) = write_mirror(&conn, &db_path) {
+            eprintln!("⚠ mirror not updated: {e}");
+        }
+    }
 
     let duration_ms = start.elapsed().as_millis();
     log_event(&conn, &command_name, None, None, None, duration_ms as i64);
@@ -540,15 +1010,17 @@ fn main() {
     maybe_auto_update(&conn, out);
 }
 
-fn dispatch(conn: &mut Connection, db_path: &Path, out: OutputCtx, command: Commands) -> Result<(), String> {
+fn dispatch(conn: &mut Connection, db_path: &Path, out: OutputCtx, cfg: &Config, command: Commands) -> Result<(), String> {
     match command {
         Commands::Init => cmd_init(conn, db_path, out),
         Commands::Status => cmd_status(conn, db_path, out),
-        Commands::Plan => cmd_plan(conn, db_path, out),
+        Commands::Plan => cmd_plan(conn, db_path, out, cfg),
         Commands::Archive { goal_id } => cmd_archive(conn, out, goal_id),
-        Commands::Context { goal_id } => cmd_context(conn, out, goal_id),
-        Commands::Next { agent, goal_id } => cmd_next(conn, out, agent, goal_id),
-        Commands::Start { agent, task_id } => cmd_next(conn, out, agent, Some(task_id)),
+        Commands::Context { goal_id, budget } => cmd_context(conn, out, cfg, goal_id, budget),
+        Commands::Next { agent, goal_id, lease, budget } => cmd_next(conn, out, cfg, agent, goal_id, lease, budget, false),
+        Commands::Start { agent, task_id, lease, force } => {
+            cmd_next(conn, out, cfg, agent, Some(task_id), lease, None, force)
+        }
         Commands::Complete {
             agent,
             task_id,
@@ -556,24 +1028,42 @@ fn dispatch(conn: &mut Connection, db_path: &Path, out: OutputCtx, command: Comm
             interpretation,
             uncertainty,
             outcome,
-        } => cmd_complete(conn, out, agent, task_id, summary.join(" "), interpretation, uncertainty, outcome),
-        Commands::Run { task_id, model } => cmd_run(conn, db_path, out, task_id, model),
-        Commands::Wrap {
-            agent,
+            verify,
+        } => cmd_complete(conn, out, cfg, agent, task_id, summary.join(" "), interpretation, uncertainty, outcome, verify),
+        Commands::Run {
             task_id,
-            ping_secs,
-            checkpoint_secs,
-            command,
-        } => cmd_wrap(
-            conn,
-            db_path,
-            out,
+            model,
+            backend,
+            task_timeout,
+            max_cpu,
+            max_memory,
+        } => WorkerLimits::parse(cfg, task_timeout.as_deref(), max_cpu.as_deref(), max_memory.as_deref())
+            .and_then(|limits| cmd_run(conn, db_path, out, cfg, task_id, model, backend, limits)),
+        Commands::Wrap {
             agent,
             task_id,
             ping_secs,
             checkpoint_secs,
+            backend,
+            task_timeout,
+            max_cpu,
+            max_memory,
             command,
-        ),
+        } => WorkerLimits::parse(cfg, task_timeout.as_deref(), max_cpu.as_deref(), max_memory.as_deref()).and_then(|limits| {
+            cmd_wrap(
+                conn,
+                db_path,
+                out,
+                cfg,
+                agent,
+                task_id,
+                ping_secs.unwrap_or_else(|| cfg.u64("worker.ping_secs")),
+                checkpoint_secs.unwrap_or_else(|| cfg.u64("worker.checkpoint_secs")),
+                backend,
+                limits,
+                command,
+            )
+        }),
         Commands::Orchestrate {
             goal_id,
             workers,
@@ -581,29 +1071,47 @@ fn dispatch(conn: &mut Connection, db_path: &Path, out: OutputCtx, command: Comm
             ping_secs,
             checkpoint_secs,
             max_tasks,
-            cli,
-            command,
-        } => cmd_orchestrate(
-            conn,
-            db_path,
-            out,
-            goal_id,
-            workers,
-            agent_prefix,
-            ping_secs,
-            checkpoint_secs,
-            max_tasks,
-            cli,
+            backend,
+            quiet,
+            tui,
+            task_timeout,
+            max_cpu,
+            max_memory,
             command,
-        ),
+        } => WorkerLimits::parse(cfg, task_timeout.as_deref(), max_cpu.as_deref(), max_memory.as_deref()).and_then(|limits| {
+            cmd_orchestrate(
+                conn,
+                db_path,
+                out,
+                cfg,
+                goal_id,
+                workers.unwrap_or_else(|| cfg.u64("orchestrate.workers") as usize),
+                agent_prefix,
+                ping_secs.unwrap_or_else(|| cfg.u64("worker.ping_secs")),
+                checkpoint_secs.unwrap_or_else(|| cfg.u64("worker.checkpoint_secs")),
+                max_tasks,
+                backend,
+                quiet,
+                tui,
+                limits,
+                command,
+            )
+        }),
         Commands::Fail {
             agent,
             task_id,
             reason,
-        } => cmd_fail(conn, out, agent, task_id, reason.join(" ")),
-        Commands::Ping { task_id } => cmd_ping(conn, out, task_id),
+        } => cmd_fail(conn, out, cfg, agent, task_id, reason.join(" ")),
+        Commands::Ping { task_id, extend } => cmd_ping(conn, out, cfg, task_id, extend),
         Commands::Checkpoint { task_id, note } => cmd_checkpoint(conn, out, task_id, note.join(" ")),
+        Commands::History { id, since } => cmd_history(conn, out, id, since),
+        Commands::Logs { task_id, attempt, follow } => cmd_logs(conn, db_path, out, task_id, attempt, follow),
+        Commands::Approve { agent, task_id, note } => cmd_approve(conn, out, agent, task_id, note.join(" ")),
+        Commands::Reject { agent, task_id, reason } => cmd_reject(conn, out, agent, task_id, reason.join(" ")),
+        Commands::Unblock { task_id, note } => cmd_unblock(conn, out, task_id, note.join(" ")),
+        Commands::Goal { action: Some(action), .. } => cmd_goal_action(conn, out, cfg, action),
         Commands::Goal {
+            action: None,
             name,
             desc,
             priority,
@@ -614,7 +1122,11 @@ fn dispatch(conn: &mut Connection, db_path: &Path, out: OutputCtx, command: Comm
             relevant_files,
             context,
             workspace,
-        } => cmd_add_goal(conn, out, name, desc, priority, why_long.or(why), for_who, success_signal, relevant_files, context, workspace),
+            requires_review,
+        } => {
+            let name = name.ok_or("goal name is required: imi goal \"name\" [desc] ...")?;
+            cmd_add_goal(conn, out, name, desc, priority, why_long.or(why), for_who, success_signal, relevant_files, context, workspace, requires_review)
+        }
         Commands::Task {
             goal_id,
             title,
@@ -627,7 +1139,18 @@ fn dispatch(conn: &mut Connection, db_path: &Path, out: OutputCtx, command: Comm
             tools,
             acceptance_criteria,
             workspace,
-        } => cmd_add_task(conn, out, goal_id, title, desc, priority, why_long.or(why), context, relevant_files, tools, acceptance_criteria, workspace),
+            depends_on,
+            max_attempts,
+            max_runtime,
+            requires_review,
+            checks,
+        } => cmd_add_task(conn, out, goal_id, title, desc, priority, why_long.or(why), context, relevant_files, tools, acceptance_criteria, workspace, depends_on, max_attempts, max_runtime, requires_review, checks),
+        Commands::Edit { target } => cmd_edit(conn, out, target),
+        Commands::Depend {
+            task_id,
+            depends_on,
+            remove,
+        } => cmd_depend(conn, out, task_id, depends_on, remove),
         Commands::Memory { lessons, action } => {
             if lessons {
                 if action.is_some() {
@@ -635,7 +1158,7 @@ fn dispatch(conn: &mut Connection, db_path: &Path, out: OutputCtx, command: Comm
                 }
                 cmd_lessons(conn, out)
             } else {
-                cmd_memory(conn, out, action)
+                cmd_memory(conn, out, cfg, action)
             }
         }
         Commands::Lesson {
@@ -643,18 +1166,40 @@ fn dispatch(conn: &mut Connection, db_path: &Path, out: OutputCtx, command: Comm
             correct_behavior,
             verified_by,
         } => cmd_lesson(conn, out, args, correct_behavior, verified_by),
-        Commands::Decide { what, why, affects } => cmd_decide(conn, out, what, why, affects),
+        Commands::Decide {
+            what,
+            why,
+            affects,
+            goal,
+            task,
+            supersedes,
+            revisit_after,
+        } => cmd_decide(conn, out, what, why, affects, goal, task, supersedes, revisit_after),
+        Commands::Decision { action } => cmd_decision(conn, out, action),
         Commands::Log { note } => cmd_log(conn, out, note.join(" ")),
         Commands::Delete { id } => cmd_delete(conn, out, id),
-        Commands::Reset { force } => cmd_reset(conn, out, force),
-        Commands::Stats => cmd_stats(conn, out),
+        Commands::Reset { force } => cmd_reset(conn, db_path, out, force),
+        Commands::Undo { n, agent, force } => cmd_undo(conn, out, n, agent, force),
+        Commands::Journal { limit, all } => cmd_journal(conn, out, limit, all),
+        Commands::Export { format, since, output } => cmd_export(conn, out, format, since, output),
+        Commands::Import { file, merge: _, replace } => cmd_import(conn, out, file, replace),
+        Commands::Mirror { action } => cmd_mirror(conn, db_path, out, action),
+        Commands::Config { action } => cmd_config(conn, db_path, out, action),
+        Commands::Search {
+            query,
+            kinds,
+            goal,
+            limit,
+        } => cmd_search(conn, out, query.join(" "), kinds, goal, limit),
+        Commands::Stats => cmd_stats(conn, out, cfg),
         Commands::Instructions { target } => cmd_instructions(out, target),
-        Commands::Verify { task_id } => cmd_verify(conn, out, task_id),
+        Commands::Verify { task_id, run, add, clear } => cmd_verify(conn, out, cfg, task_id, run, add, clear),
         Commands::Audit => cmd_audit(conn, out),
         Commands::Think => cmd_think(conn, out),
-        Commands::Check { task_id } => cmd_check(conn, out, task_id),
+        Commands::Check { task_id } => cmd_check(conn, out, cfg, task_id),
         Commands::Update => cmd_update(out),
-        Commands::Ops { args } => cmd_ops(conn, out, args),
+        Commands::Ops { args } => cmd_ops(conn, out, cfg, args),
+        Commands::Db { action } => cmd_db(conn, db_path, out, action),
     }
 }
 
@@ -690,14 +1235,29 @@ fn command_key(command: &Commands) -> &'static str {
         Commands::Fail { .. } => "fail",
         Commands::Ping { .. } => "ping",
         Commands::Checkpoint { .. } => "checkpoint",
+        Commands::History { .. } => "history",
+        Commands::Logs { .. } => "logs",
+        Commands::Approve { .. } => "approve",
+        Commands::Reject { .. } => "reject",
+        Commands::Unblock { .. } => "unblock",
         Commands::Goal { .. } => "goal",
         Commands::Task { .. } => "task",
+        Commands::Edit { .. } => "edit",
+        Commands::Depend { .. } => "depend",
         Commands::Memory { .. } => "memory",
         Commands::Lesson { .. } => "lesson",
         Commands::Decide { .. } => "decide",
+        Commands::Decision { .. } => "decision",
         Commands::Log { .. } => "log",
         Commands::Delete { .. } => "delete",
         Commands::Reset { .. } => "reset",
+        Commands::Undo { .. } => "undo",
+        Commands::Journal { .. } => "journal",
+        Commands::Export { .. } => "export",
+        Commands::Import { .. } => "import",
+        Commands::Mirror { .. } => "mirror",
+        Commands::Config { .. } => "config",
+        Commands::Search { .. } => "search",
         Commands::Stats => "stats",
         Commands::Instructions { .. } => "instructions",
         Commands::Verify { .. } => "verify",
@@ -706,6 +1266,7 @@ fn command_key(command: &Commands) -> &'static str {
         Commands::Check { .. } => "check",
         Commands::Update => "update",
         Commands::Ops { .. } => "ops",
+        Commands::Db { .. } => "db",
     }
 }
 
@@ -826,11 +1387,7 @@ fn maybe_auto_update(conn: &Connection, out: OutputCtx) {
 }
 
 fn cmd_update(out: OutputCtx) -> Result<(), String> {
-    if out.color {
-        print!("Checking for updates... ");
-    } else {
-        print!("Checking for updates... ");
-    }
+    print!("Checking for updates... ");
     let _ = io::stdout().flush();
     let latest = fetch_latest_version()
         .ok_or("Could not reach GitHub — check your connection.")?;
@@ -838,13 +1395,8 @@ fn cmd_update(out: OutputCtx) -> Result<(), String> {
         println!("already on latest (v{VERSION})");
         return Ok(());
     }
-    if out.color {
-        println!("v{latest} available");
-        print!("Installing... ");
-    } else {
-        println!("v{latest} available");
-        print!("Installing... ");
-    }
+    println!("v{latest} available");
+    print!("Installing... ");
     let _ = io::stdout().flush();
     install_version(&latest)?;
     if out.color {
@@ -893,7 +1445,7 @@ fn cmd_init(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), Str
             bold("imi"),
             dim("\"imi save this goal for later\""));
         println!();
-        println!("  {} {}", green("✓"), format!("Initialized → {}", db_path.display()));
+        println!("  {} Initialized → {}", green("✓"), db_path.display());
         println!();
     }
     Ok(())
@@ -918,6 +1470,8 @@ fn cmd_status(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), S
     let memories_count: i64 = conn
         .query_row("SELECT COUNT(*) FROM memories", [], |r| r.get(0))
         .unwrap_or(0);
+    let needs_human = query_needs_human(conn)?;
+    let review_queue = query_review_queue(conn)?;
 
     let goals = get_goals(conn)?;
 
@@ -927,6 +1481,18 @@ fn cmd_status(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), S
             let tasks = get_tasks_for_goal(conn, &g.id)?;
             let total = tasks.len() as i64;
             let done = tasks.iter().filter(|t| t.status == "done").count() as i64;
+            let mut tasks_json = Vec::new();
+            for t in &tasks {
+                let blockers = query_task_blockers(conn, &t.id)?;
+                tasks_json.push(json!({
+                    "id": t.id,
+                    "title": t.title,
+                    "status": t.status,
+                    "priority": t.priority,
+                    "agent_id": t.agent_id,
+                    "blocked_by": blockers_to_value(&blockers)
+                }));
+            }
             goal_json.push(json!({
                 "id": g.id,
                 "name": g.name,
@@ -934,13 +1500,7 @@ fn cmd_status(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), S
                 "priority": g.priority,
                 "done_tasks": done,
                 "total_tasks": total,
-                "tasks": tasks.iter().map(|t| json!({
-                    "id": t.id,
-                    "title": t.title,
-                    "status": t.status,
-                    "priority": t.priority,
-                    "agent_id": t.agent_id
-                })).collect::<Vec<_>>()
+                "tasks": tasks_json
             }));
         }
 
@@ -956,8 +1516,11 @@ fn cmd_status(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), S
                     "wip": wip_count,
                     "review": review_count,
                     "todo": todo_count,
-                    "memories": memories_count
+                    "memories": memories_count,
+                    "blocked": needs_human.len()
                 },
+                "needs_human": needs_human_to_value(&needs_human),
+                "waiting_for_review": review_queue_to_value(&review_queue),
                 "goals": goal_json
             })
         );
@@ -1007,6 +1570,11 @@ fn cmd_status(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), S
                 .into_iter()
                 .filter(|t| t.status == "todo" || t.status == "in_progress")
             {
+                let blocked_by = query_task_blockers(conn, &task.id)?
+                    .into_iter()
+                    .map(|b| b.0)
+                    .collect::<Vec<_>>()
+                    .join(" ");
                 task_rows.push(vec![
                     g.id.clone(),
                     task.id,
@@ -1014,15 +1582,32 @@ fn cmd_status(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), S
                     task.status,
                     task.priority,
                     task.agent_id.unwrap_or_default(),
+                    blocked_by,
                 ]);
             }
         }
         t.section("goals", &["id", "name", "status", "done", "total"], goal_rows);
         t.section(
             "tasks",
-            &["goal_id", "id", "title", "status", "priority", "agent"],
+            &["goal_id", "id", "title", "status", "priority", "agent", "blocked_by"],
             task_rows,
         );
+        t.section(
+            "needs_human",
+            &["id", "title", "attempts", "last_failure"],
+            needs_human
+                .iter()
+                .map(|n| vec![n.0.clone(), n.1.clone(), n.2.to_string(), n.3.clone()])
+                .collect(),
+        );
+        t.section(
+            "waiting_for_review",
+            &["id", "title", "submitted_by", "summary", "submitted_at"],
+            review_queue
+                .iter()
+                .map(|r| vec![r.0.clone(), r.1.clone(), r.2.clone(), r.3.clone(), r.4.to_string()])
+                .collect(),
+        );
         print!("{}", t.finish());
         return Ok(());
     }
@@ -1050,6 +1635,16 @@ fn cmd_status(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), S
     );
     println!("  Memories    {}", memories_count);
     println!();
+    let goal_ids = unique_id_prefixes(conn, "goals")?;
+    let task_ids = unique_id_prefixes(conn, "tasks")?;
+    if !needs_human.is_empty() {
+        print_needs_human(&task_ids, &needs_human);
+        println!();
+    }
+    if !review_queue.is_empty() {
+        print_review_queue(&task_ids, &review_queue);
+        println!();
+    }
     println!("## Active goals");
 
     let all_goals = get_goals(conn)?;
@@ -1070,7 +1665,7 @@ fn cmd_status(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), S
             g.name,
             done,
             total,
-            g.id
+            short_id(&goal_ids, &g.id)
         );
         if done > 0 {
             println!("    {} {} done", status_icon(out, "done"), done);
@@ -1086,7 +1681,7 @@ fn cmd_status(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), S
                     status_icon(out, &task.status),
                     priority_icon(out, &task.priority),
                     task.title,
-                    task.id
+                    short_id(&task_ids, &task.id)
                 );
             } else {
                 println!(
@@ -1094,10 +1689,14 @@ fn cmd_status(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), S
                     status_icon(out, &task.status),
                     priority_icon(out, &task.priority),
                     task.title,
-                    task.id,
+                    short_id(&task_ids, &task.id),
                     agent
                 );
             }
+            let blockers = query_task_blockers(conn, &task.id)?;
+            if !blockers.is_empty() {
+                println!("      ⛔ blocked by: {}", blockers_label(&task_ids, &blockers));
+            }
         }
         println!();
     }
@@ -1113,65 +1712,161 @@ fn cmd_status(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), S
     Ok(())
 }
 
-fn cmd_goals(conn: &Connection, out: OutputCtx, archived: bool) -> Result<(), String> {
-    let goals: Vec<GoalRow> = get_goals(conn)?
-        .into_iter()
-        .filter(|g| (archived && g.status == "archived") || (!archived && g.status != "archived"))
-        .collect();
+const GOAL_OUTCOMES: &[&str] = &["hit", "miss", "partial"];
 
-    if out.is_json() {
-        println!(
-            "{}",
-            json!(goals
-                .iter()
-                .map(|g| json!({
-                    "id": g.id,
-                    "name": g.name,
-                    "description": g.description,
-                    "status": g.status,
-                    "priority": g.priority,
-                    "created_at": g.created_at,
-                    "created_ago": ago(g.created_at)
-                }))
-                .collect::<Vec<_>>())
-        );
-        return Ok(());
+fn cmd_goal_action(conn: &Connection, out: OutputCtx, cfg: &Config, action: GoalAction) -> Result<(), String> {
+    match action {
+        GoalAction::Review { goal_id, outcome, evidence, agent } => {
+            cmd_goal_review(conn, out, goal_id, outcome, evidence.join(" "), agent)
+        }
+        GoalAction::Due { after } => {
+            let after = match after {
+                Some(v) => parse_duration_secs(&v)?,
+                None => cfg.duration_secs("goals.review_after"),
+            };
+            let due = query_goals_due_for_review(conn, Some(after))?;
+            if out.is_json() {
+                println!("{}", json!({"due_for_review": goals_due_to_value(&due)}));
+            } else if out.is_toon() {
+                let mut t = ToonBuilder::new();
+                t.section(
+                    "due_for_review",
+                    &["id", "name", "success_signal", "archived_at"],
+                    due.iter().map(|g| vec![g.0.clone(), g.1.clone(), g.2.clone(), g.3.to_string()]).collect(),
+                );
+                print!("{}", t.finish());
+            } else if due.is_empty() {
+                println!("No archived goals are due for an outcome review.");
+            } else {
+                println!("## Due for outcome review (archived more than {} ago)", format_duration(after));
+                let goal_ids = unique_id_prefixes(conn, "goals")?;
+                for (id, name, signal, archived_at) in &due {
+                    let id = short_id(&goal_ids, id);
+                    println!("  🎯 {name}  {id}  (archived {})", ago(*archived_at));
+                    let signal = if signal.is_empty() { "(no success signal set)" } else { signal.as_str() };
+                    println!("    success signal: {signal}");
+                }
+                println!("  → imi goal review <id> hit|miss|partial \"evidence\"");
+            }
+            Ok(())
+        }
     }
+}
 
-    if out.is_toon() {
-        let mut t = ToonBuilder::new();
-        t.section(
-            "goals",
-            &["id", "name", "status", "priority", "created_ago"],
-            goals
+/// Archived goals with no recorded outcome, as (id, name, success signal, archived at).
+/// `min_age` limits the list to goals archived at least that many seconds ago.
+fn query_goals_due_for_review(conn: &Connection, min_age: Option<i64>) -> Result<Vec<(String, String, String, i64)>, String> {
+    let cutoff = min_age.map_or(i64::MAX, |age| now_ts() - age);
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, COALESCE(success_signal,''), COALESCE(archived_at, updated_at, created_at, 0)
+             FROM goals
+             WHERE status='archived' AND outcome IS NULL
+               AND COALESCE(archived_at, updated_at, created_at, 0) <= ?1
+             ORDER BY COALESCE(archived_at, updated_at, created_at, 0) ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![cutoff], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn goals_due_to_value(rows: &[(String, String, String, i64)]) -> Value {
+    json!(rows
+        .iter()
+        .map(|(id, name, signal, archived_at)| json!({"id": id, "name": name, "success_signal": signal, "archived_at": archived_at}))
+        .collect::<Vec<_>>())
+}
+
+fn cmd_goal_review(
+    conn: &Connection,
+    out: OutputCtx,
+    goal_prefix: String,
+    outcome: Option<String>,
+    evidence: String,
+    agent: Option<String>,
+) -> Result<(), String> {
+    let goal_id = resolve_id_prefix(conn, "goals", &goal_prefix)?
+        .ok_or_else(|| format!("goal not found: {goal_prefix}"))?;
+    let (name, signal): (String, String) = conn
+        .query_row(
+            "SELECT name, COALESCE(success_signal,'') FROM goals WHERE id=?1",
+            params![goal_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let Some(outcome) = outcome else {
+        let mut stmt = conn
+            .prepare(
+                "SELECT value, COALESCE(source,''), COALESCE(created_at,0) FROM memories
+                 WHERE goal_id=?1 AND key='goal_outcome' ORDER BY created_at DESC, rowid DESC",
+            )
+            .map_err(|e| e.to_string())?;
+        let reviews: Vec<(String, String, i64)> = stmt
+            .query_map(params![goal_id], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        if out.is_json() {
+            let items: Vec<Value> = reviews
                 .iter()
-                .map(|g| {
-                    vec![
-                        g.id.clone(),
-                        g.name.clone(),
-                        g.status.clone(),
-                        g.priority.clone(),
-                        ago(g.created_at),
-                    ]
-                })
-                .collect(),
-        );
-        print!("{}", t.finish());
+                .map(|(v, who, at)| json!({"review": v, "reviewer": who, "created_at": at}))
+                .collect();
+            println!("{}", json!({"id": goal_id, "name": name, "success_signal": signal, "reviews": items}));
+        } else if out.is_toon() {
+            let mut t = ToonBuilder::new();
+            t.section("goal", &["id", "name", "success_signal"], vec![vec![goal_id.clone(), name, signal]]);
+            t.section(
+                "reviews",
+                &["review", "reviewer", "created_at"],
+                reviews.into_iter().map(|(v, who, at)| vec![v, who, at.to_string()]).collect(),
+            );
+            print!("{}", t.finish());
+        } else {
+            let goal_id = display_id(conn, "goals", &goal_id)?;
+            println!("🎯 {name}  {goal_id}");
+            println!("success signal: {}", if signal.is_empty() { "(none set)" } else { signal.as_str() });
+            if reviews.is_empty() {
+                println!("No outcome recorded yet — imi goal review {goal_id} hit|miss|partial \"evidence\"");
+            }
+            for (v, who, at) in &reviews {
+                println!("  {}  {v}  ({who})", format_timestamp(*at));
+            }
+        }
         return Ok(());
-    }
+    };
 
-    for g in goals {
-        println!(
-            "{} {} {}  {}  {}",
-            status_icon(out, &g.status),
-            priority_icon(out, &g.priority),
-            g.id,
-            g.name,
-            ago(g.created_at)
-        );
+    let outcome = outcome.trim().to_lowercase();
+    if !GOAL_OUTCOMES.contains(&outcome.as_str()) {
+        return Err(format!("outcome must be one of: {}", GOAL_OUTCOMES.join(", ")));
     }
-
-    Ok(())
+    if evidence.trim().is_empty() {
+        return Err(format!("say what you observed: imi goal review {goal_id} {outcome} \"evidence\""));
+    }
+    let reviewer = current_agent(agent.as_deref());
+    let now = now_ts();
+    conn.execute(
+        "UPDATE goals SET outcome=?1, outcome_evidence=?2, outcome_reviewed_by=?3, outcome_reviewed_at=?4, updated_at=?4 WHERE id=?5",
+        params![outcome, evidence, reviewer, now, goal_id],
+    )
+    .map_err(|e| e.to_string())?;
+    let value = format!("{outcome}: {evidence}");
+    conn.execute(
+        "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
+         VALUES (?1, ?2, NULL, 'goal_outcome', ?3, 'outcome', ?4, ?5, ?6)",
+        params![gen_typed_id("m"), goal_id, value, format!("success signal: {signal}"), reviewer, now],
+    )
+    .map_err(|e| e.to_string())?;
+    let icon = match outcome.as_str() {
+        "hit" => "✅",
+        "miss" => "❌",
+        _ => "🟡",
+    };
+    emit_simple_ok(out, &format!("{icon} Outcome for {name}: {outcome} — {evidence}"))
 }
 
 fn cmd_archive(conn: &Connection, out: OutputCtx, goal_prefix: String) -> Result<(), String> {
@@ -1179,7 +1874,7 @@ fn cmd_archive(conn: &Connection, out: OutputCtx, goal_prefix: String) -> Result
         .ok_or_else(|| format!("goal not found: {goal_prefix}"))?;
     let now = now_ts();
     conn.execute(
-        "UPDATE goals SET status='archived', updated_at=?1 WHERE id=?2",
+        "UPDATE goals SET status='archived', updated_at=?1, archived_at=?1 WHERE id=?2",
         params![now, goal_id],
     )
     .map_err(|e| e.to_string())?;
@@ -1187,124 +1882,26 @@ fn cmd_archive(conn: &Connection, out: OutputCtx, goal_prefix: String) -> Result
     Ok(())
 }
 
-fn cmd_tasks(conn: &Connection, out: OutputCtx, filter: Option<String>) -> Result<(), String> {
-    let filter = filter.unwrap_or_else(|| "all".to_string());
-    let mut query = "SELECT t.id, t.title, COALESCE(t.description,''), COALESCE(t.why,''), t.goal_id, COALESCE(t.status,'todo'), COALESCE(t.priority,'medium'), t.agent_id, COALESCE(t.created_at,0), COALESCE(g.name,'') FROM tasks t LEFT JOIN goals g ON t.goal_id=g.id".to_string();
-    let mut params_vec: Vec<String> = Vec::new();
-
-    match filter.as_str() {
-        "all" => {}
-        "todo" | "done" | "review" => {
-            query.push_str(" WHERE t.status=?1");
-            params_vec.push(filter.clone());
-        }
-        "wip" | "in_progress" => {
-            query.push_str(" WHERE t.status='in_progress'");
-        }
-        prefix => {
-            let goal_id = resolve_id_prefix(conn, "goals", prefix)?
-                .ok_or_else(|| format!("goal not found for prefix: {prefix}"))?;
-            query.push_str(" WHERE t.goal_id=?1");
-            params_vec.push(goal_id);
-        }
-    }
-    query.push_str(" ORDER BY COALESCE(t.updated_at,t.created_at,0) DESC");
-
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
-    let rows = if params_vec.is_empty() {
-        stmt.query_map([], |r| {
-            Ok((
-                r.get::<_, String>(0)?,
-                r.get::<_, String>(1)?,
-                r.get::<_, String>(5)?,
-                r.get::<_, String>(6)?,
-                r.get::<_, String>(9)?,
-            ))
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?
-    } else {
-        stmt.query_map(params![params_vec[0].clone()], |r| {
-            Ok((
-                r.get::<_, String>(0)?,
-                r.get::<_, String>(1)?,
-                r.get::<_, String>(5)?,
-                r.get::<_, String>(6)?,
-                r.get::<_, String>(9)?,
-            ))
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?
-    };
-
-    if out.is_json() {
-        println!(
-            "{}",
-            json!(rows
-                .iter()
-                .map(|r| json!({"id": r.0, "title": r.1, "status": r.2, "priority": r.3, "goal": r.4}))
-                .collect::<Vec<_>>())
-        );
-        return Ok(());
-    }
-
-    if out.is_toon() {
-        let mut t = ToonBuilder::new();
-        t.section(
-            "tasks",
-            &["id", "title", "status", "priority", "goal"],
-            rows.iter()
-                .map(|r| vec![r.0.clone(), r.1.clone(), r.2.clone(), r.3.clone(), r.4.clone()])
-                .collect(),
-        );
-        print!("{}", t.finish());
-        return Ok(());
-    }
-
-    for (id, title, status, priority, goal_name) in rows {
-        if goal_name.is_empty() {
-            println!(
-                "{} {} {}  {}",
-                status_icon(out, &status),
-                priority_icon(out, &priority),
-                id,
-                title
-            );
-        } else {
-            println!(
-                "{} {} {}  {}  — {}",
-                status_icon(out, &status),
-                priority_icon(out, &priority),
-                id,
-                title,
-                goal_name
-            );
-        }
-    }
-    Ok(())
-}
-
-fn cmd_plan(conn: &Connection, db_path: &Path, out: OutputCtx) -> Result<(), String> {
+fn cmd_plan(conn: &Connection, db_path: &Path, out: OutputCtx, cfg: &Config) -> Result<(), String> {
     if out.is_json() || out.is_toon() {
         return cmd_status(conn, db_path, out);
     }
-    cmd_context(conn, out, None)?;
+    cmd_context(conn, out, cfg, None, None)?;
     println!();
     cmd_status(conn, db_path, out)
 }
 
-fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Result<(), String> {
+fn cmd_context(conn: &Connection, out: OutputCtx, cfg: &Config, goal_id: Option<String>, budget: Option<usize>) -> Result<(), String> {
+    let mut budget = ContextBudget::new(budget)?;
     if let Some(goal_prefix) = goal_id {
-        return cmd_context_goal(conn, out, goal_prefix);
+        return cmd_context_goal(conn, out, cfg, goal_prefix, budget);
     }
 
     let now = now_ts();
     let week_ago = now - 7 * 24 * 3600;
 
-    let direction = query_direction(conn, Some(week_ago), 10)?;
-    let decisions = query_decisions(conn, 15)?;
+    let direction = query_direction(conn, Some(week_ago), budget.candidates(cfg.limit("context.direction")))?;
+    let decisions = query_decisions(conn, budget.candidates(cfg.limit("context.decisions")))?;
     let founding_intent: Vec<(String, String, i64)> = {
         let mut stmt = conn
             .prepare(
@@ -1324,13 +1921,13 @@ fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Re
     let killed_decisions: Vec<(String, String, String, i64)> = {
         let mut stmt = conn
             .prepare(
-                "SELECT what, why, COALESCE(affects,''), COALESCE(created_at,0)
-                 FROM decisions
-                 WHERE LOWER(COALESCE(what,'')) LIKE '%killed%'
-                    OR LOWER(COALESCE(what,'')) LIKE '%not%'
-                    OR LOWER(COALESCE(why,'')) LIKE '%instead%'
-                    OR LOWER(COALESCE(why,'')) LIKE '%rejected%'
-                 ORDER BY COALESCE(created_at,0) DESC
+                "SELECT d.what,
+                        CASE WHEN d.status = 'revoked' THEN 'revoked: ' || COALESCE(d.status_reason, '')
+                             ELSE 'superseded by: ' || COALESCE(n.what, d.superseded_by, '') END,
+                        COALESCE(d.affects,''), COALESCE(d.updated_at, d.created_at, 0)
+                 FROM decisions d LEFT JOIN decisions n ON n.id = d.superseded_by
+                 WHERE d.status IN ('revoked', 'superseded')
+                 ORDER BY COALESCE(d.updated_at, d.created_at, 0) DESC
                  LIMIT 5",
             )
             .map_err(|e| e.to_string())?;
@@ -1341,10 +1938,45 @@ fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Re
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| e.to_string())?
     };
-    let active_goals = query_active_goals(conn, 10)?;
+    let active_goals = query_active_goals(conn, cfg.limit("context.goals"))?;
     let wip = query_wip_tasks(conn, 10)?;
-    let lessons = query_lessons(conn, 15)?;
-    let memories = query_active_memories(conn, 15)?;
+    let lessons = query_lessons(conn, budget.candidates(cfg.limit("context.lessons")))?;
+    let memories = query_active_memories(conn, budget.candidates(cfg.limit("context.memories")))?;
+    let needs_human = query_needs_human(conn)?;
+    let review_queue = query_review_queue(conn)?;
+    #[allow(clippy::type_complexity)]
+    let mut blocked: Vec<(String, String, Vec<(String, String, String)>)> = Vec::new();
+    for g in &active_goals {
+        budget.charge(&format!("{} {} {}", g.name, g.why_, g.id));
+        for task in get_tasks_for_goal(conn, &g.id)?
+            .into_iter()
+            .filter(|t| t.status == "todo" || t.status == "in_progress")
+        {
+            budget.charge(&format!("{} {}", task.title, task.id));
+            if task.status != "todo" {
+                continue;
+            }
+            let blockers = query_task_blockers(conn, &task.id)?;
+            if !blockers.is_empty() {
+                blocked.push((task.id, task.title, blockers));
+            }
+        }
+    }
+    for n in &needs_human {
+        budget.charge(&format!("{} {} {}", n.0, n.1, n.3));
+    }
+    for r in &review_queue {
+        budget.charge(&format!("{} {} {}", r.0, r.1, r.3));
+    }
+
+    // Goals, in-progress work and blocked tasks are always shown; the rest is filled
+    // in priority order until the budget runs out.
+    let lessons = budget.fit("verified_lessons", lessons, |l| format!("{} {} {}", l.what_went_wrong, l.correct_behavior, l.verified_by));
+    let decisions = budget.fit("decisions", decisions, |d| format!("{} {} {}", d.0, d.1, d.2));
+    let killed_decisions = budget.fit("killed_decisions", killed_decisions, |d| format!("{} {} {}", d.0, d.1, d.2));
+    let direction = budget.fit("direction", direction, |d| d.0.clone());
+    let founding_intent = budget.fit("product_vision", founding_intent, |d| d.0.clone());
+    let memories = budget.fit("memories", memories, |m| format!("{} {}", m.key, m.value));
 
     if out.is_json() {
         let founding_intent_json: Vec<Value> = founding_intent
@@ -1359,14 +1991,15 @@ fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Re
             .iter()
             .map(|d| json!({"content": d.0, "author": d.1, "created_at": d.2}))
             .collect();
-        let decisions_json: Vec<Value> = decisions
-            .iter()
-            .map(|d| json!({"what": d.0, "why": d.1, "affects": d.2, "created_at": d.3}))
-            .collect();
+        let decisions_json: Vec<Value> = decisions.iter().map(decision_to_value).collect();
         let goals_json: Vec<Value> = active_goals.iter().map(goal_to_value).collect();
         let wip_json: Vec<Value> = wip.iter().map(wip_task_to_value).collect();
         let lessons_json: Vec<Value> = lessons.iter().map(lesson_to_value).collect();
         let memories_json: Vec<Value> = memories.iter().map(memory_to_value).collect();
+        let blocked_json: Vec<Value> = blocked
+            .iter()
+            .map(|b| json!({"id": b.0, "title": b.1, "blocked_by": blockers_to_value(&b.2)}))
+            .collect();
         println!(
             "{}",
             json!({
@@ -1376,8 +2009,12 @@ fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Re
                 "decisions": decisions_json,
                 "goals": goals_json,
                 "wip": wip_json,
+                "blocked": blocked_json,
+                "needs_human": needs_human_to_value(&needs_human),
+                "waiting_for_review": review_queue_to_value(&review_queue),
                 "verified_lessons": lessons_json,
-                "memories": memories_json
+                "memories": memories_json,
+                "budget": budget.to_value()
             })
         );
         return Ok(());
@@ -1411,10 +2048,10 @@ fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Re
         );
         t.section(
             "decisions",
-            &["what", "why", "affects", "created_at"],
+            &["what", "why", "affects", "created_at", "revisit_due"],
             decisions
                 .iter()
-                .map(|d| vec![d.0.clone(), d.1.clone(), d.2.clone(), d.3.to_string()])
+                .map(|d| vec![d.0.clone(), d.1.clone(), d.2.clone(), d.3.to_string(), revisit_due("active", d.4).to_string()])
                 .collect(),
         );
         t.section(
@@ -1439,6 +2076,36 @@ fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Re
                 })
                 .collect(),
         );
+        t.section(
+            "blocked",
+            &["id", "title", "blocked_by"],
+            blocked
+                .iter()
+                .map(|b| {
+                    vec![
+                        b.0.clone(),
+                        b.1.clone(),
+                        b.2.iter().map(|x| x.0.clone()).collect::<Vec<_>>().join(" "),
+                    ]
+                })
+                .collect(),
+        );
+        t.section(
+            "needs_human",
+            &["id", "title", "attempts", "last_failure"],
+            needs_human
+                .iter()
+                .map(|n| vec![n.0.clone(), n.1.clone(), n.2.to_string(), n.3.clone()])
+                .collect(),
+        );
+        t.section(
+            "waiting_for_review",
+            &["id", "title", "submitted_by", "summary", "submitted_at"],
+            review_queue
+                .iter()
+                .map(|r| vec![r.0.clone(), r.1.clone(), r.2.clone(), r.3.clone(), r.4.to_string()])
+                .collect(),
+        );
         t.section(
             "verified_lessons",
             &["what_went_wrong", "correct_behavior", "verified_by", "created_at"],
@@ -1462,10 +2129,12 @@ fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Re
                 .map(|m| vec![m.key.clone(), m.value.clone(), m.typ.clone(), m.source.clone()])
                 .collect(),
         );
+        budget.add_toon(&mut t);
         print!("{}", t.finish());
         return Ok(());
     }
 
+    let task_ids = unique_id_prefixes(conn, "tasks")?;
     println!("## IMI Context");
     println!("What matters right now:\n");
 
@@ -1503,7 +2172,14 @@ fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Re
         println!("  (none)");
     } else {
         for d in &decisions {
-            println!("  {}\n    why: {}\n    affects: {}\n    {} ago", d.0, d.1, d.2, ago(d.3));
+            println!(
+                "  {}\n    why: {}\n    affects: {}\n    {} ago{}",
+                d.0,
+                d.1,
+                d.2,
+                ago(d.3),
+                revisit_label("active", d.4)
+            );
         }
     }
 
@@ -1520,6 +2196,15 @@ fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Re
         }
     }
 
+    if !needs_human.is_empty() {
+        println!();
+        print_needs_human(&task_ids, &needs_human);
+    }
+    if !review_queue.is_empty() {
+        println!();
+        print_review_queue(&task_ids, &review_queue);
+    }
+
     println!("\n## Active goals");
     if active_goals.is_empty() {
         println!("  (none)");
@@ -1545,8 +2230,11 @@ fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Re
                     status_icon(out, &task.status),
                     priority_icon(out, &task.priority),
                     task.title,
-                    task.id
+                    short_id(&task_ids, &task.id)
                 );
+                if let Some(b) = blocked.iter().find(|b| b.0 == task.id) {
+                    println!("      ⛔ blocked by: {}", blockers_label(&task_ids, &b.2));
+                }
             }
         }
     }
@@ -1561,30 +2249,56 @@ fn cmd_context(conn: &Connection, out: OutputCtx, goal_id: Option<String>) -> Re
                 status_icon(out, &t.status),
                 priority_icon(out, &t.priority),
                 t.title,
-                t.id
+                short_id(&task_ids, &t.id)
             );
         }
     }
+    budget.print_human();
 
     Ok(())
 }
 
-fn cmd_context_goal(conn: &Connection, out: OutputCtx, goal_prefix: String) -> Result<(), String> {
+fn cmd_context_goal(conn: &Connection, out: OutputCtx, cfg: &Config, goal_prefix: String, mut budget: ContextBudget) -> Result<(), String> {
     let goal_id = resolve_id_prefix(conn, "goals", &goal_prefix)?
         .ok_or_else(|| format!("goal not found: {goal_prefix}"))?;
     let goal = get_goal(conn, &goal_id)?.ok_or_else(|| "goal not found".to_string())?;
     let tasks = get_tasks_for_goal(conn, &goal_id)?;
-    let memories = query_memories(conn, Some(&goal_id), 30)?;
+    let memories = query_memories(conn, Some(&goal_id), budget.candidates(cfg.limit("context.goal_memories")))?;
+    let decisions = query_linked_decisions(conn, Some(&goal_id), None, budget.candidates(10))?;
+
+    let mut blockers_by_task = Vec::with_capacity(tasks.len());
+    for t in &tasks {
+        blockers_by_task.push(query_task_blockers(conn, &t.id)?);
+    }
+    budget.charge(&format!("{} {} {} {}", goal.name, goal.why_, goal.for_who, goal.success_signal));
+    for t in &tasks {
+        budget.charge(&format!("{} {}", t.title, t.id));
+    }
+    let decisions = budget.fit("decisions", decisions, |d| format!("{} {} {}", d.0, d.1, d.2));
+    let memories = budget.fit("memories", memories, |m| format!("{} {}", m.key, m.value));
 
     if out.is_json() {
-        let tasks_json: Vec<Value> = tasks.iter().map(task_to_value).collect();
+        let tasks_json: Vec<Value> = tasks
+            .iter()
+            .zip(&blockers_by_task)
+            .map(|(t, b)| {
+                let mut v = task_to_value(t);
+                v["blocked_by"] = blockers_to_value(b);
+                v
+            })
+            .collect();
         let memories_json: Vec<Value> = memories.iter().map(memory_to_value).collect();
         println!(
             "{}",
             json!({
                 "goal": goal_to_value(&goal),
                 "tasks": tasks_json,
-                "memories": memories_json
+                "decisions": decisions
+                    .iter()
+                    .map(|d| json!({"what": d.0, "why": d.1, "affects": d.2, "created_at": d.3, "linked": d.4}))
+                    .collect::<Vec<_>>(),
+                "memories": memories_json,
+                "budget": budget.to_value()
             })
         );
         return Ok(());
@@ -1620,6 +2334,14 @@ fn cmd_context_goal(conn: &Connection, out: OutputCtx, goal_prefix: String) -> R
                 })
                 .collect(),
         );
+        t.section(
+            "decisions",
+            &["what", "why", "affects", "linked"],
+            decisions
+                .iter()
+                .map(|d| vec![d.0.clone(), d.1.clone(), d.2.clone(), d.4.to_string()])
+                .collect(),
+        );
         t.section(
             "memories",
             &["key", "value", "type", "source"],
@@ -1628,12 +2350,13 @@ fn cmd_context_goal(conn: &Connection, out: OutputCtx, goal_prefix: String) -> R
                 .map(|m| vec![m.key.clone(), m.value.clone(), m.typ.clone(), m.source.clone()])
                 .collect(),
         );
+        budget.add_toon(&mut t);
         print!("{}", t.finish());
         return Ok(());
     }
 
     println!("## Goal");
-    println!("{} {}  {}", status_icon(out, &goal.status), goal.name, goal.id);
+    println!("{} {}  {}", status_icon(out, &goal.status), goal.name, display_id(conn, "goals", &goal.id)?);
     if !goal.why_.is_empty() {
         println!("why: {}", goal.why_);
     }
@@ -1648,14 +2371,28 @@ fn cmd_context_goal(conn: &Connection, out: OutputCtx, goal_prefix: String) -> R
     if tasks.is_empty() {
         println!("  (none)");
     } else {
-        for t in tasks {
+        let task_ids = unique_id_prefixes(conn, "tasks")?;
+        for (t, blockers) in tasks.iter().zip(&blockers_by_task) {
             println!(
                 "  {} {} {}  {}",
                 status_icon(out, &t.status),
                 priority_icon(out, &t.priority),
                 t.title,
-                t.id
+                short_id(&task_ids, &t.id)
             );
+            if !blockers.is_empty() {
+                println!("    ⛔ blocked by: {}", blockers_label(&task_ids, blockers));
+            }
+        }
+    }
+
+    println!("\n## Decisions");
+    if decisions.is_empty() {
+        println!("  (none)");
+    } else {
+        for (what, why, _, _, linked) in &decisions {
+            let how = if *linked { "" } else { "  (matched by name — link with `imi decision link`)" };
+            println!("  - {what}{how}\n    why: {why}");
         }
     }
 
@@ -1667,22 +2404,33 @@ fn cmd_context_goal(conn: &Connection, out: OutputCtx, goal_prefix: String) -> R
             println!("  [{}] {} = {}", m.typ, m.key, m.value);
         }
     }
+    budget.print_human();
 
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn cmd_next(
     conn: &mut Connection,
     out: OutputCtx,
+    cfg: &Config,
     agent: Option<String>,
     goal_prefix: Option<String>,
+    lease: Option<String>,
+    budget: Option<usize>,
+    force: bool,
 ) -> Result<(), String> {
-    let released = release_stale_locks(conn)?;
+    let mut budget = ContextBudget::new(budget)?;
+    let lease_secs = match lease {
+        Some(l) => parse_duration_secs(&l)?,
+        None => cfg.lease_secs(),
+    };
+    let released = release_stale_locks(conn, cfg)?;
     let goal_filter = if let Some(prefix) = goal_prefix {
         if let Some(goal_id) = resolve_id_prefix(conn, "goals", &prefix)? {
             Some(goal_id)
         } else if let Some(task_id) = resolve_id_prefix(conn, "tasks", &prefix)? {
-            return cmd_start(conn, out, agent, task_id);
+            return cmd_start(conn, out, agent, task_id, lease_secs, force);
         } else {
             return Err(format!("goal or task not found: {prefix}"));
         }
@@ -1691,7 +2439,7 @@ fn cmd_next(
     };
     let agent_id = current_agent(agent.as_deref());
 
-    match claim_next_task(conn, goal_filter.as_deref(), &agent_id)? {
+    match claim_next_task(conn, goal_filter.as_deref(), &agent_id, lease_secs)? {
         ClaimResult::NoTasks => {
             if out.is_json() {
                 println!("{}", json!({"ok": true, "no_tasks": true, "released_stale": released}));
@@ -1703,7 +2451,11 @@ fn cmd_next(
                 if released > 0 {
                     println!("⚠ Released {released} stale in-progress task(s)");
                 }
-                println!("No available tasks to claim (all tasks are done or already locked).");
+                println!("No available tasks to claim (all tasks are done, locked, or waiting on prerequisites).");
+                let needs_human = query_needs_human(conn)?;
+                if !needs_human.is_empty() {
+                    println!("{} task(s) are blocked after repeated failures — see `imi status`.", needs_human.len());
+                }
             }
             Ok(())
         }
@@ -1727,39 +2479,19 @@ fn cmd_next(
                 .goal_id
                 .as_ref()
                 .and_then(|gid| get_goal(conn, gid).ok().flatten());
-            let goal_reasoning_decisions: Vec<(String, String, String, i64)> = if let Some(g) = goal.as_ref()
-            {
-                let mut stmt = conn
-                    .prepare(
-                        "SELECT what, why, COALESCE(affects,''), COALESCE(created_at,0)
-                         FROM decisions
-                         WHERE LOWER(COALESCE(affects,'')) LIKE LOWER(?1)
-                         ORDER BY COALESCE(created_at,0) DESC
-                         LIMIT 2",
-                    )
-                    .map_err(|e| e.to_string())?;
-                let mapped = stmt
-                    .query_map(params![format!("%{}%", g.name)], |r| {
-                        Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?))
-                    })
-                    .map_err(|e| e.to_string())?;
-                mapped
-                    .collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| e.to_string())?
-            } else {
-                vec![]
-            };
-            let decisions = query_decisions(conn, 10)?;
-            let direction = query_direction(conn, Some(now_ts() - 7 * 24 * 3600), 8)?;
-            let lessons = query_lessons(conn, 15)?;
+            let linked_decisions = query_linked_decisions(conn, task.goal_id.as_deref(), Some(&task.id), budget.candidates(3))?;
+            let decisions = query_decisions(conn, budget.candidates(cfg.limit("next.decisions")))?;
+            let direction = query_direction(conn, Some(now_ts() - 7 * 24 * 3600), budget.candidates(cfg.limit("next.direction")))?;
+            let lessons = query_lessons(conn, budget.candidates(cfg.limit("context.lessons")))?;
+            let memory_limit = budget.candidates(cfg.limit("context.memories"));
             let memories = if let Some(gid) = &task.goal_id {
-                query_memories(conn, Some(gid), 15)?
+                query_memories(conn, Some(gid), memory_limit)?
             } else {
-                query_active_memories(conn, 15)?
+                query_active_memories(conn, memory_limit)?
             };
             let last_failure: Option<String> = if let Some(gid) = &task.goal_id {
                 conn.query_row(
-                    "SELECT value FROM memories WHERE type='failure' AND (goal_id=?1 OR task_id IN (SELECT id FROM tasks WHERE goal_id=?1)) ORDER BY created_at DESC LIMIT 1",
+                    &format!("SELECT value FROM memories WHERE type='failure' AND (goal_id=?1 OR task_id IN (SELECT id FROM tasks WHERE goal_id=?1)) AND {MEMORY_LIVE_SQL} ORDER BY created_at DESC LIMIT 1"),
                     params![gid],
                     |r| r.get(0),
                 )
@@ -1767,7 +2499,7 @@ fn cmd_next(
                 .map_err(|e| e.to_string())?
             } else {
                 conn.query_row(
-                    "SELECT value FROM memories WHERE type='failure' ORDER BY created_at DESC LIMIT 1",
+                    &format!("SELECT value FROM memories WHERE type='failure' AND {MEMORY_LIVE_SQL} ORDER BY created_at DESC LIMIT 1"),
                     [],
                     |r| r.get(0),
                 )
@@ -1775,10 +2507,32 @@ fn cmd_next(
                 .map_err(|e| e.to_string())?
             };
 
+            // The task and its goal are always handed out in full. After that the budget
+            // goes to lessons, decisions linked to this goal, this task's checkpoints, the
+            // last failure, human direction, other decisions and finally other memories.
+            budget.charge(&format!(
+                "{} {} {} {} {} {}",
+                task.title, task.why_, task.description, task.context, task.acceptance_criteria, task.relevant_files
+            ));
+            if let Some(g) = goal.as_ref() {
+                budget.charge(&format!("{} {} {} {} {}", g.name, g.why_, g.description, g.for_who, g.success_signal));
+            }
+            let lessons = budget.fit("verified_lessons", lessons, |l| format!("{} {} {}", l.what_went_wrong, l.correct_behavior, l.verified_by));
+            let linked_decisions = budget.fit("decisions_affecting_goal", linked_decisions, |d| format!("{} {} {}", d.0, d.1, d.2));
+            let (checkpoints, other_memories): (Vec<MemoryRow>, Vec<MemoryRow>) = memories
+                .into_iter()
+                .partition(|m| m.typ == "checkpoint" && m.task_id.as_deref() == Some(task.id.as_str()));
+            let checkpoints = budget.fit("checkpoints", checkpoints, |m| m.value.clone());
+            let last_failure = budget.fit("last_failure", last_failure.into_iter().collect(), |f| f.clone()).pop();
+            let direction = budget.fit("direction", direction, |d| d.0.clone());
+            let decisions = budget.fit("decisions", decisions, |d| format!("{} {} {}", d.0, d.1, d.2));
+            let mut memories = budget.fit("memories", other_memories, |m| format!("{} {}", m.key, m.value));
+            memories.extend(checkpoints);
+            memories.sort_by_key(|m| std::cmp::Reverse(m.created_at));
+
             if out.is_json() {
                 let relevant_files: Vec<String> = serde_json::from_str(&task.relevant_files).unwrap_or_default();
                 let tools: Vec<String> = serde_json::from_str(&task.tools).unwrap_or_default();
-                let goal_name = goal.as_ref().map(|g| g.name.clone()).unwrap_or_default();
                 let prior_work_on_goal: Vec<Value> = if let Some(gid) = &task.goal_id {
                     let mut stmt = conn
                         .prepare(
@@ -1797,7 +2551,9 @@ fn cmd_next(
                         .map_err(|e| e.to_string())?
                         .collect::<Result<Vec<_>, _>>()
                         .map_err(|e| e.to_string())?;
-                    rows.into_iter()
+                    budget
+                        .fit("prior_work_on_goal", rows, |r| format!("{} {}", r.1, r.2))
+                        .into_iter()
                         .map(|(task_id, title, summary, created_at)| {
                             json!({"task_id": task_id, "title": title, "completion_summary": summary, "created_at": created_at})
                         })
@@ -1805,35 +2561,14 @@ fn cmd_next(
                 } else {
                     Vec::new()
                 };
-                let decisions_affecting_goal: Vec<Value> = if goal_name.is_empty() {
-                    Vec::new()
-                } else {
-                    let mut stmt = conn
-                        .prepare(
-                            "SELECT COALESCE(what,''), COALESCE(why,''), COALESCE(affects,''), COALESCE(created_at,0)
-                             FROM decisions
-                             WHERE COALESCE(affects,'') LIKE ?1
-                             ORDER BY COALESCE(created_at,0) DESC
-                             LIMIT 3",
-                        )
-                        .map_err(|e| e.to_string())?;
-                    let pattern = format!("%{}%", goal_name);
-                    let rows: Vec<(String, String, String, i64)> = stmt
-                        .query_map(params![pattern], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
-                        .map_err(|e| e.to_string())?
-                        .collect::<Result<Vec<_>, _>>()
-                        .map_err(|e| e.to_string())?;
-                    rows.into_iter()
-                        .map(|(what, why, affects, created_at)| {
-                            json!({"what": what, "why": why, "affects": affects, "created_at": created_at})
-                        })
-                        .collect()
-                };
-                let goal_json = goal.as_ref().map(goal_to_value);
-                let decisions_json: Vec<Value> = decisions
+                let decisions_affecting_goal: Vec<Value> = linked_decisions
                     .iter()
-                    .map(|d| json!({"what": d.0, "why": d.1, "affects": d.2, "created_at": d.3}))
+                    .map(|(what, why, affects, created_at, linked)| {
+                        json!({"what": what, "why": why, "affects": affects, "created_at": created_at, "linked": linked})
+                    })
                     .collect();
+                let goal_json = goal.as_ref().map(goal_to_value);
+                let decisions_json: Vec<Value> = decisions.iter().map(decision_to_value).collect();
                 let direction_json: Vec<Value> = direction
                     .iter()
                     .map(|d| json!({"content": d.0, "author": d.1, "created_at": d.2}))
@@ -1845,6 +2580,7 @@ fn cmd_next(
                     json!({
                         "ok": true,
                         "released_stale": released,
+                        "lease_seconds": lease_secs,
                         "verified_lessons": lessons_json,
                         "task": {
                             "id": task.id,
@@ -1865,7 +2601,8 @@ fn cmd_next(
                         "decisions": decisions_json,
                         "direction": direction_json,
                         "last_failure": last_failure,
-                        "memories": memories_json
+                        "memories": memories_json,
+                        "budget": budget.to_value()
                     })
                 );
                 return Ok(());
@@ -1918,11 +2655,11 @@ fn cmd_next(
                         why_this_matters.push_str(&format!("- Goal description: {}\n", g.description));
                     }
                 }
-                if goal_reasoning_decisions.is_empty() {
+                if linked_decisions.is_empty() {
                     why_this_matters.push_str("- Related decisions: none\n");
                 } else {
                     why_this_matters.push_str("- Related decisions:\n");
-                    for d in &goal_reasoning_decisions {
+                    for d in &linked_decisions {
                         why_this_matters.push_str(&format!("  - {} (why: {})\n", d.0, d.1));
                     }
                 }
@@ -1961,6 +2698,7 @@ fn cmd_next(
                         .map(|m| vec![m.key.clone(), m.value.clone(), m.typ.clone()])
                         .collect(),
                 );
+                budget.add_toon(&mut t);
                 print!("{}", t.finish());
                 return Ok(());
             }
@@ -1981,8 +2719,11 @@ fn cmd_next(
                 }
                 println!();
             }
-            println!("🔒 Task claimed and locked to this agent");
-            println!("ID: {}  {}", task.id, task.title);
+            println!(
+                "🔒 Task claimed and locked to this agent for {} (renew with `imi ping`)",
+                format_duration(lease_secs)
+            );
+            println!("ID: {}  {}", display_id(conn, "tasks", &task.id)?, task.title);
             if !task.why_.is_empty() {
                 println!("Why: {}", task.why_);
             }
@@ -2020,20 +2761,28 @@ fn cmd_next(
             if !decisions.is_empty() {
                 println!("\nDecisions:");
                 for d in decisions.iter().take(5) {
-                    println!("  - {} — {}", d.0, d.1);
+                    println!("  - {} — {}{}", d.0, d.1, revisit_label("active", d.4));
                 }
             }
             if let Some(failure) = last_failure {
                 println!("\nLast failure: {}", failure);
             }
+            budget.print_human();
             Ok(())
         }
     }
 }
 
-fn cmd_start(conn: &Connection, out: OutputCtx, agent: Option<String>, task_id: String) -> Result<(), String> {
+fn cmd_start(
+    conn: &Connection,
+    out: OutputCtx,
+    agent: Option<String>,
+    task_id: String,
+    lease_secs: i64,
+    force: bool,
+) -> Result<(), String> {
     let agent_id = current_agent(agent.as_deref());
-    let task = ensure_task_in_progress(conn, &task_id, &agent_id)?;
+    let task = ensure_task_in_progress(conn, &task_id, &agent_id, lease_secs, force)?;
 
     emit_simple_ok(out, &format!("Task {id} is now in progress and locked to this agent", id = task.id))?;
     Ok(())
@@ -2088,13 +2837,14 @@ fn spawn_lifecycle_watchdog(
                 if let Ok(conn) = open_connection(&db_path) {
                     let updated = conn
                         .execute(
-                            "UPDATE tasks SET updated_at=?1, last_ping_at=?1 WHERE id=?2 AND status='in_progress'",
+                            "UPDATE tasks SET updated_at=?1, last_ping_at=?1, lease_expires_at=?1 + lease_seconds WHERE id=?2 AND status='in_progress'",
                             params![now, task_id.clone()],
                         )
                         .unwrap_or(0);
                     if updated == 0 {
                         break;
                     }
+                    log_event(&conn, "ping", Some(&task_id), goal_id.as_deref(), Some(&agent_id), 0);
                 }
                 last_ping = Instant::now();
             }
@@ -2109,11 +2859,11 @@ fn spawn_lifecycle_watchdog(
                     let _ = conn.execute(
                         "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
                          VALUES (?1, ?2, ?3, 'checkpoint', ?4, 'checkpoint', ?4, ?5, ?6)",
-                        params![gen_id(), goal_id.clone(), task_id.clone(), note, agent_id.clone(), now],
+                        params![gen_typed_id("m"), goal_id.clone(), task_id.clone(), note, agent_id.clone(), now],
                     );
                     let updated = conn
                         .execute(
-                            "UPDATE tasks SET updated_at=?1, last_ping_at=?1 WHERE id=?2 AND status='in_progress'",
+                            "UPDATE tasks SET updated_at=?1, last_ping_at=?1, lease_expires_at=?1 + lease_seconds WHERE id=?2 AND status='in_progress'",
                             params![now, task_id.clone()],
                         )
                         .unwrap_or(0);
@@ -2133,12 +2883,30 @@ fn spawn_lifecycle_watchdog(
     })
 }
 
-fn ensure_task_in_progress(conn: &Connection, task_id: &str, agent_id: &str) -> Result<TaskRow, String> {
+fn ensure_task_in_progress(
+    conn: &Connection,
+    task_id: &str,
+    agent_id: &str,
+    lease_secs: i64,
+    force: bool,
+) -> Result<TaskRow, String> {
     let mut task = resolve_task(conn, task_id)?;
     let was_in_progress = task.status == "in_progress";
     if task.status == "done" {
         return Err("task is already done".to_string());
     }
+    if task.status == "review" {
+        return Err(format!(
+            "task is waiting for review — `imi approve {0}` or `imi reject {0} \"reason\"` first",
+            task.id
+        ));
+    }
+    if task.status == "blocked" {
+        return Err(format!(
+            "task is blocked after repeated failures — `imi unblock {} \"what changed\"` first",
+            task.id
+        ));
+    }
     if task.status == "in_progress" {
         if let Some(owner) = &task.agent_id {
             if !owner.is_empty() && owner != agent_id {
@@ -2146,11 +2914,23 @@ fn ensure_task_in_progress(conn: &Connection, task_id: &str, agent_id: &str) ->
             }
         }
     }
+    if !was_in_progress && !force {
+        let blockers = query_task_blockers(conn, &task.id)?;
+        if !blockers.is_empty() {
+            let task_ids = unique_id_prefixes(conn, "tasks")?;
+            return Err(format!(
+                "task is waiting on prerequisites: {} — finish them first or pass --force",
+                blockers_label(&task_ids, &blockers)
+            ));
+        }
+    }
 
     let now = now_ts();
     conn.execute(
-        "UPDATE tasks SET status='in_progress', agent_id=?1, updated_at=?2, last_ping_at=?2 WHERE id=?3",
-        params![agent_id, now, task.id],
+        "UPDATE tasks SET status='in_progress', agent_id=?1, updated_at=?2, last_ping_at=?2,
+                lease_seconds=?4, lease_expires_at=?2 + ?4
+         WHERE id=?3",
+        params![agent_id, now, task.id, lease_secs],
     )
     .map_err(|e| e.to_string())?;
     if let Some(goal_id) = &task.goal_id {
@@ -2161,7 +2941,7 @@ fn ensure_task_in_progress(conn: &Connection, task_id: &str, agent_id: &str) ->
         conn.execute(
             "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
              VALUES (?1, ?2, ?3, 'task_started', ?4, 'lifecycle', ?4, ?5, ?6)",
-            params![gen_id(), task.goal_id.clone(), task.id.clone(), note, agent_id, now],
+            params![gen_typed_id("m"), task.goal_id.clone(), task.id.clone(), note, agent_id, now],
         )
         .map_err(|e| e.to_string())?;
     }
@@ -2185,20 +2965,36 @@ Future-agent note: Run `imi check <task_id>` and confirm criteria coverage befor
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn cmd_complete(
     conn: &Connection,
     out: OutputCtx,
+    cfg: &Config,
     agent: Option<String>,
     task_id: String,
     summary: String,
     interpretation: Option<String>,
     uncertainty: Option<String>,
     outcome: Option<String>,
+    verify: bool,
 ) -> Result<(), String> {
     let agent_id = current_agent(agent.as_deref());
     let task = resolve_task(conn, &task_id)?;
-    let now = now_ts();
-    let summary_text = if summary.trim().is_empty() {
+    if verify {
+        let results = run_acceptance_checks(conn, cfg, &task.id, &agent_id)?;
+        if results.is_empty() {
+            if !out.is_json() {
+                println!("⚠  --verify: task has no acceptance checks (add some with `imi verify {} --add`)", task.id);
+            }
+        } else if results.iter().any(|r| !r.1) {
+            return Err(format!(
+                "acceptance checks failed — task not completed:\n{}",
+                results.iter().map(check_result_line).collect::<Vec<_>>().join("\n")
+            ));
+        }
+    }
+    let now = now_ts();
+    let summary_text = if summary.trim().is_empty() {
         "completed".to_string()
     } else {
         summary
@@ -2219,17 +3015,26 @@ fn cmd_complete(
         }
     }
 
-    conn.execute(
-        "UPDATE tasks SET status='done', summary=?1, agent_id=?2, updated_at=?3, completed_at=?3 WHERE id=?4",
-        params![summary_text, agent_id, now, task.id],
-    )
-    .map_err(|e| e.to_string())?;
+    let needs_review = task_requires_review(conn, &task.id)?;
+    if needs_review {
+        conn.execute(
+            "UPDATE tasks SET status='review', summary=?1, agent_id=?2, updated_at=?3, lease_expires_at=NULL WHERE id=?4",
+            params![summary_text, agent_id, now, task.id],
+        )
+        .map_err(|e| e.to_string())?;
+    } else {
+        conn.execute(
+            "UPDATE tasks SET status='done', summary=?1, agent_id=?2, updated_at=?3, completed_at=?3 WHERE id=?4",
+            params![summary_text, agent_id, now, task.id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
 
     conn.execute(
         "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
          VALUES (?1, ?2, ?3, 'completion_summary', ?4, 'completion', ?4, ?5, ?6)",
         params![
-            gen_id(),
+            gen_typed_id("m"),
             task.goal_id,
             task.id,
             summary_text,
@@ -2238,12 +3043,16 @@ fn cmd_complete(
         ],
     )
     .map_err(|e| e.to_string())?;
-    let completion_note = format!("Task completed by {agent_id}");
+    let completion_note = if needs_review {
+        format!("Task completed by {agent_id}, waiting for review")
+    } else {
+        format!("Task completed by {agent_id}")
+    };
     conn.execute(
         "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
          VALUES (?1, ?2, ?3, 'task_completed', ?4, 'lifecycle', ?4, ?5, ?6)",
         params![
-            gen_id(),
+            gen_typed_id("m"),
             task.goal_id.clone(),
             task.id.clone(),
             completion_note,
@@ -2258,7 +3067,7 @@ fn cmd_complete(
             conn.execute(
                 "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
                  VALUES (?1, ?2, ?3, 'interpretation', ?4, 'completion', ?4, ?5, ?6)",
-                params![gen_id(), task.goal_id, task.id, interp, agent_id, now],
+                params![gen_typed_id("m"), task.goal_id, task.id, interp, agent_id, now],
             )
             .map_err(|e| e.to_string())?;
         }
@@ -2269,7 +3078,7 @@ fn cmd_complete(
             conn.execute(
                 "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
                  VALUES (?1, ?2, ?3, 'uncertainty', ?4, 'completion', ?4, ?5, ?6)",
-                params![gen_id(), task.goal_id, task.id, unc, agent_id, now],
+                params![gen_typed_id("m"), task.goal_id, task.id, unc, agent_id, now],
             )
             .map_err(|e| e.to_string())?;
         }
@@ -2280,34 +3089,157 @@ fn cmd_complete(
             conn.execute(
                 "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
                  VALUES (?1, ?2, ?3, 'outcome', ?4, 'outcome', ?4, ?5, ?6)",
-                params![gen_id(), task.goal_id, task.id, out_note, agent_id, now],
+                params![gen_typed_id("m"), task.goal_id, task.id, out_note, agent_id, now],
             )
             .map_err(|e| e.to_string())?;
         }
     }
 
     if let Some(ref goal_id) = task.goal_id {
-        sync_goal(conn, goal_id)?;
-        // auto-archive when all tasks under the goal are done
-        let goal_status: String = conn
-            .query_row("SELECT status FROM goals WHERE id=?1", params![goal_id], |r| r.get(0))
-            .map_err(|e| e.to_string())?;
-        if goal_status == "done" {
-            conn.execute(
-                "UPDATE goals SET status='archived', updated_at=?1 WHERE id=?2",
-                params![now, goal_id],
-            )
-            .map_err(|e| e.to_string())?;
-            if !out.is_json() {
-                println!("🗂  Goal complete — auto-archived. Run `imi goals --archived` to see it.");
-            }
-        }
+        sync_goal_and_archive(conn, out, goal_id, now)?;
+    }
+
+    if needs_review {
+        emit_simple_ok(
+            out,
+            &format!(
+                "🔎 Task submitted for review and completion summary saved — `imi approve {0}` or `imi reject {0} \"reason\"`",
+                task.id
+            ),
+        )?;
+    } else {
+        emit_simple_ok(out, "✅ Task marked done and completion summary saved")?;
     }
+    Ok(())
+}
 
-    emit_simple_ok(out, "✅ Task marked done and completion summary saved")?;
+/// Sync the goal's status and auto-archive it once every task under it is done.
+fn sync_goal_and_archive(conn: &Connection, out: OutputCtx, goal_id: &str, now: i64) -> Result<(), String> {
+    sync_goal(conn, goal_id)?;
+    let goal_status: String = conn
+        .query_row("SELECT status FROM goals WHERE id=?1", params![goal_id], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    if goal_status == "done" {
+        conn.execute(
+            "UPDATE goals SET status='archived', updated_at=?1, archived_at=?1 WHERE id=?2",
+            params![now, goal_id],
+        )
+        .map_err(|e| e.to_string())?;
+        if !out.is_json() {
+            println!("🗂  Goal complete — auto-archived. Run `imi goals --archived` to see it.");
+        }
+    }
     Ok(())
 }
 
+/// A task's own requires_review wins; NULL falls back to its goal's setting.
+fn task_requires_review(conn: &Connection, task_id: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT COALESCE(t.requires_review, g.requires_review, 0) FROM tasks t LEFT JOIN goals g ON g.id = t.goal_id WHERE t.id=?1",
+        params![task_id],
+        |r| r.get::<_, i64>(0),
+    )
+    .map(|v| v != 0)
+    .map_err(|e| e.to_string())
+}
+
+fn resolve_review_task(conn: &Connection, task_id: &str) -> Result<TaskRow, String> {
+    let task = resolve_task(conn, task_id)?;
+    if task.status != "review" {
+        return Err(format!("task {} is {}, not waiting for review", task.id, task.status));
+    }
+    Ok(task)
+}
+
+fn cmd_approve(conn: &Connection, out: OutputCtx, agent: Option<String>, task_id: String, note: String) -> Result<(), String> {
+    let reviewer = current_agent(agent.as_deref());
+    let task = resolve_review_task(conn, &task_id)?;
+    let now = now_ts();
+    conn.execute(
+        "UPDATE tasks SET status='done', reviewed_by=?1, reviewed_at=?2, updated_at=?2, completed_at=?2 WHERE id=?3",
+        params![reviewer, now, task.id],
+    )
+    .map_err(|e| e.to_string())?;
+    let value = if note.trim().is_empty() {
+        format!("Approved by {reviewer}")
+    } else {
+        format!("Approved by {reviewer}: {note}")
+    };
+    conn.execute(
+        "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
+         VALUES (?1, ?2, ?3, 'review_approved', ?4, 'lifecycle', ?4, ?5, ?6)",
+        params![gen_typed_id("m"), task.goal_id, task.id, value, reviewer, now],
+    )
+    .map_err(|e| e.to_string())?;
+    if let Some(goal_id) = &task.goal_id {
+        sync_goal_and_archive(conn, out, goal_id, now)?;
+    }
+    emit_simple_ok(out, &format!("✅ Task {} approved by {reviewer} and marked done", task.id))
+}
+
+fn cmd_reject(conn: &Connection, out: OutputCtx, agent: Option<String>, task_id: String, reason: String) -> Result<(), String> {
+    if reason.trim().is_empty() {
+        return Err("say what needs to change: imi reject <task_id> \"reason\"".to_string());
+    }
+    let reviewer = current_agent(agent.as_deref());
+    let task = resolve_review_task(conn, &task_id)?;
+    let now = now_ts();
+    conn.execute(
+        "UPDATE tasks SET status='todo', agent_id=NULL, reviewed_by=?1, reviewed_at=?2, updated_at=?2 WHERE id=?3",
+        params![reviewer, now, task.id],
+    )
+    .map_err(|e| e.to_string())?;
+    // Stored as a failure so the next claimer gets it as last_failure.
+    conn.execute(
+        "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
+         VALUES (?1, ?2, ?3, 'review_rejected', ?4, 'failure', ?4, ?5, ?6)",
+        params![gen_typed_id("m"), task.goal_id, task.id, format!("Rejected in review by {reviewer}: {reason}"), reviewer, now],
+    )
+    .map_err(|e| e.to_string())?;
+    if let Some(goal_id) = &task.goal_id {
+        sync_goal(conn, goal_id)?;
+    }
+    emit_simple_ok(out, &format!("↩️  Task {} rejected by {reviewer} and back in 📋 todo", task.id))
+}
+
+/// Tasks sitting in review, as (id, title, submitted by, summary, submitted at).
+#[allow(clippy::type_complexity)]
+fn query_review_queue(conn: &Connection) -> Result<Vec<(String, String, String, String, i64)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, COALESCE(agent_id,''), COALESCE(summary,''), COALESCE(updated_at, created_at, 0)
+             FROM tasks WHERE status = 'review'
+             ORDER BY COALESCE(updated_at, created_at, 0) ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn review_queue_to_value(rows: &[(String, String, String, String, i64)]) -> Value {
+    json!(rows
+        .iter()
+        .map(|(id, title, agent, summary, at)| json!({"id": id, "title": title, "submitted_by": agent, "summary": summary, "submitted_at": at}))
+        .collect::<Vec<_>>())
+}
+
+fn print_review_queue(task_ids: &BTreeMap<String, String>, rows: &[(String, String, String, String, i64)]) {
+    println!("## Waiting for review");
+    for (id, title, agent, summary, at) in rows {
+        let id = short_id(task_ids, id);
+        let by = if agent.is_empty() { String::new() } else { format!(" by {agent}") };
+        println!("  🔎 {title}  {id}  (submitted{by} {})", ago(*at));
+        if !summary.is_empty() {
+            println!("    summary: {summary}");
+        }
+    }
+    println!("  → imi approve <id>  |  imi reject <id> \"reason\"");
+}
+
 fn build_task_context(conn: &Connection, db_path: &Path, task_id: &str) -> Result<PathBuf, String> {
     let task: (String, String, String, String, String, String, String, String) = conn
         .query_row(
@@ -2330,7 +3262,7 @@ fn build_task_context(conn: &Connection, db_path: &Path, task_id: &str) -> Resul
             |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?, r.get::<_, String>(2)?)),
         ).optional().map_err(|e| e.to_string())?
     };
-    let (goal_name, goal_description, goal_why) = goal.unwrap_or_else(|| ("".to_string(), "".to_string(), "".to_string()));
+    let (_, goal_description, goal_why) = goal.unwrap_or_else(|| ("".to_string(), "".to_string(), "".to_string()));
 
     let prior_work_rows: Vec<(String, String, String, i64)> = if task.7.is_empty() { Vec::new() } else {
         let mut stmt = conn.prepare(
@@ -2347,17 +3279,9 @@ fn build_task_context(conn: &Connection, db_path: &Path, task_id: &str) -> Resul
         prior_work_rows.iter().map(|(tid, title, summary, _)| format!("- **{title}** ({tid}): {summary}")).collect::<Vec<_>>().join("\n")
     };
 
-    let goal_decisions_rows: Vec<(String, String, String)> = if goal_name.is_empty() { Vec::new() } else {
-        let mut stmt = conn.prepare(
-            "SELECT COALESCE(what,''), COALESCE(why,''), COALESCE(affects,'') FROM decisions WHERE COALESCE(affects,'') LIKE ?1 ORDER BY COALESCE(created_at,0) DESC LIMIT 3",
-        ).map_err(|e| e.to_string())?;
-        let pat = format!("%{}%", goal_name);
-        let rows: Vec<(String, String, String)> = stmt.query_map(params![pat], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
-            .map_err(|e| e.to_string())?.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
-        rows
-    };
+    let goal_decisions_rows = query_linked_decisions(conn, Some(task.7.as_str()).filter(|g| !g.is_empty()), Some(&task.0), 3)?;
     let goal_decisions_text = if goal_decisions_rows.is_empty() { "- (none)".to_string() } else {
-        goal_decisions_rows.iter().map(|(what, why, affects)| format!("- **{what}** — {why} (affects: {affects})")).collect::<Vec<_>>().join("\n")
+        goal_decisions_rows.iter().map(|(what, why, affects, _, _)| format!("- **{what}** — {why} (affects: {affects})")).collect::<Vec<_>>().join("\n")
     };
 
     let imi_dir = db_path.parent().ok_or_else(|| "invalid db path".to_string())?;
@@ -2381,259 +3305,322 @@ fn build_task_context(conn: &Connection, db_path: &Path, task_id: &str) -> Resul
     Ok(run_dir)
 }
 
-fn cmd_run(
-    conn: &Connection,
-    db_path: &Path,
-    out: OutputCtx,
+/// One task run as a worker backend sees it. `context.md` and anything the backend
+/// writes in `prepare` live in `run_dir`, which is `.imi/runs/<task_id>`.
+struct WorkerRun {
     task_id: String,
-    model: Option<String>,
-) -> Result<(), String> {
-    let id = resolve_id_prefix(conn, "tasks", &task_id)?
-        .ok_or_else(|| format!("No task with ID '{task_id}' — run `imi tasks` to list available tasks"))?;
-    let agent_id = current_agent(None);
-    let claimed = ensure_task_in_progress(conn, &id, &agent_id)?;
-
-    let run_dir = build_task_context(conn, db_path, &id)?;
+    title: String,
+    agent_id: String,
+    model: String,
+    run_dir: PathBuf,
+    context_file: PathBuf,
+    workspace: Option<PathBuf>,
+    limits: WorkerLimits,
+}
 
-    let selected_model = model.unwrap_or_else(|| "claude-sonnet-4-5".to_string());
-    let hank_json = json!({
-        "globalSystemPromptFile": "../../prompts/execute-mode.md",
-        "context": fs::read_to_string(run_dir.join("context.md")).map_err(|e| format!("failed to read context.md: {e}"))?,
-        "codons": [
-            {
-                "model": selected_model,
-                "promptFile": "context.md",
-                "continuationMode": "fresh"
-            }
-        ]
-    });
-    fs::write(
-        run_dir.join("hank.json"),
-        serde_json::to_string_pretty(&hank_json).map_err(|e| e.to_string())?,
-    )
-    .map_err(|e| format!("failed to write hank.json: {e}"))?;
+/// Caps on one worker run. The wall-clock timeout is enforced by `run_worker`; CPU and
+/// memory become `ulimit` calls in front of the worker's command.
+#[derive(Clone, Default)]
+struct WorkerLimits {
+    timeout_secs: Option<i64>,
+    cpu_secs: Option<i64>,
+    memory_kb: Option<u64>,
+}
 
-    let watchdog = spawn_lifecycle_watchdog(
-        db_path.to_path_buf(),
-        claimed.id.clone(),
-        claimed.goal_id.clone(),
-        agent_id.clone(),
-        300,
-        900,
-    );
-    let status = Command::new("bunx")
-        .arg("hankweave")
-        .current_dir(&run_dir)
-        .stdin(std::process::Stdio::inherit())
-        .stdout(std::process::Stdio::inherit())
-        .stderr(std::process::Stdio::inherit())
-        .status();
-    if let Some(watchdog) = watchdog {
-        watchdog.stop();
+impl WorkerLimits {
+    /// Without `--task-timeout` the `worker.task_timeout` setting applies; 0 means none.
+    fn parse(cfg: &Config, timeout: Option<&str>, cpu: Option<&str>, memory: Option<&str>) -> Result<Self, String> {
+        let configured = Some(cfg.u64("worker.task_timeout") as i64).filter(|secs| *secs > 0);
+        Ok(Self {
+            timeout_secs: timeout.map(parse_duration_secs).transpose()?.or(configured),
+            cpu_secs: cpu.map(parse_duration_secs).transpose()?,
+            memory_kb: memory.map(parse_size_kb).transpose()?,
+        })
     }
-    let status = match status {
-        Ok(status) => status,
-        Err(e) if e.kind() == io::ErrorKind::NotFound => {
-            let reason = "hankweave not found. Install with: npm install -g hankweave".to_string();
-            if task_status(conn, &claimed.id).unwrap_or_default() == "in_progress" {
-                let _ = cmd_fail(conn, out, Some(agent_id.clone()), claimed.id.clone(), reason.clone());
-            }
-            return Err(reason);
-        }
-        Err(e) => {
-            let reason = format!("failed to run hankweave: {e}");
-            if task_status(conn, &claimed.id).unwrap_or_default() == "in_progress" {
-                let _ = cmd_fail(conn, out, Some(agent_id.clone()), claimed.id.clone(), reason.clone());
-            }
-            return Err(reason);
-        }
+}
+
+/// `512M`, `2G`, `800K` or a plain byte count, in KiB as `ulimit -d` wants it. The limit is
+/// RLIMIT_DATA rather than `ulimit -v`: JIT runtimes like Node and Bun reserve gigabytes of
+/// address space up front and fail to start under a virtual memory cap.
+fn parse_size_kb(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let bad = || format!("invalid size '{s}' (use e.g. 800K, 512M, 2G)");
+    let (num, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, ""),
     };
-    if !status.success() {
-        let reason = format!("hankweave exited with status: {status}");
-        if task_status(conn, &claimed.id).unwrap_or_default() == "in_progress" {
-            let _ = cmd_fail(conn, out, Some(agent_id.clone()), claimed.id.clone(), reason.clone());
-        }
-        return Err(reason);
+    let n: u64 = num.parse().map_err(|_| bad())?;
+    let kb = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => n / 1024,
+        "K" | "KB" => n,
+        "M" | "MB" => n * 1024,
+        "G" | "GB" => n * 1024 * 1024,
+        _ => return Err(bad()),
+    };
+    if kb == 0 {
+        return Err(bad());
     }
+    Ok(kb)
+}
 
-    if task_status(conn, &claimed.id).unwrap_or_default() == "done" {
-        emit_simple_ok(out, "Task already completed")?;
-        return Ok(());
+/// What a worker left behind. `detail` explains the exit: it becomes the failure reason,
+/// or the auto-summary when the worker wrote no `summary.md`.
+struct WorkerResult {
+    success: bool,
+    detail: String,
+    summary: Option<String>,
+    artifacts: Vec<PathBuf>,
+}
+
+/// An agent CLI imi can hand a task to. `prepare` writes anything the worker reads
+/// besides `context.md`, `launch` builds the process, and `collect` turns its exit
+/// status and run dir into a result.
+trait WorkerBackend {
+    fn name(&self) -> &str;
+
+    fn prepare(&self, _run: &WorkerRun) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn launch(&self, run: &WorkerRun) -> Command;
+
+    fn start_error(&self, e: &io::Error) -> String {
+        format!("worker backend '{}' failed to start: {e}", self.name())
+    }
+
+    fn collect(&self, run: &WorkerRun, status: std::process::ExitStatus) -> WorkerResult {
+        let detail = if status.success() {
+            format!("worker backend '{}' succeeded", self.name())
+        } else {
+            format!("worker backend '{}' exited with status {status}", self.name())
+        };
+        collect_run_outputs(run, status.success(), detail)
     }
+}
 
-    let summary = fs::read_to_string(run_dir.join("summary.md"))
+/// `summary.md` and the files under `artifacts/` in the run dir, whichever the worker wrote.
+fn collect_run_outputs(run: &WorkerRun, success: bool, detail: String) -> WorkerResult {
+    let summary = fs::read_to_string(run.run_dir.join("summary.md"))
         .ok()
         .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .unwrap_or_else(|| runtime_completion_summary("run", "no summary.md provided by worker"));
-    cmd_complete(conn, out, Some(agent_id), claimed.id, summary, None, None, None)
+        .filter(|s| !s.is_empty());
+    let mut artifacts = Vec::new();
+    let mut pending = vec![run.run_dir.join("artifacts")];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                artifacts.push(path);
+            }
+        }
+    }
+    artifacts.sort();
+    WorkerResult { success, detail, summary, artifacts }
 }
 
-fn cmd_wrap(
-    conn: &Connection,
-    db_path: &Path,
-    out: OutputCtx,
-    agent: Option<String>,
-    task_id: String,
-    ping_secs: u64,
-    checkpoint_secs: u64,
-    command: Vec<String>,
-) -> Result<(), String> {
-    let agent_id = current_agent(agent.as_deref());
-    let task = ensure_task_in_progress(conn, &task_id, &agent_id)?;
-    let workspace_path: String = conn
-        .query_row(
-            "SELECT COALESCE(workspace_path,'') FROM tasks WHERE id=?1",
-            params![task.id.clone()],
-            |r| r.get(0),
-        )
-        .unwrap_or_default();
+/// Environment every backend's process gets, on top of its own placeholders.
+fn worker_command(run: &WorkerRun, program: &str) -> Command {
+    let mut ulimits = Vec::new();
+    if let Some(cpu) = run.limits.cpu_secs {
+        ulimits.push(format!("ulimit -t {cpu}"));
+    }
+    if let Some(kb) = run.limits.memory_kb {
+        ulimits.push(format!("ulimit -d {kb}"));
+    }
+    // The limits have to be set inside the child, so a shell sets them and execs the worker.
+    let mut cmd = if ulimits.is_empty() {
+        Command::new(program)
+    } else {
+        let mut sh = Command::new("sh");
+        sh.arg("-c").arg(format!("{} && exec \"$@\"", ulimits.join(" && "))).arg("sh").arg(program);
+        sh
+    };
+    cmd.env("IMI_TASK_ID", &run.task_id)
+        .env("IMI_TASK_TITLE", &run.title)
+        .env("IMI_TASK_CONTEXT_FILE", run.context_file.display().to_string())
+        .env("IMI_RUN_DIR", run.run_dir.display().to_string())
+        .env("IMI_MODEL", &run.model)
+        .stdin(std::process::Stdio::inherit())
+        .stdout(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::inherit());
+    if let Some(workspace) = &run.workspace {
+        cmd.current_dir(workspace);
+    }
+    cmd
+}
 
-    // Build context.md so the wrapped command (or hankweave) can read task details
-    let run_dir = build_task_context(conn, db_path, &task.id).ok();
-    let context_file = run_dir.as_ref().map(|d| d.join("context.md"));
+/// The default backend: writes `hank.json` and runs `bunx hankweave` inside the run dir.
+struct HankweaveBackend;
 
-    // No command given — use hankweave (same execution path as imi run)
-    if command.is_empty() {
-        let run_dir = run_dir.ok_or_else(|| "failed to build task context".to_string())?;
+impl WorkerBackend for HankweaveBackend {
+    fn name(&self) -> &str {
+        "hankweave"
+    }
+
+    fn prepare(&self, run: &WorkerRun) -> Result<(), String> {
         let hank_json = json!({
             "globalSystemPromptFile": "../../prompts/execute-mode.md",
-            "context": fs::read_to_string(run_dir.join("context.md")).map_err(|e| format!("failed to read context.md: {e}"))?,
-            "codons": [{ "model": "claude-sonnet-4-5", "promptFile": "context.md", "continuationMode": "fresh" }]
+            "context": fs::read_to_string(&run.context_file).map_err(|e| format!("failed to read context.md: {e}"))?,
+            "codons": [
+                {
+                    "model": run.model,
+                    "promptFile": "context.md",
+                    "continuationMode": "fresh"
+                }
+            ]
         });
         fs::write(
-            run_dir.join("hank.json"),
+            run.run_dir.join("hank.json"),
             serde_json::to_string_pretty(&hank_json).map_err(|e| e.to_string())?,
         )
-        .map_err(|e| format!("failed to write hank.json: {e}"))?;
-        let watchdog = spawn_lifecycle_watchdog(
-            db_path.to_path_buf(), task.id.clone(), task.goal_id.clone(), agent_id.clone(), ping_secs, checkpoint_secs,
-        );
-        let status = Command::new("bunx")
-            .arg("hankweave")
-            .current_dir(&run_dir)
-            .stdin(std::process::Stdio::inherit())
-            .stdout(std::process::Stdio::inherit())
-            .stderr(std::process::Stdio::inherit())
-            .status();
-        if let Some(watchdog) = watchdog { watchdog.stop(); }
-        let status = match status {
-            Ok(s) => s,
-            Err(e) if e.kind() == io::ErrorKind::NotFound => {
-                let reason = "hankweave not found. Install with: npm install -g hankweave".to_string();
-                if task_status(conn, &task.id).unwrap_or_default() == "in_progress" {
-                    let _ = cmd_fail(conn, out, Some(agent_id.clone()), task.id.clone(), reason.clone());
-                }
-                return Err(reason);
-            }
-            Err(e) => {
-                let reason = format!("failed to run hankweave: {e}");
-                if task_status(conn, &task.id).unwrap_or_default() == "in_progress" {
-                    let _ = cmd_fail(conn, out, Some(agent_id.clone()), task.id.clone(), reason.clone());
-                }
-                return Err(reason);
-            }
-        };
-        if !status.success() {
-            let reason = format!("hankweave exited with status: {status}");
-            if task_status(conn, &task.id).unwrap_or_default() == "in_progress" {
-                let _ = cmd_fail(conn, out, Some(agent_id.clone()), task.id.clone(), reason.clone());
-            }
-            return Err(reason);
-        }
-        if task_status(conn, &task.id).unwrap_or_default() == "done" {
-            emit_simple_ok(out, "Task already completed")?;
-            return Ok(());
-        }
-        let summary = fs::read_to_string(run_dir.join("summary.md"))
-            .ok()
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .unwrap_or_else(|| runtime_completion_summary("wrap", "no summary.md provided by worker"));
-        return cmd_complete(conn, out, Some(agent_id), task.id, summary, None, None, None);
+        .map_err(|e| format!("failed to write hank.json: {e}"))
     }
 
-    // Custom command path
-    let first = command.first().unwrap();
-    let watchdog = spawn_lifecycle_watchdog(
-        db_path.to_path_buf(),
-        task.id.clone(),
-        task.goal_id.clone(),
-        agent_id.clone(),
-        ping_secs,
-        checkpoint_secs,
-    );
-
-    let mut child = Command::new(first);
-    child
-        .args(command.iter().skip(1))
-        .env("IMI_TASK_ID", &task.id)
-        .env("IMI_TASK_TITLE", &task.title)
-        .stdin(std::process::Stdio::inherit())
-        .stdout(std::process::Stdio::inherit())
-        .stderr(std::process::Stdio::inherit());
-    if let Some(ref cf) = context_file {
-        child.env("IMI_TASK_CONTEXT_FILE", cf.display().to_string());
+    fn launch(&self, run: &WorkerRun) -> Command {
+        let mut cmd = worker_command(run, "bunx");
+        cmd.arg("hankweave").current_dir(&run.run_dir);
+        cmd
     }
 
-    if !workspace_path.trim().is_empty() {
-        let workspace = PathBuf::from(workspace_path.trim());
-        if workspace.exists() {
-            child.current_dir(workspace);
+    fn start_error(&self, e: &io::Error) -> String {
+        if e.kind() == io::ErrorKind::NotFound {
+            "hankweave not found. Install with: npm install -g hankweave".to_string()
+        } else {
+            format!("failed to run hankweave: {e}")
         }
     }
 
-    let status = child.status();
-    if let Some(watchdog) = watchdog {
-        watchdog.stop();
+    fn collect(&self, run: &WorkerRun, status: std::process::ExitStatus) -> WorkerResult {
+        let detail = if status.success() {
+            "no summary.md provided by worker".to_string()
+        } else {
+            format!("hankweave exited with status: {status}")
+        };
+        collect_run_outputs(run, status.success(), detail)
     }
+}
 
-    let status = match status {
-        Ok(status) => status,
-        Err(e) => {
-            let reason = format!("wrapped command failed to start: {e}");
-            if task_status(conn, &task.id).unwrap_or_default() == "in_progress" {
-                let _ = cmd_fail(conn, out, Some(agent_id.clone()), task.id.clone(), reason.clone());
-            }
-            return Err(reason);
-        }
-    };
+/// A shell command template from config (`[backends.<name>] command = "..."`) or one
+/// of the built-ins. Placeholders are substituted shell-quoted before `sh -c` runs it.
+struct TemplateBackend {
+    name: String,
+    template: String,
+}
 
-    let current_status = task_status(conn, &task.id).unwrap_or_default();
-    if status.success() {
-        if current_status == "done" {
-            emit_simple_ok(out, "Wrapped command succeeded (task already completed)")?;
-            return Ok(());
+/// Placeholders a backend template may use. Anything else in braces, like `${HOME}`, is left for the shell.
+const WORKER_PLACEHOLDERS: &[&str] = &["context_file", "task_id", "title", "model", "run_dir", "workspace", "agent"];
+
+const BUILTIN_BACKENDS: &[(&str, &str)] = &[
+    ("claude", r#"claude -p "$(cat {context_file})" --dangerously-skip-permissions"#),
+    ("codex", r#"codex exec "$(cat {context_file})""#),
+    ("opencode", "opencode"),
+    ("copilot", "gh agent-task create -F {context_file}"),
+];
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+impl TemplateBackend {
+    fn expand(&self, run: &WorkerRun) -> String {
+        let mut out = String::new();
+        let mut rest = self.template.as_str();
+        while let Some(open) = rest.find('{') {
+            out.push_str(&rest[..open]);
+            let after = &rest[open + 1..];
+            let name = after.split_once('}').map(|(name, _)| name).filter(|name| WORKER_PLACEHOLDERS.contains(name));
+            let Some(name) = name else {
+                out.push('{');
+                rest = after;
+                continue;
+            };
+            let value = match name {
+                "context_file" => run.context_file.display().to_string(),
+                "task_id" => run.task_id.clone(),
+                "title" => run.title.clone(),
+                "model" => run.model.clone(),
+                "run_dir" => run.run_dir.display().to_string(),
+                "workspace" => run.workspace.as_ref().unwrap_or(&run.run_dir).display().to_string(),
+                _ => run.agent_id.clone(),
+            };
+            out.push_str(&shell_quote(&value));
+            rest = &after[name.len() + 1..];
         }
-        let summary = runtime_completion_summary("wrap", &format!("command succeeded: {}", command.join(" ")));
-        return cmd_complete(conn, out, Some(agent_id), task.id, summary, None, None, None);
+        out.push_str(rest);
+        out
+    }
+}
+
+impl WorkerBackend for TemplateBackend {
+    fn name(&self) -> &str {
+        &self.name
     }
 
-    let reason = format!("wrapped command exited with status {status}: {}", command.join(" "));
-    if current_status == "in_progress" {
-        let _ = cmd_fail(conn, out, Some(agent_id), task.id, reason.clone());
+    fn launch(&self, run: &WorkerRun) -> Command {
+        let mut cmd = worker_command(run, "sh");
+        cmd.arg("-c").arg(self.expand(run));
+        cmd
     }
-    Err(reason)
 }
 
-struct OrchestrateWorker {
-    task_id: String,
-    agent_id: String,
-    child: Child,
+/// `imi wrap <task> -- <cmd...>`: an explicit argv, run as given in the workspace.
+struct ArgvBackend {
+    argv: Vec<String>,
+}
+
+impl WorkerBackend for ArgvBackend {
+    fn name(&self) -> &str {
+        "command"
+    }
+
+    fn launch(&self, run: &WorkerRun) -> Command {
+        let mut cmd = worker_command(run, &self.argv[0]);
+        cmd.args(&self.argv[1..]);
+        cmd
+    }
+
+    fn start_error(&self, e: &io::Error) -> String {
+        format!("wrapped command failed to start: {e}")
+    }
+
+    fn collect(&self, run: &WorkerRun, status: std::process::ExitStatus) -> WorkerResult {
+        let detail = if status.success() {
+            format!("command succeeded: {}", self.argv.join(" "))
+        } else {
+            format!("wrapped command exited with status {status}: {}", self.argv.join(" "))
+        };
+        collect_run_outputs(run, status.success(), detail)
+    }
+}
+
+/// Backend command templates declared in config, user file first and project file on top.
+fn config_backends(conn: &Connection) -> Result<BTreeMap<String, String>, String> {
+    let mut paths: Vec<PathBuf> = user_config_path().into_iter().collect();
+    if let Some(p) = conn.path().filter(|p| !p.is_empty()) {
+        paths.push(project_config_path(Path::new(p)));
+    }
+    let mut backends = BTreeMap::new();
+    for path in paths {
+        for (key, value) in read_config_file(&path)? {
+            let name = key.strip_prefix("backends.").and_then(|k| k.strip_suffix(".command"));
+            if let Some(name) = name.filter(|n| !n.is_empty()) {
+                backends.insert(name.to_string(), value);
+            }
+        }
+    }
+    Ok(backends)
 }
 
-/// Resolve which CLI command workers should use when no explicit command is given.
-/// - None / "hankweave" → use `imi run` (hankweave, default)
-/// - "auto"             → detect from env vars: Copilot → copilot, Claude Code → claude, OpenCode → opencode, else hankweave
-/// - "claude"           → `sh -c 'claude -p "$(cat "$IMI_TASK_CONTEXT_FILE")" --dangerously-skip-permissions'`
-/// - "opencode"         → `opencode`
-/// - "codex"            → `sh -c 'codex exec "$(cat "$IMI_TASK_CONTEXT_FILE")"'`
-/// - "copilot"          → `sh -c 'gh agent-task create -F "$IMI_TASK_CONTEXT_FILE"'`
-/// Returns None to signal "use imi run / hankweave", or Some(command_vec) to use imi wrap.
-fn resolve_worker_cli(cli: Option<&str>) -> Option<Vec<String>> {
-    let cli = match cli {
-        None | Some("hankweave") => return None,
-        Some("auto") => {
+/// Pick a backend by name, falling back to `worker.backend`. `auto` detects the agent
+/// CLI imi is running under from its env vars and falls back to hankweave. Config
+/// entries win over the built-ins, so `[backends.claude]` replaces the stock template.
+fn resolve_worker_backend(conn: &Connection, cfg: &Config, name: Option<&str>) -> Result<Box<dyn WorkerBackend>, String> {
+    let configured = cfg.value("worker.backend");
+    let name = match name.unwrap_or(&configured) {
+        "auto" => {
             // Prefer the currently active user CLI session when multiple markers are present.
             if env::var("GH_COPILOT_SESSION_ID").is_ok() || env::var("COPILOT_AGENT_SESSION").is_ok() {
                 "copilot"
@@ -2642,91 +3629,527 @@ fn resolve_worker_cli(cli: Option<&str>) -> Option<Vec<String>> {
             } else if env::var("OPENCODE_SESSION").is_ok() {
                 "opencode"
             } else {
-                return None; // fall back to hankweave
+                "hankweave"
             }
         }
-        Some(v) => v,
+        other => other,
     };
-    match cli {
-        "claude" => Some(vec![
-            "sh".into(), "-c".into(),
-            r#"claude -p "$(cat "$IMI_TASK_CONTEXT_FILE")" --dangerously-skip-permissions"#.into(),
-        ]),
-        "opencode" => Some(vec!["opencode".into()]),
-        "codex" => Some(vec![
-            "sh".into(), "-c".into(),
-            r#"codex exec "$(cat "$IMI_TASK_CONTEXT_FILE")""#.into(),
-        ]),
-        "copilot" => Some(vec![
-            "sh".into(), "-c".into(),
-            r#"gh agent-task create -F "$IMI_TASK_CONTEXT_FILE""#.into(),
-        ]),
-        _ => None,
-    }
+    let declared = config_backends(conn)?;
+    if let Some(template) = declared.get(name) {
+        return Ok(Box::new(TemplateBackend { name: name.to_string(), template: template.clone() }));
+    }
+    if name == "hankweave" {
+        return Ok(Box::new(HankweaveBackend));
+    }
+    if let Some((_, template)) = BUILTIN_BACKENDS.iter().find(|(n, _)| *n == name) {
+        return Ok(Box::new(TemplateBackend { name: name.to_string(), template: template.to_string() }));
+    }
+    let mut known: Vec<String> = vec!["auto".into(), "hankweave".into()];
+    known.extend(BUILTIN_BACKENDS.iter().map(|(n, _)| n.to_string()));
+    known.extend(declared.into_keys());
+    known.sort();
+    known.dedup();
+    Err(format!(
+        "unknown worker backend '{name}' (known: {}). Declare one in config.toml under [backends.{name}] with command = \"...\"",
+        known.join(", ")
+    ))
 }
 
-fn spawn_orchestrate_worker(
-    db_path: &Path,
-    task_id: &str,
-    agent_id: &str,
-    ping_secs: u64,
+/// What SIGINT and SIGTERM leave behind instead of killing imi, so run, wrap and
+/// orchestrate can stop their workers and hand claimed tasks back first.
+#[derive(Default)]
+struct Shutdown {
+    signal: AtomicUsize,
+    count: AtomicUsize,
+}
+
+impl Shutdown {
+    fn request(&self, signal: usize) {
+        self.signal.store(signal, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The last signal received, or 0.
+    fn signal(&self) -> usize {
+        self.signal.load(Ordering::Relaxed)
+    }
+
+    /// A second signal skips the grace period: workers are killed straight away.
+    fn forced(&self) -> bool {
+        self.count.load(Ordering::Relaxed) > 1
+    }
+}
+
+fn install_shutdown_handler() -> Arc<Shutdown> {
+    let shutdown = Arc::new(Shutdown::default());
+    for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+        let shutdown = Arc::clone(&shutdown);
+        // SAFETY: the handler only touches atomics, which is async-signal-safe.
+        let _ = unsafe { signal_hook::low_level::register(signal, move || shutdown.request(signal as usize)) };
+    }
+    shutdown
+}
+
+fn signal_name(signal: usize) -> &'static str {
+    match signal as i32 {
+        signal_hook::consts::SIGINT => "SIGINT",
+        signal_hook::consts::SIGTERM => "SIGTERM",
+        _ => "signal",
+    }
+}
+
+/// Send `signal` to `pid`, or to its whole process group when `group` is set. A process
+/// that has already exited is not an error.
+fn send_signal(pid: u32, group: bool, signal: i32) -> io::Result<()> {
+    let pid = libc::pid_t::try_from(pid)
+        .ok()
+        .filter(|p| *p > 0)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("invalid pid {pid}")))?;
+    // SAFETY: kill/killpg take plain integers; a positive pid can't address imi's own group.
+    let rc = unsafe {
+        if group {
+            libc::killpg(pid, signal)
+        } else {
+            libc::kill(pid, signal)
+        }
+    };
+    if rc == 0 {
+        return Ok(());
+    }
+    let err = io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::ESRCH) {
+        Ok(())
+    } else {
+        Err(err)
+    }
+}
+
+/// Make process group `pgid` the terminal's foreground job. SIGTTOU is ignored for the call
+/// so a background group (the worker before exec, wrap once it has exited) may take it.
+fn hand_terminal_to(pgid: libc::pid_t) {
+    unsafe {
+        let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+        libc::tcsetpgrp(libc::STDIN_FILENO, pgid);
+        libc::signal(libc::SIGTTOU, previous);
+    }
+}
+
+/// Wait for a worker, passing a shutdown signal on to it and killing it once the grace
+/// period runs out or a second signal arrives, or as soon as it outlives `timeout_secs`. The worker
+/// leads its own process group, so signals and the kill reach everything it started.
+/// The flag in the result is true when the timeout killed it.
+fn wait_for_worker(
+    child: &mut Child,
+    shutdown: &Shutdown,
+    grace_secs: i64,
+    timeout_secs: Option<i64>,
+) -> Result<(std::process::ExitStatus, bool), String> {
+    let pid = child.id();
+    let started = Instant::now();
+    let mut deadline: Option<Instant> = None;
+    loop {
+        if let Some(status) = child.try_wait().map_err(|e| format!("failed to wait for worker: {e}"))? {
+            return Ok((status, false));
+        }
+        let timed_out = timeout_secs.is_some_and(|t| started.elapsed().as_secs() >= t.max(0) as u64);
+        let signal = shutdown.signal();
+        if timed_out || shutdown.forced() || deadline.is_some_and(|at| Instant::now() >= at) {
+            if send_signal(pid, true, libc::SIGKILL).is_err() {
+                let _ = child.kill();
+            }
+            let status = child.wait().map_err(|e| format!("failed to wait for worker: {e}"))?;
+            return Ok((status, timed_out && signal == 0));
+        }
+        if signal != 0 && deadline.is_none() {
+            // A worker we can't signal gets no grace period.
+            let grace = match send_signal(pid, true, signal as i32) {
+                Ok(()) => grace_secs.max(0) as u64,
+                Err(_) => 0,
+            };
+            deadline = Some(Instant::now() + Duration::from_secs(grace));
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Hand an interrupted task straight back to todo. Unlike a failure it doesn't spend an
+/// attempt; the reason is kept as an `interrupted` failure memory. False if the task was
+/// no longer in progress.
+fn release_interrupted_task(conn: &Connection, agent_id: &str, task_id: &str, reason: &str) -> Result<bool, String> {
+    let task = resolve_task(conn, task_id)?;
+    let now = now_ts();
+    let released = conn
+        .execute(
+            "UPDATE tasks SET status='todo', agent_id=NULL, updated_at=?1 WHERE id=?2 AND status='in_progress'",
+            params![now, task.id],
+        )
+        .map_err(|e| e.to_string())?;
+    if released == 0 {
+        return Ok(false);
+    }
+    conn.execute(
+        "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
+         VALUES (?1, ?2, ?3, 'interrupted', ?4, 'failure', ?4, ?5, ?6)",
+        params![gen_typed_id("m"), task.goal_id, task.id, reason, agent_id, now],
+    )
+    .map_err(|e| e.to_string())?;
+    if let Some(goal_id) = &task.goal_id {
+        sync_goal(conn, goal_id)?;
+    }
+    Ok(true)
+}
+
+/// The shared run/wrap lifecycle: build context, let the backend prepare and launch,
+/// keep the lock alive while it runs, then complete or fail the task from its result.
+#[allow(clippy::too_many_arguments)]
+fn run_worker(
+    conn: &Connection,
+    db_path: &Path,
+    out: OutputCtx,
+    cfg: &Config,
+    mode: &str,
+    task: TaskRow,
+    agent_id: String,
+    backend: &dyn WorkerBackend,
+    model: String,
+    ping_secs: u64,
+    checkpoint_secs: u64,
+    mut limits: WorkerLimits,
+) -> Result<(), String> {
+    let fail_as = |key: &str, reason: String| -> Result<(), String> {
+        if task_status(conn, &task.id).unwrap_or_default() == "in_progress" {
+            let _ = fail_task(conn, out, cfg, Some(agent_id.clone()), task.id.clone(), key, reason.clone());
+        }
+        Err(reason)
+    };
+    let fail = |reason: String| fail_as("failure_reason", reason);
+
+    let run_dir = match build_task_context(conn, db_path, &task.id) {
+        Ok(dir) => dir,
+        Err(e) => return fail(format!("failed to build task context: {e}")),
+    };
+    let (workspace, max_runtime): (String, Option<i64>) = conn
+        .query_row(
+            "SELECT COALESCE(workspace_path,''), max_runtime FROM tasks WHERE id=?1",
+            params![task.id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .unwrap_or_default();
+    // The task's own max_runtime is more specific than --task-timeout, so it wins.
+    let timeout_source = if max_runtime.is_some() { "the task's max_runtime" } else { "--task-timeout or worker.task_timeout" };
+    limits.timeout_secs = max_runtime.or(limits.timeout_secs);
+    let run = WorkerRun {
+        task_id: task.id.clone(),
+        title: task.title.clone(),
+        agent_id: agent_id.clone(),
+        model,
+        context_file: run_dir.join("context.md"),
+        run_dir,
+        workspace: Some(PathBuf::from(workspace.trim())).filter(|p| !p.as_os_str().is_empty() && p.exists()),
+        limits,
+    };
+    // summary.md and artifacts/ describe one attempt; drop whatever an earlier one left.
+    let _ = fs::remove_file(run.run_dir.join("summary.md"));
+    let _ = fs::remove_dir_all(run.run_dir.join("artifacts"));
+    if let Err(e) = backend.prepare(&run) {
+        return fail(e);
+    }
+
+    let watchdog = spawn_lifecycle_watchdog(
+        db_path.to_path_buf(),
+        task.id.clone(),
+        task.goal_id.clone(),
+        agent_id.clone(),
+        ping_secs,
+        checkpoint_secs,
+    );
+    let shutdown = install_shutdown_handler();
+    // The worker gets its own process group so a timeout or shutdown reaches everything it
+    // spawned. When wrap owns the terminal, that group becomes the foreground job until it exits.
+    let foreground = io::stdin().is_terminal() && unsafe { libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp() };
+    let mut launch = backend.launch(&run);
+    launch.process_group(0);
+    if foreground {
+        unsafe {
+            launch.pre_exec(|| {
+                hand_terminal_to(libc::getpid());
+                Ok(())
+            });
+        }
+    }
+    let pid_file = run.run_dir.join("worker.pid");
+    // A wrap that was itself SIGKILLed never got to clean up; don't let its pid linger.
+    let _ = fs::remove_file(&pid_file);
+    let status = match launch.spawn() {
+        Ok(mut child) => {
+            let _ = fs::write(&pid_file, child.id().to_string());
+            let grace = cfg.duration_secs("worker.shutdown_grace");
+            let waited = wait_for_worker(&mut child, &shutdown, grace, run.limits.timeout_secs);
+            if foreground {
+                hand_terminal_to(unsafe { libc::getpgrp() });
+            }
+            let _ = fs::remove_file(&pid_file);
+            waited
+        }
+        Err(e) => Err(backend.start_error(&e)),
+    };
+    if let Some(watchdog) = watchdog {
+        watchdog.stop();
+    }
+    let status = match status {
+        Ok((_, true)) => {
+            let limit = format_duration(run.limits.timeout_secs.unwrap_or_default());
+            return fail_as("timeout", format!("timeout: {} was still running after {limit} ({timeout_source}) and was killed", backend.name()));
+        }
+        Ok((status, false)) => status,
+        Err(e) => return fail(e),
+    };
+    // A Ctrl-C at the terminal only reaches the foreground worker, not wrap itself.
+    let signal = match shutdown.signal() {
+        0 if foreground && status.signal() == Some(libc::SIGINT) => libc::SIGINT as usize,
+        signal => signal,
+    };
+    if signal != 0 {
+        let reason = format!("interrupted by {} while {} was running", signal_name(signal), backend.name());
+        release_interrupted_task(conn, &agent_id, &task.id, &reason)?;
+        return Err(format!("{reason} — task {} released back to todo", task.id));
+    }
+
+    let result = backend.collect(&run, status);
+    if !result.success {
+        return fail(result.detail);
+    }
+    if task_status(conn, &task.id).unwrap_or_default() == "done" {
+        emit_simple_ok(out, "Task already completed")?;
+        return Ok(());
+    }
+    let mut summary = result.summary.unwrap_or_else(|| runtime_completion_summary(mode, &result.detail));
+    if !result.artifacts.is_empty() {
+        let listed: Vec<String> = result
+            .artifacts
+            .iter()
+            .map(|p| p.strip_prefix(&run.run_dir).unwrap_or(p).display().to_string())
+            .collect();
+        summary.push_str(&format!("\n\nArtifacts: {}", listed.join(", ")));
+    }
+    cmd_complete(conn, out, cfg, Some(agent_id), task.id, summary, None, None, None, false)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_run(
+    conn: &Connection,
+    db_path: &Path,
+    out: OutputCtx,
+    cfg: &Config,
+    task_id: String,
+    model: Option<String>,
+    backend: Option<String>,
+    limits: WorkerLimits,
+) -> Result<(), String> {
+    let id = resolve_id_prefix(conn, "tasks", &task_id)?
+        .ok_or_else(|| format!("No task with ID '{task_id}' — run `imi tasks` to list available tasks"))?;
+    let backend = resolve_worker_backend(conn, cfg, backend.as_deref())?;
+    let agent_id = current_agent(None);
+    let ping_secs = cfg.u64("worker.ping_secs");
+    let checkpoint_secs = cfg.u64("worker.checkpoint_secs");
+    let claimed = ensure_task_in_progress(conn, &id, &agent_id, cfg.lease_secs().max(ping_secs as i64 * 3), false)?;
+    let model = model.unwrap_or_else(|| cfg.value("run.model"));
+    run_worker(conn, db_path, out, cfg, "run", claimed, agent_id, backend.as_ref(), model, ping_secs, checkpoint_secs, limits)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_wrap(
+    conn: &Connection,
+    db_path: &Path,
+    out: OutputCtx,
+    cfg: &Config,
+    agent: Option<String>,
+    task_id: String,
+    ping_secs: u64,
+    checkpoint_secs: u64,
+    backend: Option<String>,
+    limits: WorkerLimits,
+    command: Vec<String>,
+) -> Result<(), String> {
+    // An explicit command wins; otherwise the named (or configured) backend runs the task.
+    let backend: Box<dyn WorkerBackend> = if command.is_empty() {
+        resolve_worker_backend(conn, cfg, backend.as_deref())?
+    } else {
+        Box::new(ArgvBackend { argv: command })
+    };
+    let agent_id = current_agent(agent.as_deref());
+    let task = ensure_task_in_progress(conn, &task_id, &agent_id, cfg.lease_secs().max(ping_secs as i64 * 3), false)?;
+    let model = cfg.value("run.model");
+    run_worker(conn, db_path, out, cfg, "wrap", task, agent_id, backend.as_ref(), model, ping_secs, checkpoint_secs, limits)
+}
+
+struct OrchestrateWorker {
+    task_id: String,
+    agent_id: String,
+    title: String,
+    child: Child,
+    started_at: i64,
+    run_dir: PathBuf,
+    pumps: Vec<JoinHandle<()>>,
+    last_line: Arc<Mutex<String>>,
+    killed: Option<String>,
+}
+
+const WORKER_STREAMS: [&str; 2] = ["stdout", "stderr"];
+
+/// A task's run dir, `.imi/runs/<task_id>`. Orchestrated attempts log into numbered subdirs.
+fn task_runs_dir(db_path: &Path, task_id: &str) -> PathBuf {
+    db_path.parent().unwrap_or_else(|| Path::new(".")).join("runs").join(task_id)
+}
+
+/// Attempt numbers with a log dir under the task's run dir, oldest first.
+fn worker_log_attempts(db_path: &Path, task_id: &str) -> Vec<u32> {
+    let mut attempts: Vec<u32> = fs::read_dir(task_runs_dir(db_path, task_id))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().to_str().and_then(|n| n.parse().ok()))
+                .collect()
+        })
+        .unwrap_or_default();
+    attempts.sort_unstable();
+    attempts
+}
+
+/// Copy one of a worker's streams into its log line by line, stamping each line, and
+/// echo it under the agent's name when the orchestrator isn't quiet. The newest line
+/// is kept for the dashboard.
+fn pump_worker_output<R: Read + Send + 'static>(
+    src: R,
+    mut log: File,
+    echo: Option<String>,
+    is_stderr: bool,
+    last_line: Arc<Mutex<String>>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = io::BufReader::new(src);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let line = String::from_utf8_lossy(&buf);
+            let line = line.trim_end_matches(['\n', '\r']);
+            let _ = writeln!(log, "[{}] {line}", format_timestamp(now_ts()));
+            match &echo {
+                Some(agent) if is_stderr => eprintln!("[{agent}] {line}"),
+                Some(agent) => println!("[{agent}] {line}"),
+                None => {}
+            }
+            if !line.trim().is_empty() {
+                if let Ok(mut last) = last_line.lock() {
+                    *last = line.to_string();
+                }
+            }
+        }
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_orchestrate_worker(
+    db_path: &Path,
+    task_id: &str,
+    agent_id: &str,
+    ping_secs: u64,
     checkpoint_secs: u64,
+    backend: &str,
     command: &[String],
-) -> Result<Child, String> {
+    limits: &WorkerLimits,
+    log_dir: &Path,
+    echo: bool,
+    last_line: &Arc<Mutex<String>>,
+) -> Result<(Child, Vec<JoinHandle<()>>), String> {
     let exe = env::current_exe().map_err(|e| format!("failed to locate current executable: {e}"))?;
     let mut child_cmd = Command::new(exe);
     child_cmd
         .env("IMI_DB", db_path.display().to_string())
         .env("IMI_AGENT_ID", agent_id)
         .stdin(std::process::Stdio::null())
-        .stdout(std::process::Stdio::inherit())
-        .stderr(std::process::Stdio::inherit());
-
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+    // Its own process group: a terminal Ctrl-C reaches only the orchestrator, which passes
+    // it on, and killing the group takes down the agent the worker started too.
+    child_cmd.process_group(0);
+
+    child_cmd.args([
+        "wrap",
+        task_id,
+        "--agent",
+        agent_id,
+        "--ping-secs",
+        &ping_secs.to_string(),
+        "--checkpoint-secs",
+        &checkpoint_secs.to_string(),
+    ]);
+    if let Some(secs) = limits.timeout_secs {
+        child_cmd.arg("--task-timeout").arg(format!("{secs}s"));
+    }
+    if let Some(secs) = limits.cpu_secs {
+        child_cmd.arg("--max-cpu").arg(format!("{secs}s"));
+    }
+    if let Some(kb) = limits.memory_kb {
+        child_cmd.arg("--max-memory").arg(format!("{kb}K"));
+    }
     if command.is_empty() {
-        child_cmd.args(["run", task_id]);
+        child_cmd.args(["--backend", backend]);
     } else {
-        child_cmd
-            .args([
-                "wrap",
-                task_id,
-                "--agent",
-                agent_id,
-                "--ping-secs",
-                &ping_secs.to_string(),
-                "--checkpoint-secs",
-                &checkpoint_secs.to_string(),
-                "--",
-            ])
-            .args(command);
+        child_cmd.arg("--").args(command);
     }
 
-    child_cmd
+    let open_log = |stream: &str| {
+        File::create(log_dir.join(format!("{stream}.log")))
+            .map_err(|e| format!("failed to create {stream}.log in {}: {e}", log_dir.display()))
+    };
+    let (stdout_log, stderr_log) = (open_log("stdout")?, open_log("stderr")?);
+    let mut child = child_cmd
         .spawn()
-        .map_err(|e| format!("failed to spawn worker process: {e}"))
+        .map_err(|e| format!("failed to spawn worker process: {e}"))?;
+    let echo = echo.then(|| agent_id.to_string());
+    let mut pumps = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        pumps.push(pump_worker_output(stdout, stdout_log, echo.clone(), false, Arc::clone(last_line)));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        pumps.push(pump_worker_output(stderr, stderr_log, echo, true, Arc::clone(last_line)));
+    }
+    Ok((child, pumps))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn cmd_orchestrate(
     conn: &mut Connection,
     db_path: &Path,
     out: OutputCtx,
+    cfg: &Config,
     goal_id: Option<String>,
     workers: usize,
     agent_prefix: Option<String>,
     ping_secs: u64,
     checkpoint_secs: u64,
     max_tasks: Option<usize>,
-    cli: Option<String>,
+    backend: Option<String>,
+    quiet: bool,
+    tui: bool,
+    limits: WorkerLimits,
     command: Vec<String>,
 ) -> Result<(), String> {
     if workers == 0 {
         return Err("workers must be >= 1".to_string());
     }
 
-    // Resolve which CLI to use for workers (only applies when no explicit command given)
-    let resolved_command: Vec<String> = if command.is_empty() {
-        resolve_worker_cli(cli.as_deref()).unwrap_or_default()
+    // Resolve the backend here so `auto` sees this process's env and a typo fails before any claim.
+    let backend = if command.is_empty() {
+        resolve_worker_backend(conn, cfg, backend.as_deref())?.name().to_string()
     } else {
-        command
+        String::new()
     };
 
     let goal = if let Some(goal_id) = goal_id {
@@ -2738,6 +4161,18 @@ fn cmd_orchestrate(
         None
     };
 
+    if tui && (out.is_json() || out.is_toon()) {
+        return Err("--tui draws a terminal dashboard; drop --json/--toon".to_string());
+    }
+    let goal_label = match &goal {
+        Some(id) => conn
+            .query_row("SELECT name FROM goals WHERE id=?1", params![id], |r| r.get::<_, String>(0))
+            .unwrap_or_else(|_| id.clone()),
+        None => "all goals".to_string(),
+    };
+    let mut dashboard = if tui { Some(Dashboard::start(workers, goal_label)?) } else { None };
+    // Status lines and cmd_fail's own output would scribble over the dashboard.
+    let human = !out.is_json() && !out.is_toon() && dashboard.is_none();
     let prefix = agent_prefix.unwrap_or_else(|| "imi-worker".to_string());
     let limit = max_tasks.unwrap_or(usize::MAX);
     let mut active: Vec<OrchestrateWorker> = Vec::new();
@@ -2746,11 +4181,83 @@ fn cmd_orchestrate(
     let mut failed = 0usize;
     let mut no_more_tasks = false;
     let mut race_guard = 0usize;
+    let shutdown = install_shutdown_handler();
+    let grace = cfg.duration_secs("worker.shutdown_grace");
+    let mut shutdown_deadline: Option<Instant> = None;
+    let mut abandoned: Vec<(String, String, String)> = Vec::new();
 
     loop {
-        while !no_more_tasks && active.len() < workers && launched < limit {
+        if let Some(dash) = dashboard.as_mut() {
+            for key in dash.read_keys() {
+                match key {
+                    DashboardKey::Select(i) => dash.selected = i.min(active.len().saturating_sub(1)),
+                    DashboardKey::Up => dash.selected = dash.selected.saturating_sub(1),
+                    DashboardKey::Down => dash.selected = (dash.selected + 1).min(active.len().saturating_sub(1)),
+                    DashboardKey::Pause => {
+                        dash.paused = !dash.paused;
+                        dash.message = if dash.paused { "claiming paused".into() } else { "claiming resumed".into() };
+                    }
+                    DashboardKey::Kill => {
+                        if let Some(worker) = active.get_mut(dash.selected) {
+                            dash.message = format!("killed {} ({})", worker.agent_id, worker.title);
+                            kill_worker(worker, "killed from the orchestrate dashboard");
+                        }
+                    }
+                    DashboardKey::Quit => {
+                        no_more_tasks = true;
+                        dash.message = "finishing running workers, no new claims".into();
+                    }
+                    DashboardKey::Abort => {
+                        shutdown.request(signal_hook::consts::SIGINT as usize);
+                    }
+                }
+            }
+        }
+        let paused = dashboard.as_ref().is_some_and(|d| d.paused);
+
+        // First signal: stop claiming and pass it to every worker. Whoever is still running
+        // when the grace period ends, or when a second signal arrives, gets killed; their
+        // tasks go back to todo either way.
+        let signal = shutdown.signal();
+        if signal != 0 {
+            no_more_tasks = true;
+            match shutdown_deadline {
+                None => {
+                    for worker in active.iter_mut() {
+                        if send_signal(worker.child.id(), false, signal as i32).is_err() {
+                            kill_worker(worker, "could not pass the shutdown signal on");
+                        }
+                    }
+                    shutdown_deadline = Some(Instant::now() + Duration::from_secs(grace.max(0) as u64));
+                    let note = format!(
+                        "{} — stopping {} worker(s), killing any still running after {} (send it again to kill them now)",
+                        signal_name(signal),
+                        active.len(),
+                        format_duration(grace)
+                    );
+                    match dashboard.as_mut() {
+                        Some(dash) => dash.message = note,
+                        None if human => println!("⏹ {note}"),
+                        None => {}
+                    }
+                }
+                Some(_) if shutdown.forced() => {
+                    for worker in active.iter_mut() {
+                        kill_worker(worker, "killed by a second shutdown signal");
+                    }
+                }
+                Some(at) if Instant::now() >= at => {
+                    for worker in active.iter_mut() {
+                        kill_worker(worker, "killed after the shutdown grace period");
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+
+        while !paused && !no_more_tasks && active.len() < workers && launched < limit {
             let worker_agent = format!("{prefix}-{}", launched + 1);
-            let claim = claim_next_task(conn, goal.as_deref(), &worker_agent)?;
+            let claim = claim_next_task(conn, goal.as_deref(), &worker_agent, cfg.lease_secs())?;
             match claim {
                 ClaimResult::NoTasks => {
                     no_more_tasks = true;
@@ -2767,22 +4274,47 @@ fn cmd_orchestrate(
                 ClaimResult::Claimed(task) => {
                     race_guard = 0;
                     launched += 1;
-                    match spawn_orchestrate_worker(
-                        db_path,
-                        &task.id,
-                        &worker_agent,
-                        ping_secs,
-                        checkpoint_secs,
-                        &resolved_command,
-                    ) {
-                        Ok(child) => active.push(OrchestrateWorker {
-                            task_id: task.id,
-                            agent_id: worker_agent,
-                            child,
-                        }),
+                    let attempt = worker_log_attempts(db_path, &task.id).last().map_or(1, |n| n + 1);
+                    let run_dir = task_runs_dir(db_path, &task.id);
+                    let log_dir = run_dir.join(attempt.to_string());
+                    let last_line = Arc::new(Mutex::new(String::new()));
+                    let spawned = fs::create_dir_all(&log_dir)
+                        .map_err(|e| format!("failed to create {}: {e}", log_dir.display()))
+                        .and_then(|_| {
+                            spawn_orchestrate_worker(
+                                db_path,
+                                &task.id,
+                                &worker_agent,
+                                ping_secs,
+                                checkpoint_secs,
+                                &backend,
+                                &command,
+                                &limits,
+                                &log_dir,
+                                human && !quiet,
+                                &last_line,
+                            )
+                        });
+                    match spawned {
+                        Ok((child, pumps)) => {
+                            if human {
+                                println!("▶ {worker_agent}  {}  (attempt {attempt}, imi logs {})", task.title, display_id(conn, "tasks", &task.id)?);
+                            }
+                            active.push(OrchestrateWorker {
+                                task_id: task.id,
+                                agent_id: worker_agent,
+                                title: task.title,
+                                child,
+                                started_at: now_ts(),
+                                run_dir,
+                                pumps,
+                                last_line,
+                                killed: None,
+                            })
+                        }
                         Err(e) => {
                             failed += 1;
-                            let _ = cmd_fail(conn, out, Some(worker_agent), task.id, e.clone());
+                            fail_worker_task(conn, out, cfg, dashboard.is_some(), &worker_agent, &task.id, &e);
                         }
                     }
                 }
@@ -2795,20 +4327,33 @@ fn cmd_orchestrate(
             match status {
                 None => idx += 1,
                 Some(status) => {
-                    if status.success() {
+                    let worker = &mut active[idx];
+                    for pump in worker.pumps.drain(..) {
+                        let _ = pump.join();
+                    }
+                    if human {
+                        let elapsed = ago(worker.started_at);
+                        if status.success() {
+                            println!("✓ {}  {}  ({elapsed})", worker.agent_id, worker.title);
+                        } else {
+                            println!("✗ {}  {}  ({elapsed}, {status}) — imi logs {}", worker.agent_id, worker.title, display_id(conn, "tasks", &worker.task_id)?);
+                        }
+                    }
+                    if status.success() && worker.killed.is_none() {
                         done += 1;
+                    } else if signal != 0 {
+                        let reason = format!("interrupted by {} while orchestrated as {}", signal_name(signal), worker.agent_id);
+                        let _ = release_interrupted_task(conn, &worker.agent_id, &worker.task_id, &reason);
+                        abandoned.push((worker.task_id.clone(), worker.title.clone(), worker.agent_id.clone()));
                     } else {
                         failed += 1;
-                        let reason = format!("worker {} exited with status {status}", active[idx].agent_id);
-                        let _ = cmd_fail(
-                            conn,
-                            out,
-                            Some(active[idx].agent_id.clone()),
-                            active[idx].task_id.clone(),
-                            reason,
-                        );
+                        let reason = worker
+                            .killed
+                            .clone()
+                            .unwrap_or_else(|| format!("worker {} exited with status {status}", worker.agent_id));
+                        fail_worker_task(conn, out, cfg, dashboard.is_some(), &worker.agent_id, &worker.task_id, &reason);
                     }
-                    active.swap_remove(idx);
+                    active.remove(idx);
                 }
             }
         }
@@ -2817,23 +4362,31 @@ fn cmd_orchestrate(
             break;
         }
 
-        if active.is_empty() && no_more_tasks {
-            break;
+        if let Some(dash) = dashboard.as_mut() {
+            let queued = count_claimable_tasks(conn, goal.as_deref());
+            dash.draw(conn, &active, queued, done, failed);
         }
-
         thread::sleep(Duration::from_millis(400));
     }
+    drop(dashboard);
+    let signal = shutdown.signal();
 
     if out.is_json() {
+        let abandoned_json: Vec<Value> = abandoned
+            .iter()
+            .map(|(task_id, title, agent_id)| json!({"task_id": task_id, "title": title, "agent_id": agent_id}))
+            .collect();
         println!(
             "{}",
             json!({
-                "ok": failed == 0,
+                "ok": failed == 0 && signal == 0,
                 "goal_id": goal,
                 "workers": workers,
                 "launched": launched,
                 "completed": done,
-                "failed": failed
+                "failed": failed,
+                "interrupted": (signal != 0).then(|| signal_name(signal)),
+                "abandoned": abandoned_json
             })
         );
     } else if out.is_toon() {
@@ -2848,14 +4401,33 @@ fn cmd_orchestrate(
                 failed.to_string(),
             ]],
         );
+        if !abandoned.is_empty() {
+            let rows = abandoned.iter().map(|(task_id, title, agent_id)| vec![task_id.clone(), title.clone(), agent_id.clone()]).collect();
+            t.section("abandoned", &["task_id", "title", "agent_id"], rows);
+        }
         print!("{}", t.finish());
     } else {
         println!(
             "Orchestrate finished: launched={} completed={} failed={}",
             launched, done, failed
         );
+        if !abandoned.is_empty() {
+            println!("⏹ Interrupted by {} — released back to todo:", signal_name(signal));
+            let task_ids = unique_id_prefixes(conn, "tasks")?;
+            for (task_id, title, agent_id) in &abandoned {
+                let task_id = short_id(&task_ids, task_id);
+                println!("  - {title}  {task_id}  ({agent_id})");
+            }
+        }
     }
 
+    if signal != 0 {
+        return Err(format!(
+            "orchestrate interrupted by {} — {} task(s) released back to todo",
+            signal_name(signal),
+            abandoned.len()
+        ));
+    }
     if failed > 0 {
         return Err(format!("orchestrate finished with {failed} failed worker(s)"));
     }
@@ -2863,73 +4435,620 @@ fn cmd_orchestrate(
     Ok(())
 }
 
-fn cmd_fail(
-    conn: &Connection,
-    out: OutputCtx,
-    agent: Option<String>,
-    task_id: String,
-    reason: String,
-) -> Result<(), String> {
-    if reason.trim().is_empty() {
-        return Err("reason is required".to_string());
+/// Fail an orchestrated task that is still claimed. Under the dashboard the failure is recorded without
+/// cmd_fail's output, which would land on top of the screen.
+fn fail_worker_task(conn: &Connection, out: OutputCtx, cfg: &Config, silent: bool, agent_id: &str, task_id: &str, reason: &str) {
+    // A worker that failed under `imi wrap` has already released its task.
+    if task_status(conn, task_id).unwrap_or_default() != "in_progress" {
+        return;
+    }
+    if silent {
+        let _ = record_task_failure(conn, cfg, agent_id, task_id, "failure_reason", reason);
+    } else {
+        let _ = cmd_fail(conn, out, cfg, Some(agent_id.to_string()), task_id.to_string(), reason.to_string());
     }
+}
 
-    let task = resolve_task(conn, &task_id)?;
-    let agent_id = current_agent(agent.as_deref());
-    let now = now_ts();
+/// Parent of `pid` from `/proc`, falling back to `ps` where there is no procfs.
+fn parent_pid(pid: u32) -> Option<u32> {
+    if let Ok(stat) = fs::read_to_string(format!("/proc/{pid}/stat")) {
+        // The command name may contain spaces and parens; the fields after it don't.
+        return stat.rsplit_once(')')?.1.split_whitespace().nth(1)?.parse().ok();
+    }
+    let output = Command::new("ps").args(["-o", "ppid=", "-p", &pid.to_string()]).output().ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
 
-    conn.execute(
-        "UPDATE tasks SET status='todo', agent_id=NULL, updated_at=?1 WHERE id=?2",
-        params![now, task.id],
-    )
-    .map_err(|e| e.to_string())?;
+/// SIGKILL a worker and, when it leads its own process group, the agent it started.
+/// The orchestrate loop notices the exit and fails the task with `reason`.
+fn kill_worker(worker: &mut OrchestrateWorker, reason: &str) {
+    if worker.killed.is_some() {
+        return;
+    }
+    worker.killed = Some(reason.to_string());
+    // The agent sits in a process group of its own under `imi wrap`; take it down first.
+    // worker.pid may be left over from an earlier attempt and name a recycled pid, so only
+    // trust it while that process is still a child of this worker's wrap.
+    let agent_pid = fs::read_to_string(worker.run_dir.join("worker.pid"))
+        .ok()
+        .and_then(|p| p.trim().parse::<u32>().ok())
+        .filter(|pid| parent_pid(*pid) == Some(worker.child.id()));
+    if let Some(pid) = agent_pid {
+        let _ = send_signal(pid, true, libc::SIGKILL);
+    }
+    if send_signal(worker.child.id(), true, libc::SIGKILL).is_err() {
+        let _ = worker.child.kill();
+    }
+}
 
-    conn.execute(
-        "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
-         VALUES (?1, ?2, ?3, 'failure_reason', ?4, 'failure', ?4, ?5, ?6)",
-        params![gen_id(), task.goal_id, task.id, reason, agent_id, now],
+/// Tasks `imi orchestrate` could still claim: todo, goal not archived, dependencies done.
+fn count_claimable_tasks(conn: &Connection, goal_id: Option<&str>) -> i64 {
+    conn.query_row(
+        "SELECT COUNT(*) FROM tasks
+         WHERE status='todo'
+           AND (?1 IS NULL OR goal_id=?1)
+           AND (goal_id IS NULL OR goal_id IN (SELECT id FROM goals WHERE status!='archived'))
+           AND NOT EXISTS (
+               SELECT 1 FROM task_dependencies d JOIN tasks p ON p.id = d.depends_on
+               WHERE d.task_id = tasks.id AND COALESCE(p.status,'todo') != 'done'
+           )",
+        params![goal_id],
+        |r| r.get(0),
     )
-    .map_err(|e| e.to_string())?;
+    .unwrap_or(0)
+}
 
-    if let Some(goal_id) = task.goal_id {
-        sync_goal(conn, &goal_id)?;
-    }
+enum DashboardKey {
+    Select(usize),
+    Up,
+    Down,
+    Kill,
+    Pause,
+    Quit,
+    Abort,
+}
 
-    if out.is_json() {
-        println!(
-            "{}",
-            json!({"ok": true, "status": "todo", "id": task.id, "title": task.title})
-        );
-    } else if out.is_toon() {
-        let mut t = ToonBuilder::new();
-        t.section(
-            "task",
-            &["id", "title", "status"],
-            vec![vec![task.id, task.title, "todo".to_string()]],
-        );
-        print!("{}", t.finish());
-    } else {
-        println!("🚫 Task {} marked blocked for this attempt and moved back to 📋 todo", task.id);
+/// `imi orchestrate --tui`: redraws the workers every poll on the alternate screen and
+/// reads single keys with the terminal in non-canonical mode. The width is read once and
+/// again after each SIGWINCH. Dropping it puts the terminal back the way `stty -g` found it.
+struct Dashboard {
+    saved_tty: String,
+    cols: usize,
+    resized: Arc<AtomicBool>,
+    winch: Option<signal_hook::SigId>,
+    keys: mpsc::Receiver<u8>,
+    stop_keys: Arc<AtomicBool>,
+    reader: Option<JoinHandle<()>>,
+    workers: usize,
+    goal_label: String,
+    selected: usize,
+    paused: bool,
+    message: String,
+}
+
+fn stty(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(std::process::Stdio::inherit())
+        .output()
+        .map_err(|e| format!("failed to run stty: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("stty {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
     }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
 
-    Ok(())
+fn terminal_cols() -> usize {
+    stty(&["size"])
+        .ok()
+        .and_then(|size| size.split_whitespace().nth(1).and_then(|c| c.parse::<usize>().ok()))
+        .unwrap_or(100)
+        .max(60)
 }
 
-fn cmd_ping(conn: &Connection, out: OutputCtx, task_id: String) -> Result<(), String> {
+/// Last ping and last checkpoint of each task, in one query for the whole dashboard.
+fn worker_heartbeats(conn: &Connection, task_ids: &[&str]) -> BTreeMap<String, (Option<i64>, Option<i64>)> {
+    if task_ids.is_empty() {
+        return BTreeMap::new();
+    }
+    let holes: Vec<String> = (1..=task_ids.len()).map(|i| format!("?{i}")).collect();
+    let sql = format!(
+        "SELECT t.id, t.last_ping_at,
+                (SELECT MAX(m.created_at) FROM memories m WHERE m.task_id = t.id AND m.key = 'checkpoint')
+         FROM tasks t WHERE t.id IN ({})",
+        holes.join(",")
+    );
+    let Ok(mut stmt) = conn.prepare(&sql) else {
+        return BTreeMap::new();
+    };
+    stmt.query_map(rusqlite::params_from_iter(task_ids.iter()), |r| Ok((r.get::<_, String>(0)?, (r.get(1)?, r.get(2)?))))
+        .map(|rows| rows.filter_map(Result::ok).collect())
+        .unwrap_or_default()
+}
+
+/// Pad or cut `text` to exactly `width` columns, marking a cut with `…`.
+fn fit_width(text: &str, width: usize) -> String {
+    let count = text.chars().count();
+    if count <= width {
+        format!("{text}{}", " ".repeat(width - count))
+    } else if width == 0 {
+        String::new()
+    } else {
+        format!("{}…", text.chars().take(width - 1).collect::<String>())
+    }
+}
+
+impl Dashboard {
+    fn start(workers: usize, goal_label: String) -> Result<Self, String> {
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            return Err("--tui needs an interactive terminal".to_string());
+        }
+        let saved_tty = stty(&["-g"])?;
+        // -isig: Ctrl-C arrives as a key, so the dashboard can fail the tasks it abandons.
+        stty(&["-icanon", "-echo", "-isig", "min", "0", "time", "1"])?;
+
+        let (tx, keys) = mpsc::channel();
+        let stop_keys = Arc::new(AtomicBool::new(false));
+        let stop_ref = Arc::clone(&stop_keys);
+        let reader = thread::spawn(move || {
+            let mut stdin = io::stdin();
+            let mut buf = [0u8; 16];
+            while !stop_ref.load(Ordering::Relaxed) {
+                match stdin.read(&mut buf) {
+                    Ok(n) => {
+                        if buf[..n].iter().any(|b| tx.send(*b).is_err()) {
+                            return;
+                        }
+                    }
+                    Err(_) => return,
+                }
+            }
+        });
+        let resized = Arc::new(AtomicBool::new(false));
+        let winch = signal_hook::flag::register(signal_hook::consts::SIGWINCH, Arc::clone(&resized)).ok();
+        print!("\x1b[?1049h\x1b[?25l");
+        let _ = io::stdout().flush();
+        Ok(Self {
+            saved_tty,
+            cols: terminal_cols(),
+            resized,
+            winch,
+            keys,
+            stop_keys,
+            reader: Some(reader),
+            workers,
+            goal_label,
+            selected: 0,
+            paused: false,
+            message: String::new(),
+        })
+    }
+
+    fn read_keys(&mut self) -> Vec<DashboardKey> {
+        let bytes: Vec<u8> = self.keys.try_iter().collect();
+        let mut keys = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                0x1b if bytes.get(i + 1) == Some(&b'[') => {
+                    match bytes.get(i + 2) {
+                        Some(b'A') => keys.push(DashboardKey::Up),
+                        Some(b'B') => keys.push(DashboardKey::Down),
+                        _ => {}
+                    }
+                    i += 2;
+                }
+                b'1'..=b'9' => keys.push(DashboardKey::Select((bytes[i] - b'1') as usize)),
+                b'k' => keys.push(DashboardKey::Kill),
+                b'p' => keys.push(DashboardKey::Pause),
+                b'q' => keys.push(DashboardKey::Quit),
+                0x03 => keys.push(DashboardKey::Abort),
+                _ => {}
+            }
+            i += 1;
+        }
+        keys
+    }
+
+    fn draw(&mut self, conn: &Connection, active: &[OrchestrateWorker], queued: i64, done: usize, failed: usize) {
+        if self.resized.swap(false, Ordering::Relaxed) {
+            self.cols = terminal_cols();
+        }
+        let cols = self.cols;
+        self.selected = self.selected.min(active.len().saturating_sub(1));
+        let bold = |s: &str| format!("\x1b[1m{s}\x1b[0m");
+        let dim = |s: &str| format!("\x1b[2m{s}\x1b[0m");
+
+        let mut screen = String::from("\x1b[H");
+        let mut line = |text: String| {
+            screen.push_str(&text);
+            screen.push_str("\x1b[K\r\n");
+        };
+        let state = if self.paused { "  \x1b[33m[claiming paused]\x1b[0m" } else { "" };
+        line(format!("{}  {}{state}", bold("imi orchestrate"), fit_width(&self.goal_label, cols / 2)));
+        line(format!(
+            "running {}/{}   queued {queued}   done \x1b[32m{done}\x1b[0m   failed \x1b[31m{failed}\x1b[0m",
+            active.len(),
+            self.workers
+        ));
+        line(String::new());
+        let fixed = 3 + 16 + 1 + 8 + 6 + 9;
+        let title_w = ((cols.saturating_sub(fixed)) * 2 / 5).max(12);
+        let tail_w = cols.saturating_sub(fixed + title_w + 2).max(10);
+        line(dim(&format!(
+            "   {} {} {} {} {} {}",
+            fit_width("AGENT", 15),
+            fit_width("TASK", title_w),
+            fit_width("ELAPSED", 7),
+            fit_width("PING", 5),
+            fit_width("CHECKPT", 8),
+            "LAST OUTPUT"
+        )));
+        if active.is_empty() {
+            line(dim("   (no workers running)"));
+        }
+        let task_ids: Vec<&str> = active.iter().map(|w| w.task_id.as_str()).collect();
+        let heartbeats = worker_heartbeats(conn, &task_ids);
+        for (i, worker) in active.iter().enumerate() {
+            let (ping, checkpoint) = heartbeats.get(&worker.task_id).copied().unwrap_or((None, None));
+            let last = worker.last_line.lock().map(|l| l.clone()).unwrap_or_default();
+            let marker = if i == self.selected { "▸" } else { " " };
+            let row = format!(
+                "{marker}{} {} {} {} {} {} {}",
+                if i < 9 { (i + 1).to_string() } else { " ".to_string() },
+                fit_width(&worker.agent_id, 15),
+                fit_width(&worker.title, title_w),
+                fit_width(&ago(worker.started_at), 7),
+                fit_width(&ping.map(ago).unwrap_or_else(|| "—".into()), 5),
+                fit_width(&checkpoint.map(ago).unwrap_or_else(|| "—".into()), 8),
+                fit_width(&last, tail_w),
+            );
+            let row = if worker.killed.is_some() { dim(&row) } else { row };
+            line(if i == self.selected { format!("\x1b[7m{row}\x1b[0m") } else { row });
+        }
+        line(String::new());
+        line(dim("↑/↓ or 1-9 select · k kill (fails its task) · p pause claiming · q finish and quit · Ctrl-C kill all"));
+        line(self.message.clone());
+        screen.push_str("\x1b[J");
+        print!("{screen}");
+        let _ = io::stdout().flush();
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        if let Some(id) = self.winch.take() {
+            signal_hook::low_level::unregister(id);
+        }
+        self.stop_keys.store(true, Ordering::Relaxed);
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        let _ = stty(&[&self.saved_tty]);
+    }
+}
+
+/// Split a stored log line into its `[YYYY-MM-DD HH:MM:SS]` stamp and text.
+fn parse_worker_log_line(line: &str) -> (String, String) {
+    match (line.get(..1), line.get(1..20), line.get(20..22)) {
+        (Some("["), Some(at), Some("] ")) => (at.to_string(), line[22..].to_string()),
+        _ => (String::new(), line.to_string()),
+    }
+}
+
+/// Complete lines appended to a log since `offset`, advancing it past them. A trailing
+/// partial line is left for the next read.
+fn read_new_log_lines(path: &Path, offset: &mut u64) -> Vec<String> {
+    let Ok(mut file) = File::open(path) else { return Vec::new() };
+    if file.seek(SeekFrom::Start(*offset)).is_err() {
+        return Vec::new();
+    }
+    let mut buf = Vec::new();
+    if file.read_to_end(&mut buf).is_err() {
+        return Vec::new();
+    }
+    let Some(end) = buf.iter().rposition(|b| *b == b'\n') else { return Vec::new() };
+    *offset += end as u64 + 1;
+    String::from_utf8_lossy(&buf[..end]).lines().map(str::to_string).collect()
+}
+
+fn print_worker_log_line(out: OutputCtx, stream: &str, at: &str, text: &str) {
+    let text = if stream == "stderr" { paint(out, "31", text) } else { text.to_string() };
+    println!("{}  {text}", paint(out, "2", at));
+}
+
+fn cmd_logs(conn: &Connection, db_path: &Path, out: OutputCtx, task_id: String, attempt: Option<u32>, follow: bool) -> Result<(), String> {
+    let id = resolve_id_prefix(conn, "tasks", &task_id)?
+        .ok_or_else(|| format!("No task with ID '{task_id}' — run `imi tasks` to list available tasks"))?;
+    if follow && (out.is_json() || out.is_toon()) {
+        return Err("--follow streams plain text; drop --json/--toon".to_string());
+    }
+    let attempts = worker_log_attempts(db_path, &id);
+    let Some(&latest) = attempts.last() else {
+        return Err(format!("no worker logs for task {id} — logs are kept for tasks run by `imi orchestrate`"));
+    };
+    let attempt = attempt.unwrap_or(latest);
+    if !attempts.contains(&attempt) {
+        let known: Vec<String> = attempts.iter().map(|n| n.to_string()).collect();
+        return Err(format!("task {id} has no attempt {attempt} (attempts: {})", known.join(", ")));
+    }
+    let dir = task_runs_dir(db_path, &id).join(attempt.to_string());
+    let title: String = conn
+        .query_row("SELECT title FROM tasks WHERE id=?1", params![id], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+
+    // Merge both streams by timestamp; the sort is stable, so each stream keeps its own order.
+    let mut offsets = [0u64; 2];
+    let mut lines: Vec<(String, &str, String)> = Vec::new();
+    for (i, stream) in WORKER_STREAMS.iter().enumerate() {
+        for line in read_new_log_lines(&dir.join(format!("{stream}.log")), &mut offsets[i]) {
+            let (at, text) = parse_worker_log_line(&line);
+            lines.push((at, stream, text));
+        }
+    }
+    lines.sort_by(|a, b| a.0.cmp(&b.0));
+
+    if out.is_json() {
+        let items: Vec<Value> = lines
+            .iter()
+            .map(|(at, stream, text)| json!({"at": at, "stream": stream, "text": text}))
+            .collect();
+        println!(
+            "{}",
+            json!({"task_id": id, "title": title, "attempt": attempt, "attempts": attempts, "dir": dir.display().to_string(), "lines": items})
+        );
+        return Ok(());
+    }
+    if out.is_toon() {
+        let rows = lines
+            .into_iter()
+            .map(|(at, stream, text)| vec![at, stream.to_string(), text])
+            .collect();
+        let mut t = ToonBuilder::new();
+        t.section("logs", &["at", "stream", "text"], rows);
+        print!("{}", t.finish());
+        return Ok(());
+    }
+
+    println!("{}", paint(out, "1", &format!("Logs for {title} — attempt {attempt} of {}", attempts.len())));
+    println!("{}", paint(out, "2", &dir.display().to_string()));
+    if lines.is_empty() && !follow {
+        println!("(no output)");
+    }
+    for (at, stream, text) in &lines {
+        print_worker_log_line(out, stream, at, text);
+    }
+    if !follow {
+        return Ok(());
+    }
+    // Check the task before reading so the last lines a finishing worker writes still get drained.
+    loop {
+        let running = task_status(conn, &id).unwrap_or_default() == "in_progress";
+        for (i, stream) in WORKER_STREAMS.iter().enumerate() {
+            for line in read_new_log_lines(&dir.join(format!("{stream}.log")), &mut offsets[i]) {
+                let (at, text) = parse_worker_log_line(&line);
+                print_worker_log_line(out, stream, &at, &text);
+            }
+        }
+        let _ = io::stdout().flush();
+        if !running {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+/// Release a task after a failed attempt: bump its attempt count, store the reason as a
+/// failure memory, and block it for a human once the retry budget is spent.
+/// Returns the task with its new status, attempt count and budget.
+fn record_task_failure(
+    conn: &Connection,
+    cfg: &Config,
+    agent_id: &str,
+    task_id: &str,
+    key: &str,
+    reason: &str,
+) -> Result<(TaskRow, &'static str, i64, i64), String> {
+    let task = resolve_task(conn, task_id)?;
+    if task.status != "in_progress" {
+        return Err(format!("task is not in progress (status: {})", task.status));
+    }
+    let now = now_ts();
+
+    let (attempts, task_max): (i64, Option<i64>) = conn
+        .query_row(
+            "SELECT attempts + 1, max_attempts FROM tasks WHERE id=?1",
+            params![task.id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    let max_attempts = task_max.unwrap_or_else(|| cfg.max_attempts());
+    let status = if attempts >= max_attempts { "blocked" } else { "todo" };
+
+    conn.execute(
+        "UPDATE tasks SET status=?1, attempts=?2, agent_id=NULL, updated_at=?3 WHERE id=?4",
+        params![status, attempts, now, task.id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 'failure', ?5, ?6, ?7)",
+        params![gen_typed_id("m"), task.goal_id, task.id, key, reason, agent_id, now],
+    )
+    .map_err(|e| e.to_string())?;
+    if status == "blocked" {
+        let note = format!("Blocked after {attempts} failed attempt(s) — needs a human (`imi unblock {}`)", task.id);
+        conn.execute(
+            "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
+             VALUES (?1, ?2, ?3, 'task_escalated', ?4, 'lifecycle', ?4, ?5, ?6)",
+            params![gen_typed_id("m"), task.goal_id, task.id, note, agent_id, now],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    if let Some(goal_id) = &task.goal_id {
+        sync_goal(conn, goal_id)?;
+    }
+
+    Ok((task, status, attempts, max_attempts))
+}
+
+fn cmd_fail(
+    conn: &Connection,
+    out: OutputCtx,
+    cfg: &Config,
+    agent: Option<String>,
+    task_id: String,
+    reason: String,
+) -> Result<(), String> {
+    fail_task(conn, out, cfg, agent, task_id, "failure_reason", reason)
+}
+
+/// `imi fail`, with the failure memory stored under `key` (`failure_reason`, or `timeout`
+/// when a worker outlived its limit).
+fn fail_task(
+    conn: &Connection,
+    out: OutputCtx,
+    cfg: &Config,
+    agent: Option<String>,
+    task_id: String,
+    key: &str,
+    reason: String,
+) -> Result<(), String> {
+    if reason.trim().is_empty() {
+        return Err("reason is required".to_string());
+    }
+    let agent_id = current_agent(agent.as_deref());
+    let (task, status, attempts, max_attempts) = record_task_failure(conn, cfg, &agent_id, &task_id, key, &reason)?;
+
+    if out.is_json() {
+        println!(
+            "{}",
+            json!({
+                "ok": true,
+                "status": status,
+                "id": task.id,
+                "title": task.title,
+                "attempts": attempts,
+                "max_attempts": max_attempts
+            })
+        );
+    } else if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section(
+            "task",
+            &["id", "title", "status", "attempts", "max_attempts"],
+            vec![vec![task.id, task.title, status.to_string(), attempts.to_string(), max_attempts.to_string()]],
+        );
+        print!("{}", t.finish());
+    } else if status == "blocked" {
+        let id = display_id(conn, "tasks", &task.id)?;
+        println!("🚫 Task {id} failed {attempts}/{max_attempts} times — blocked until a human runs `imi unblock {id}`");
+    } else {
+        println!(
+            "🚫 Task {} failed (attempt {attempts}/{max_attempts}) and moved back to 📋 todo",
+            display_id(conn, "tasks", &task.id)?
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_unblock(conn: &Connection, out: OutputCtx, task_id: String, note: String) -> Result<(), String> {
+    if note.trim().is_empty() {
+        return Err("say what changed: imi unblock <task_id> \"note\"".to_string());
+    }
+    let task = resolve_task(conn, &task_id)?;
+    if task.status != "blocked" {
+        return Err(format!("task {} is {}, not blocked", task.id, task.status));
+    }
+    let now = now_ts();
+    conn.execute(
+        "UPDATE tasks SET status='todo', attempts=0, agent_id=NULL, updated_at=?1 WHERE id=?2",
+        params![now, task.id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
+         VALUES (?1, ?2, ?3, 'task_unblocked', ?4, 'lifecycle', ?4, ?5, ?6)",
+        params![gen_typed_id("m"), task.goal_id, task.id, note, current_agent(None), now],
+    )
+    .map_err(|e| e.to_string())?;
+    if let Some(goal_id) = &task.goal_id {
+        sync_goal(conn, goal_id)?;
+    }
+    emit_simple_ok(out, &format!("Task {} unblocked and back in 📋 todo", task.id))
+}
+
+/// Tasks that exhausted their retry budget, as (id, title, attempts, last failure reason).
+fn query_needs_human(conn: &Connection) -> Result<Vec<(String, String, i64, String)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.id, t.title, t.attempts,
+                    COALESCE((SELECT m.value FROM memories m
+                              WHERE m.task_id = t.id AND m.key IN ('failure_reason', 'timeout')
+                              ORDER BY m.created_at DESC, m.rowid DESC LIMIT 1), '')
+             FROM tasks t
+             WHERE t.status = 'blocked'
+             ORDER BY COALESCE(t.updated_at, t.created_at, 0) DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn needs_human_to_value(rows: &[(String, String, i64, String)]) -> Value {
+    json!(rows
+        .iter()
+        .map(|(id, title, attempts, reason)| json!({"id": id, "title": title, "attempts": attempts, "last_failure": reason}))
+        .collect::<Vec<_>>())
+}
+
+fn print_needs_human(task_ids: &BTreeMap<String, String>, rows: &[(String, String, i64, String)]) {
+    println!("## Needs human");
+    for (id, title, attempts, reason) in rows {
+        let id = short_id(task_ids, id);
+        println!("  🚫 {title}  {id}  ({attempts} failed attempt(s))");
+        if !reason.is_empty() {
+            println!("    last failure: {reason}");
+        }
+        println!("    → fix the cause, then `imi unblock {id} \"what changed\"`");
+    }
+}
+
+fn cmd_ping(conn: &Connection, out: OutputCtx, cfg: &Config, task_id: String, extend: Option<String>) -> Result<(), String> {
     let id = resolve_id_prefix(conn, "tasks", &task_id)?
         .ok_or_else(|| format!("No task with ID '{task_id}' — run `imi tasks` to list available tasks"))?;
+    let extend_secs = extend.as_deref().map(parse_duration_secs).transpose()?;
     let now = now_ts();
     let n = conn
         .execute(
-            "UPDATE tasks SET updated_at=?1, last_ping_at=?1 WHERE id=?2 AND status='in_progress'",
-            params![now, id],
+            "UPDATE tasks SET updated_at=?1, last_ping_at=?1,
+                    lease_seconds=COALESCE(?3, lease_seconds, ?4),
+                    lease_expires_at=?1 + COALESCE(?3, lease_seconds, ?4)
+             WHERE id=?2 AND status='in_progress'",
+            params![now, id, extend_secs, cfg.lease_secs()],
         )
         .map_err(|e| e.to_string())?;
     if n == 0 {
         return Err("task is not in progress".to_string());
     }
+    let goal_id: Option<String> = conn
+        .query_row("SELECT goal_id FROM tasks WHERE id=?1", params![id], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    log_event(conn, "ping", Some(&id), goal_id.as_deref(), Some(&current_agent(None)), 0);
 
-    emit_simple_ok(out, "pong")?;
+    match extend_secs {
+        Some(secs) => emit_simple_ok(out, &format!("pong — lease extended to {}", format_duration(secs)))?,
+        None => emit_simple_ok(out, "pong")?,
+    }
     Ok(())
 }
 
@@ -2948,12 +5067,12 @@ fn cmd_checkpoint(conn: &Connection, out: OutputCtx, task_id: String, note: Stri
     conn.execute(
         "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
          VALUES (?1, ?2, ?3, 'checkpoint', ?4, 'checkpoint', ?4, ?5, ?6)",
-        params![gen_id(), task.goal_id, task.id, note, agent_id, now],
+        params![gen_typed_id("m"), task.goal_id, task.id, note, agent_id, now],
     )
     .map_err(|e| e.to_string())?;
 
     conn.execute(
-        "UPDATE tasks SET updated_at=?1, last_ping_at=?1 WHERE id=?2 AND status='in_progress'",
+        "UPDATE tasks SET updated_at=?1, last_ping_at=?1, lease_expires_at=?1 + lease_seconds WHERE id=?2 AND status='in_progress'",
         params![now, task.id],
     )
     .map_err(|e| e.to_string())?;
@@ -2969,65 +5088,361 @@ fn cmd_checkpoint(conn: &Connection, out: OutputCtx, task_id: String, note: Stri
     Ok(())
 }
 
-fn cmd_add_goal(
-    conn: &Connection,
-    out: OutputCtx,
-    name: String,
-    desc: Option<String>,
-    priority: Option<String>,
-    why: Option<String>,
-    for_who: Option<String>,
-    success_signal: Option<String>,
-    relevant_files: Vec<String>,
-    context: Option<String>,
-    workspace: Option<String>,
-) -> Result<(), String> {
-    let id = gen_id();
-    let now = now_ts();
-    let cwd = workspace.unwrap_or_else(|| {
-        env::current_dir()
-            .ok()
-            .map(|x| x.display().to_string())
-            .unwrap_or_default()
-    });
-    let rf_json = if relevant_files.is_empty() {
-        "[]".to_string()
-    } else {
-        serde_json::to_string(&relevant_files).unwrap_or_else(|_| "[]".to_string())
-    };
+/// Rough token count for `--budget`: about four characters per token and at least
+/// one per word, which tracks the common BPE tokenizers closely enough for packing.
+fn approx_tokens(text: &str) -> usize {
+    text.split_whitespace().map(|w| w.chars().count().div_ceil(4).max(1)).sum()
+}
 
-    conn.execute(
-        "INSERT INTO goals (id, name, description, why, for_who, success_signal, status, priority, context, tags, workspace_path, relevant_files, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'todo', ?7, ?8, '[]', ?9, ?10, ?11, ?11)",
-        params![
-            id,
-            name,
-            desc.unwrap_or_else(|| "".to_string()),
-            why.unwrap_or_default(),
-            for_who.unwrap_or_default(),
-            success_signal.unwrap_or_default(),
-            priority.unwrap_or_else(|| "medium".to_string()),
-            context.unwrap_or_default(),
-            cwd,
-            rf_json,
-            now
-        ],
-    )
-    .map_err(|e| e.to_string())?;
+/// Packs context sections into a token budget. Sections are offered in priority
+/// order; rows inside a section keep their ranking, and a row that no longer fits is
+/// skipped so shorter rows further down can still use the space. Without a budget
+/// every row passes through untouched.
+struct ContextBudget {
+    limit: Option<usize>,
+    used: usize,
+    /// (section, kept, dropped) in the order sections were packed
+    sections: Vec<(&'static str, usize, usize)>,
+}
 
-    if out.is_json() {
-        println!("{}", json!({"ok": true, "id": id}));
-    } else if out.is_toon() {
-        let mut t = ToonBuilder::new();
-        t.section("goal", &["id", "name"], vec![vec![id, name]]);
-        print!("{}", t.finish());
-    } else {
-        println!("Added goal: {}", id);
+impl ContextBudget {
+    // Per-row cost of keys and punctuation around the text itself.
+    const ROW_OVERHEAD: usize = 4;
+    // Rows fetched per section when a budget is set, so the packer picks the cut.
+    const CANDIDATES: i64 = 200;
+
+    fn new(limit: Option<usize>) -> Result<Self, String> {
+        if limit == Some(0) {
+            return Err("--budget must be at least 1 token".to_string());
+        }
+        Ok(Self { limit, used: 0, sections: Vec::new() })
     }
-    Ok(())
-}
 
-fn cmd_add_task(
+    fn candidates(&self, default_limit: i64) -> i64 {
+        if self.limit.is_some() { Self::CANDIDATES.max(default_limit) } else { default_limit }
+    }
+
+    /// Count content that is always shown against the budget.
+    fn charge(&mut self, text: &str) {
+        self.used += approx_tokens(text) + Self::ROW_OVERHEAD;
+    }
+
+    fn fit<T>(&mut self, section: &'static str, rows: Vec<T>, text: impl Fn(&T) -> String) -> Vec<T> {
+        let Some(limit) = self.limit else { return rows };
+        let total = rows.len();
+        let mut kept = Vec::new();
+        for row in rows {
+            let cost = approx_tokens(&text(&row)) + Self::ROW_OVERHEAD;
+            if self.used + cost <= limit {
+                self.used += cost;
+                kept.push(row);
+            }
+        }
+        self.sections.push((section, kept.len(), total - kept.len()));
+        kept
+    }
+
+    fn truncated(&self) -> impl Iterator<Item = &(&'static str, usize, usize)> {
+        self.sections.iter().filter(|s| s.2 > 0)
+    }
+
+    fn to_value(&self) -> Value {
+        let Some(limit) = self.limit else { return Value::Null };
+        let truncated: Vec<Value> = self
+            .truncated()
+            .map(|(section, kept, dropped)| json!({"section": section, "kept": kept, "dropped": dropped}))
+            .collect();
+        json!({"limit": limit, "used": self.used, "truncated": truncated})
+    }
+
+    fn add_toon(&self, t: &mut ToonBuilder) {
+        if let Some(limit) = self.limit {
+            let truncated: Vec<String> = self.truncated().map(|(s, _, dropped)| format!("{s}:{dropped}")).collect();
+            t.section(
+                "budget",
+                &["limit", "used", "truncated"],
+                vec![vec![limit.to_string(), self.used.to_string(), truncated.join(" ")]],
+            );
+        }
+    }
+
+    fn print_human(&self) {
+        let Some(limit) = self.limit else { return };
+        let truncated: Vec<String> = self
+            .truncated()
+            .map(|(section, kept, dropped)| format!("{dropped} of {} {}", kept + dropped, section.replace('_', " ")))
+            .collect();
+        if truncated.is_empty() {
+            println!("\n📦 ~{} of {limit} tokens used, nothing truncated", self.used);
+        } else {
+            println!(
+                "\n📦 ~{} of {limit} tokens used — truncated {} (raise --budget to include them)",
+                self.used,
+                truncated.join(", ")
+            );
+        }
+    }
+}
+
+/// Accepts `90` (seconds), `45s`, `30m`, `2h` or `1d`.
+fn parse_duration_secs(s: &str) -> Result<i64, String> {
+    let s = s.trim();
+    let bad = || format!("invalid duration '{s}' (use e.g. 90s, 30m, 2h, 1d)");
+    let (num, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let n: i64 = num.parse().map_err(|_| bad())?;
+    let secs = match unit {
+        "s" => n,
+        "m" => n * 60,
+        "h" => n * 3600,
+        "d" => n * 86400,
+        _ => return Err(bad()),
+    };
+    if secs <= 0 {
+        return Err(bad());
+    }
+    Ok(secs)
+}
+
+fn format_duration(secs: i64) -> String {
+    match secs {
+        s if s % 86400 == 0 && s >= 86400 => format!("{}d", s / 86400),
+        s if s % 3600 == 0 && s >= 3600 => format!("{}h", s / 3600),
+        s if s % 60 == 0 && s >= 60 => format!("{}m", s / 60),
+        s => format!("{s}s"),
+    }
+}
+
+/// Accepts a unix timestamp, YYYY-MM-DD, or a look-back span such as `24h` / `7d` / `2w`.
+fn parse_since_arg(s: &str) -> Result<i64, String> {
+    let s = s.trim();
+    if let Ok(ts) = s.parse::<i64>() {
+        return Ok(ts);
+    }
+    let span = |suffix: char, secs: i64| s.strip_suffix(suffix).and_then(|n| n.parse::<i64>().ok()).map(|n| now_ts() - n * secs);
+    if let Some(ts) = span('h', 3600).or_else(|| span('d', 86400)).or_else(|| span('w', 7 * 86400)) {
+        return Ok(ts);
+    }
+    parse_date_arg(s).map_err(|_| format!("invalid --since '{s}' (use a unix timestamp, YYYY-MM-DD, or e.g. 24h / 7d)"))
+}
+
+// (at, kind, task_id, agent, detail)
+type HistoryEntry = (i64, String, String, String, String);
+
+/// Everything recorded about a set of tasks, oldest first. `scope` is a SQL
+/// predicate on a `task_id` column that takes the target id as ?1.
+fn query_task_history(conn: &Connection, scope: &str, target: &str, since: i64) -> Result<Vec<HistoryEntry>, String> {
+    let task_scope = scope.replace("task_id", "id");
+    let revision_scope = scope.replace("task_id", "entity_id");
+    let sql = format!(
+        "SELECT at, kind, task_id, agent, detail FROM (
+           SELECT COALESCE(created_at,0) AS at, 0 AS src, rowid AS seq, 'created' AS kind, id AS task_id,
+                  COALESCE(created_by,'') AS agent, title AS detail
+             FROM tasks WHERE {task_scope}
+           UNION ALL
+           SELECT COALESCE(created_at,0), 1, rowid, 'depends_on', task_id, '', depends_on
+             FROM task_dependencies WHERE {scope}
+           UNION ALL
+           SELECT COALESCE(created_at,0), 2, rowid, key, task_id, COALESCE(source,''), value
+             FROM memories WHERE {scope} AND key != 'task_digest'
+           UNION ALL
+           SELECT COALESCE(created_at,0), 2, rowid, key, task_id, COALESCE(source,''), value
+             FROM memory_archive WHERE {scope}
+           UNION ALL
+           SELECT COALESCE(created_at,0), 2, rowid, 'edited', entity_id, COALESCE(agent_id,''),
+                  field || ': ' || COALESCE(old_value,'') || ' → ' || COALESCE(new_value,'')
+             FROM revisions WHERE entity = 'task' AND {revision_scope}
+           UNION ALL
+           SELECT COALESCE(created_at,0), 3, rowid, command, task_id, COALESCE(agent_id,''), ''
+             FROM events WHERE {scope}
+         ) WHERE at >= ?2
+         ORDER BY at, src, seq"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![target, since], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn cmd_history(conn: &Connection, out: OutputCtx, id: String, since: Option<String>) -> Result<(), String> {
+    let since = since.as_deref().map(parse_since_arg).transpose()?.unwrap_or(0);
+    let (scope_kind, target_id, title, mut entries) = if let Some(task_id) = resolve_id_prefix(conn, "tasks", &id)? {
+        let title: String = conn
+            .query_row("SELECT title FROM tasks WHERE id=?1", params![task_id], |r| r.get(0))
+            .map_err(|e| e.to_string())?;
+        let entries = query_task_history(conn, "task_id = ?1", &task_id, since)?;
+        ("task", task_id, title, entries)
+    } else if let Some(goal_id) = resolve_id_prefix(conn, "goals", &id)? {
+        let (name, created_at): (String, i64) = conn
+            .query_row("SELECT name, COALESCE(created_at,0) FROM goals WHERE id=?1", params![goal_id], |r| {
+                Ok((r.get(0)?, r.get(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        let mut entries = query_task_history(conn, "task_id IN (SELECT id FROM tasks WHERE goal_id = ?1)", &goal_id, since)?;
+        if created_at >= since {
+            entries.insert(0, (created_at, "goal_created".to_string(), String::new(), String::new(), name.clone()));
+        }
+        ("goal", goal_id, name, entries)
+    } else {
+        return Err(format!("No task or goal with ID '{id}'"));
+    };
+    entries.sort_by_key(|e| e.0);
+
+    let count = |kinds: &[&str]| entries.iter().filter(|e| kinds.contains(&e.1.as_str())).count();
+    let claims = count(&["task_claimed", "task_started"]);
+    let failures = count(&["failure_reason", "timeout"]);
+    let lock_releases = count(&["lock_expired"]);
+    let mut agents: Vec<String> = entries
+        .iter()
+        .filter(|e| matches!(e.1.as_str(), "task_claimed" | "task_started") && !e.3.is_empty())
+        .map(|e| e.3.clone())
+        .collect();
+    agents.sort();
+    agents.dedup();
+
+    if out.is_json() {
+        println!(
+            "{}",
+            json!({
+                "kind": scope_kind,
+                "id": target_id,
+                "title": title,
+                "since": since,
+                "summary": {"claims": claims, "failures": failures, "lock_releases": lock_releases, "agents": agents},
+                "events": entries.iter().map(|(at, kind, task_id, agent, detail)| json!({
+                    "at": at,
+                    "kind": kind,
+                    "task_id": task_id,
+                    "agent": agent,
+                    "detail": detail,
+                })).collect::<Vec<_>>()
+            })
+        );
+        return Ok(());
+    }
+
+    if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section(
+            "summary",
+            &["kind", "id", "title", "claims", "failures", "lock_releases", "agents"],
+            vec![vec![
+                scope_kind.to_string(),
+                target_id.clone(),
+                title.clone(),
+                claims.to_string(),
+                failures.to_string(),
+                lock_releases.to_string(),
+                agents.join(" "),
+            ]],
+        );
+        t.section(
+            "history",
+            &["at", "kind", "task_id", "agent", "detail"],
+            entries
+                .iter()
+                .map(|(at, kind, task_id, agent, detail)| {
+                    vec![at.to_string(), kind.clone(), task_id.clone(), agent.clone(), detail.clone()]
+                })
+                .collect(),
+        );
+        print!("{}", t.finish());
+        return Ok(());
+    }
+
+    let task_ids = unique_id_prefixes(conn, "tasks")?;
+    let shown_id = if scope_kind == "goal" { display_id(conn, "goals", &target_id)? } else { short_id(&task_ids, &target_id).to_string() };
+    println!("History for {scope_kind} \"{title}\"  {shown_id}");
+    println!(
+        "  {claims} claim(s), {failures} failure(s), {lock_releases} expired lock(s){}",
+        if agents.is_empty() { String::new() } else { format!(", agents: {}", agents.join(", ")) }
+    );
+    if entries.is_empty() {
+        println!("\n  (nothing recorded)");
+        return Ok(());
+    }
+    println!();
+    for (at, kind, task_id, agent, detail) in &entries {
+        let detail = match kind.as_str() {
+            "ping" => "heartbeat".to_string(),
+            "depends_on" => format!("waits on {detail}"),
+            _ => detail.replace('\n', " "),
+        };
+        let who = if agent.is_empty() { String::new() } else { format!("  @{agent}") };
+        let task = if scope_kind == "goal" && !task_id.is_empty() { format!("{}  ", short_id(&task_ids, task_id)) } else { String::new() };
+        println!("  {}  {:<18} {task}{detail}{who}", paint(out, "2", &format_timestamp(*at)), kind);
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_add_goal(
+    conn: &Connection,
+    out: OutputCtx,
+    name: String,
+    desc: Option<String>,
+    priority: Option<String>,
+    why: Option<String>,
+    for_who: Option<String>,
+    success_signal: Option<String>,
+    relevant_files: Vec<String>,
+    context: Option<String>,
+    workspace: Option<String>,
+    requires_review: bool,
+) -> Result<(), String> {
+    let id = gen_typed_id("g");
+    let now = now_ts();
+    let cwd = workspace.unwrap_or_else(|| {
+        env::current_dir()
+            .ok()
+            .map(|x| x.display().to_string())
+            .unwrap_or_default()
+    });
+    let rf_json = if relevant_files.is_empty() {
+        "[]".to_string()
+    } else {
+        serde_json::to_string(&relevant_files).unwrap_or_else(|_| "[]".to_string())
+    };
+
+    conn.execute(
+        "INSERT INTO goals (id, name, description, why, for_who, success_signal, status, priority, context, tags, workspace_path, relevant_files, requires_review, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'todo', ?7, ?8, '[]', ?9, ?10, ?12, ?11, ?11)",
+        params![
+            id,
+            name,
+            desc.unwrap_or_default(),
+            why.unwrap_or_default(),
+            for_who.unwrap_or_default(),
+            success_signal.unwrap_or_default(),
+            priority.unwrap_or_else(|| "medium".to_string()),
+            context.unwrap_or_default(),
+            cwd,
+            rf_json,
+            now,
+            requires_review
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    if out.is_json() {
+        println!("{}", json!({"ok": true, "id": id}));
+    } else if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section("goal", &["id", "name"], vec![vec![id, name]]);
+        print!("{}", t.finish());
+    } else {
+        println!("Added goal: {}", id);
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_add_task(
     conn: &Connection,
     out: OutputCtx,
     goal_prefix: String,
@@ -3040,10 +5455,26 @@ fn cmd_add_task(
     tools: Vec<String>,
     acceptance_criteria: Option<String>,
     workspace: Option<String>,
+    depends_on: Vec<String>,
+    max_attempts: Option<i64>,
+    max_runtime: Option<String>,
+    requires_review: bool,
+    checks: Vec<String>,
 ) -> Result<(), String> {
+    if max_attempts.is_some_and(|n| n < 1) {
+        return Err("--max-attempts must be at least 1".to_string());
+    }
+    let max_runtime = max_runtime.as_deref().map(parse_duration_secs).transpose()?;
     let goal_id = resolve_id_prefix(conn, "goals", &goal_prefix)?
         .ok_or_else(|| format!("goal not found: {goal_prefix}"))?;
-    let id = gen_id();
+    let checks = checks.iter().map(|c| parse_check_spec(c)).collect::<Result<Vec<_>, _>>()?;
+    let mut prerequisites = Vec::new();
+    for dep in depends_on.iter().map(|d| d.trim()).filter(|d| !d.is_empty()) {
+        let dep_id = resolve_id_prefix(conn, "tasks", dep)?
+            .ok_or_else(|| format!("dependency not found: {dep}"))?;
+        prerequisites.push(dep_id);
+    }
+    let id = gen_typed_id("t");
     let now = now_ts();
     let cwd = workspace.unwrap_or_else(|| {
         env::current_dir()
@@ -3063,8 +5494,8 @@ fn cmd_add_task(
     };
 
     conn.execute(
-        "INSERT INTO tasks (id, title, description, why, context, linked_files, tags, time_frame, priority, status, goal_id, execution_format, workspace_path, relevant_files, tools, acceptance_criteria, created_at, updated_at, created_by)
-         VALUES (?1, ?2, ?3, ?4, ?5, '[]', '[]', 'this_week', ?6, 'todo', ?7, 'json', ?8, ?9, ?10, ?11, ?12, ?12, 'user')",
+        "INSERT INTO tasks (id, title, description, why, context, linked_files, tags, time_frame, priority, status, goal_id, execution_format, workspace_path, relevant_files, tools, acceptance_criteria, max_attempts, requires_review, max_runtime, created_at, updated_at, created_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, '[]', '[]', 'this_week', ?6, 'todo', ?7, 'json', ?8, ?9, ?10, ?11, ?13, ?14, ?15, ?12, ?12, 'user')",
         params![
             id,
             title,
@@ -3077,15 +5508,25 @@ fn cmd_add_task(
             rf_json,
             tools_json,
             acceptance_criteria,
-            now
+            now,
+            max_attempts,
+            requires_review.then_some(1),
+            max_runtime
         ],
     )
     .map_err(|e| e.to_string())?;
 
+    for dep_id in &prerequisites {
+        add_task_dependency(conn, &id, dep_id)?;
+    }
+    for (kind, target, expected) in &checks {
+        add_acceptance_check(conn, &id, kind, target, expected)?;
+    }
+
     sync_goal(conn, &goal_id)?;
 
     if out.is_json() {
-        println!("{}", json!({"ok": true, "id": id, "goal_id": goal_id}));
+        println!("{}", json!({"ok": true, "id": id, "goal_id": goal_id, "depends_on": prerequisites}));
     } else if out.is_toon() {
         let mut t = ToonBuilder::new();
         t.section("task", &["id", "goal_id", "title"], vec![vec![id, goal_id, title]]);
@@ -3097,266 +5538,2896 @@ fn cmd_add_task(
     Ok(())
 }
 
-fn cmd_memory(conn: &Connection, out: OutputCtx, action: Option<MemoryAction>) -> Result<(), String> {
-    match action {
-        Some(MemoryAction::Add {
-            goal_id,
-            key,
-            value,
-        }) => {
-            let gid = resolve_id_prefix(conn, "goals", &goal_id)?
-                .ok_or_else(|| format!("goal not found: {goal_id}"))?;
-            let now = now_ts();
-            conn.execute(
-                "INSERT INTO memories (id, goal_id, key, value, type, source, created_at) VALUES (?1, ?2, ?3, ?4, 'learning', 'agent', ?5)",
-                params![gen_id(), gid, key, value, now],
-            )
+/// Apply field changes to one row, recording each changed field in `revisions`.
+/// Returns the (field, old, new) triples that actually changed.
+fn apply_edit(
+    conn: &Connection,
+    table: &str,
+    entity: &str,
+    id: &str,
+    changes: &[(&'static str, String)],
+) -> Result<Vec<(String, String, String)>, String> {
+    let agent = current_agent(None);
+    let now = now_ts();
+    let mut changed = Vec::new();
+    for (column, new_value) in changes {
+        let old_value: String = conn
+            .query_row(&format!("SELECT CAST(COALESCE({column}, '') AS TEXT) FROM {table} WHERE id=?1"), params![id], |r| {
+                r.get(0)
+            })
             .map_err(|e| e.to_string())?;
-            emit_simple_ok(out, "Memory added")
-        }
-        _ => {
-            let memories = query_memories(conn, None, 50)?;
-            if out.is_json() {
-                println!(
-                    "{}",
-                    json!(memories
-                        .iter()
-                        .map(memory_to_value)
-                        .collect::<Vec<_>>())
-                );
-            } else if out.is_toon() {
-                let mut t = ToonBuilder::new();
-                t.section(
-                    "memories",
-                    &["id", "goal_id", "task_id", "key", "value", "type"],
-                    memories
-                        .iter()
-                        .map(|m| {
-                            vec![
-                                m.id.clone(),
-                                m.goal_id.clone().unwrap_or_default(),
-                                m.task_id.clone().unwrap_or_default(),
-                                m.key.clone(),
-                                m.value.clone(),
-                                m.typ.clone(),
-                            ]
-                        })
-                        .collect(),
-                );
-                print!("{}", t.finish());
-            } else if memories.is_empty() {
-                println!("No memories.");
-            } else {
-                for m in memories {
-                    println!("[{}] {} = {}", m.typ, m.key, m.value);
-                }
-            }
-            Ok(())
+        if &old_value == new_value {
+            continue;
         }
+        conn.execute(&format!("UPDATE {table} SET {column}=?1 WHERE id=?2"), params![new_value, id])
+            .map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO revisions (id, entity, entity_id, field, old_value, new_value, agent_id, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![gen_id(), entity, id, column, old_value, new_value, agent, now],
+        )
+        .map_err(|e| e.to_string())?;
+        changed.push((column.to_string(), old_value, new_value.clone()));
+    }
+    if !changed.is_empty() && table != "lessons" {
+        conn.execute(&format!("UPDATE {table} SET updated_at=?1 WHERE id=?2"), params![now, id])
+            .map_err(|e| e.to_string())?;
     }
+    Ok(changed)
 }
 
-fn cmd_lesson(
-    conn: &Connection,
-    out: OutputCtx,
-    mut args: Vec<String>,
-    correct_behavior: Option<String>,
-    verified_by: Option<String>,
-) -> Result<(), String> {
-    if args.first().map(|s| s.eq_ignore_ascii_case("add")).unwrap_or(false) {
-        let _ = args.remove(0);
-    }
-    let what_went_wrong = args.join(" ").trim().to_string();
-    if what_went_wrong.is_empty() {
-        return Err("lesson text is required".to_string());
-    }
+#[allow(clippy::type_complexity)]
+fn query_revisions(conn: &Connection, entity: &str, id: &str) -> Result<Vec<(String, String, String, String, i64)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT field, COALESCE(old_value,''), COALESCE(new_value,''), COALESCE(agent_id,''), COALESCE(created_at,0)
+             FROM revisions WHERE entity=?1 AND entity_id=?2
+             ORDER BY COALESCE(created_at,0), rowid",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![entity, id], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
 
-    let correct_behavior = match correct_behavior {
-        Some(v) if !v.trim().is_empty() => v.trim().to_string(),
-        _ => {
-            let v = ops_read_line("Correct behavior: ")?;
-            if v.trim().is_empty() {
-                return Err("correct behavior is required".to_string());
+fn json_list(items: Vec<String>) -> String {
+    let items: Vec<String> = items.into_iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+    serde_json::to_string(&items).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn cmd_edit(conn: &mut Connection, out: OutputCtx, target: EditTarget) -> Result<(), String> {
+    let mut moved_from_goal: Option<String> = None;
+    let (entity, table, id, changes): (&str, &str, String, Vec<(&'static str, String)>) = match target {
+        EditTarget::Task {
+            task_id,
+            title,
+            desc,
+            why,
+            priority,
+            context,
+            acceptance_criteria,
+            goal,
+            relevant_files,
+            tools,
+            workspace,
+            requires_review,
+            max_runtime,
+        } => {
+            let task = resolve_task(conn, &task_id)?;
+            let mut changes = Vec::new();
+            if let Some(goal) = goal {
+                let goal_id = resolve_id_prefix(conn, "goals", &goal)?.ok_or_else(|| format!("goal not found: {goal}"))?;
+                if task.goal_id.as_deref() != Some(goal_id.as_str()) {
+                    moved_from_goal = task.goal_id.clone();
+                }
+                changes.push(("goal_id", goal_id));
             }
-            v
+            changes.extend(title.map(|v| ("title", v)));
+            changes.extend(desc.map(|v| ("description", v)));
+            changes.extend(why.map(|v| ("why", v)));
+            changes.extend(priority.map(|v| ("priority", v)));
+            changes.extend(context.map(|v| ("context", v)));
+            changes.extend(acceptance_criteria.map(|v| ("acceptance_criteria", v)));
+            changes.extend(relevant_files.map(|v| ("relevant_files", json_list(v))));
+            changes.extend(tools.map(|v| ("tools", json_list(v))));
+            changes.extend(workspace.map(|v| ("workspace_path", v)));
+            changes.extend(requires_review.map(|v| ("requires_review", i64::from(v).to_string())));
+            if let Some(v) = max_runtime {
+                changes.push(("max_runtime", parse_duration_secs(&v)?.to_string()));
+            }
+            ("task", "tasks", task.id, changes)
+        }
+        EditTarget::Goal {
+            goal_id,
+            name,
+            desc,
+            why,
+            for_who,
+            success_signal,
+            priority,
+            context,
+            relevant_files,
+            requires_review,
+        } => {
+            let id = resolve_id_prefix(conn, "goals", &goal_id)?.ok_or_else(|| format!("goal not found: {goal_id}"))?;
+            let mut changes = Vec::new();
+            changes.extend(name.map(|v| ("name", v)));
+            changes.extend(desc.map(|v| ("description", v)));
+            changes.extend(why.map(|v| ("why", v)));
+            changes.extend(for_who.map(|v| ("for_who", v)));
+            changes.extend(success_signal.map(|v| ("success_signal", v)));
+            changes.extend(priority.map(|v| ("priority", v)));
+            changes.extend(context.map(|v| ("context", v)));
+            changes.extend(relevant_files.map(|v| ("relevant_files", json_list(v))));
+            changes.extend(requires_review.map(|v| ("requires_review", i64::from(v).to_string())));
+            ("goal", "goals", id, changes)
+        }
+        EditTarget::Decision { decision_id, what, why, affects } => {
+            let id = resolve_id_prefix(conn, "decisions", &decision_id)?
+                .ok_or_else(|| format!("decision not found: {decision_id}"))?;
+            let mut changes = Vec::new();
+            changes.extend(what.map(|v| ("what", v)));
+            changes.extend(why.map(|v| ("why", v)));
+            changes.extend(affects.map(|v| ("affects", v)));
+            ("decision", "decisions", id, changes)
+        }
+        EditTarget::Lesson {
+            lesson_id,
+            what_went_wrong,
+            correct_behavior,
+            verified_by,
+        } => {
+            let id = resolve_id_prefix(conn, "lessons", &lesson_id)?
+                .ok_or_else(|| format!("lesson not found: {lesson_id}"))?;
+            let mut changes = Vec::new();
+            changes.extend(what_went_wrong.map(|v| ("what_went_wrong", v)));
+            changes.extend(correct_behavior.map(|v| ("correct_behavior", v)));
+            changes.extend(verified_by.map(|v| ("verified_by", v)));
+            ("lesson", "lessons", id, changes)
         }
     };
-    let verified_by = verified_by
-        .map(|v| v.trim().to_string())
-        .filter(|v| !v.is_empty())
-        .unwrap_or_else(|| "human".to_string());
 
-    conn.execute(
-        "INSERT INTO lessons (id, what_went_wrong, correct_behavior, verified_by, created_at)
-         VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![gen_id(), what_went_wrong, correct_behavior, verified_by, now_ts()],
-    )
-    .map_err(|e| e.to_string())?;
+    let required: &[&str] = match entity {
+        "task" => &["title"],
+        "goal" => &["name"],
+        "decision" => &["what", "why"],
+        _ => &["what_went_wrong", "correct_behavior"],
+    };
+    if let Some((field, _)) = changes.iter().find(|(f, v)| required.contains(f) && v.trim().is_empty()) {
+        return Err(format!("--{} cannot be empty", field.replace('_', "-")));
+    }
 
-    emit_simple_ok(out, "Lesson added")
+    if changes.is_empty() {
+        let revisions = query_revisions(conn, entity, &id)?;
+        if out.is_json() {
+            let items: Vec<Value> = revisions
+                .iter()
+                .map(|(field, old, new, agent, at)| json!({"field": field, "old": old, "new": new, "agent": agent, "created_at": at}))
+                .collect();
+            println!("{}", json!({"entity": entity, "id": id, "revisions": items}));
+        } else if out.is_toon() {
+            let mut t = ToonBuilder::new();
+            t.section(
+                "revisions",
+                &["field", "old", "new", "agent", "created_at"],
+                revisions
+                    .into_iter()
+                    .map(|(field, old, new, agent, at)| vec![field, old, new, agent, at.to_string()])
+                    .collect(),
+            );
+            print!("{}", t.finish());
+        } else if revisions.is_empty() {
+            let id = display_id(conn, table, &id)?;
+            println!("No edits recorded for {entity} {id}. Pass field flags (see `imi edit {entity} --help`) to change it.");
+        } else {
+            println!("Revisions of {entity} {}:", display_id(conn, table, &id)?);
+            for (field, old, new, agent, at) in &revisions {
+                let who = if agent.is_empty() { String::new() } else { format!("  @{agent}") };
+                println!("  {}  {field}{who}\n    - {}\n    + {}", format_timestamp(*at), old.replace('\n', " "), new.replace('\n', " "));
+            }
+        }
+        return Ok(());
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let changed = apply_edit(&tx, table, entity, &id, &changes)?;
+    if entity == "task" {
+        if let Some(old_goal) = &moved_from_goal {
+            // Memories follow the task so goal-scoped context still finds them.
+            tx.execute(
+                "UPDATE memories SET goal_id=(SELECT goal_id FROM tasks WHERE id=?1) WHERE task_id=?1",
+                params![id],
+            )
+            .map_err(|e| e.to_string())?;
+            sync_goal(&tx, old_goal)?;
+        }
+        let goal_id: Option<String> = tx
+            .query_row("SELECT goal_id FROM tasks WHERE id=?1", params![id], |r| r.get(0))
+            .map_err(|e| e.to_string())?;
+        if let Some(gid) = goal_id {
+            sync_goal(&tx, &gid)?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if out.is_json() {
+        let items: Vec<Value> = changed
+            .iter()
+            .map(|(field, old, new)| json!({"field": field, "old": old, "new": new}))
+            .collect();
+        println!("{}", json!({"ok": true, "entity": entity, "id": id, "changed": items}));
+    } else if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section(
+            "edited",
+            &["field", "old", "new"],
+            changed.into_iter().map(|(field, old, new)| vec![field, old, new]).collect(),
+        );
+        print!("{}", t.finish());
+    } else if changed.is_empty() {
+        println!("Nothing changed — {entity} {} already has those values.", display_id(conn, table, &id)?);
+    } else {
+        let fields: Vec<&str> = changed.iter().map(|c| c.0.as_str()).collect();
+        println!("✏️  Updated {entity} {}: {} (old values kept as revisions)", display_id(conn, table, &id)?, fields.join(", "));
+    }
+    Ok(())
 }
 
-fn cmd_lessons(conn: &Connection, out: OutputCtx) -> Result<(), String> {
-    let lessons = query_lessons(conn, 200)?;
+fn cmd_depend(conn: &Connection, out: OutputCtx, task_id: String, depends_on: String, remove: bool) -> Result<(), String> {
+    let task = resolve_task(conn, &task_id)?;
+    let prereq = resolve_task(conn, &depends_on)?;
+
+    if remove {
+        let n = conn
+            .execute(
+                "DELETE FROM task_dependencies WHERE task_id=?1 AND depends_on=?2",
+                params![task.id, prereq.id],
+            )
+            .map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err(format!("{} does not depend on {}", task.id, prereq.id));
+        }
+        return emit_simple_ok(out, &format!("Dependency removed: {} no longer waits on {}", task.id, prereq.id));
+    }
+
+    add_task_dependency(conn, &task.id, &prereq.id)?;
     if out.is_json() {
         println!(
             "{}",
-            json!(lessons
+            json!({"ok": true, "task_id": task.id, "depends_on": prereq.id, "prerequisite_status": prereq.status})
+        );
+    } else if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section(
+            "dependency",
+            &["task_id", "depends_on", "prerequisite_status"],
+            vec![vec![task.id, prereq.id, prereq.status]],
+        );
+        print!("{}", t.finish());
+    } else {
+        println!("🔗 {} now waits on {} ({})", task.title, prereq.title, display_id(conn, "tasks", &prereq.id)?);
+        if prereq.status != "done" {
+            println!("   It won't be claimed by `imi next` until that task is done.");
+        }
+    }
+    Ok(())
+}
+
+fn cmd_memory(conn: &mut Connection, out: OutputCtx, cfg: &Config, action: Option<MemoryAction>) -> Result<(), String> {
+    match action {
+        Some(MemoryAction::Add {
+            goal_id,
+            key,
+            value,
+            kind,
+            ttl,
+        }) => {
+            let gid = resolve_id_prefix(conn, "goals", &goal_id)?
+                .ok_or_else(|| format!("goal not found: {goal_id}"))?;
+            let kind = validate_memory_type(kind.as_deref().unwrap_or("learning"))?;
+            let now = now_ts();
+            let expires_at = ttl.as_deref().map(parse_duration_secs).transpose()?.map(|secs| now + secs);
+            conn.execute(
+                "INSERT INTO memories (id, goal_id, key, value, type, source, created_at, expires_at) VALUES (?1, ?2, ?3, ?4, ?5, 'agent', ?6, ?7)",
+                params![gen_typed_id("m"), gid, key, value, kind, now, expires_at],
+            )
+            .map_err(|e| e.to_string())?;
+            match expires_at {
+                Some(at) => emit_simple_ok(out, &format!("Memory added (expires {})", format_date(at))),
+                None => emit_simple_ok(out, "Memory added"),
+            }
+        }
+        Some(MemoryAction::Compact { goal, older_than, dry_run }) => {
+            cmd_memory_compact(conn, out, cfg, goal, older_than, dry_run)
+        }
+        Some(MemoryAction::List { archived: true, .. }) => cmd_memory_archive_list(conn, out),
+        Some(MemoryAction::List { kind, goal, task, source, expired, .. }) => {
+            let kind = kind.as_deref().map(validate_memory_type).transpose()?;
+            let goal = match goal {
+                Some(prefix) => Some(resolve_id_prefix(conn, "goals", &prefix)?.ok_or_else(|| format!("goal not found: {prefix}"))?),
+                None => None,
+            };
+            let task = match task {
+                Some(prefix) => Some(resolve_id_prefix(conn, "tasks", &prefix)?.ok_or_else(|| format!("task not found: {prefix}"))?),
+                None => None,
+            };
+            let memories = query_memory_list(conn, kind, goal.as_deref(), task.as_deref(), source.as_deref(), expired)?;
+            print_memory_list(out, &memories)
+        }
+        None => print_memory_list(out, &query_memories(conn, None, 50)?),
+    }
+}
+
+/// Every value `memories.type` may hold. `lifecycle`, `checkpoint` and `digest` are
+/// written by imi itself; the rest come from agents and humans.
+const MEMORY_TYPES: &[&str] = &["learning", "completion", "failure", "outcome", "lifecycle", "checkpoint", "digest"];
+
+fn validate_memory_type(kind: &str) -> Result<&'static str, String> {
+    let kind = kind.trim().to_ascii_lowercase();
+    MEMORY_TYPES
+        .iter()
+        .find(|k| **k == kind)
+        .copied()
+        .ok_or_else(|| format!("unknown memory type '{kind}' (expected one of: {})", MEMORY_TYPES.join(", ")))
+}
+
+fn query_memory_list(
+    conn: &Connection,
+    kind: Option<&str>,
+    goal_id: Option<&str>,
+    task_id: Option<&str>,
+    source: Option<&str>,
+    include_expired: bool,
+) -> Result<Vec<MemoryRow>, String> {
+    let live = if include_expired { "1" } else { MEMORY_LIVE_SQL };
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, goal_id, task_id, key, value, COALESCE(type,'learning'), COALESCE(source,'agent'), COALESCE(created_at,0), expires_at
+             FROM memories
+             WHERE (?1 IS NULL OR COALESCE(type,'learning') = ?1)
+               AND (?2 IS NULL OR goal_id = ?2 OR task_id IN (SELECT id FROM tasks WHERE goal_id = ?2))
+               AND (?3 IS NULL OR task_id = ?3)
+               AND (?4 IS NULL OR COALESCE(source,'agent') = ?4)
+               AND {live}
+             ORDER BY COALESCE(created_at,0) DESC LIMIT 50"
+        ))
+        .map_err(|e| e.to_string())?;
+    let mapped = stmt
+        .query_map(params![kind, goal_id, task_id, source], |r| {
+            Ok(MemoryRow {
+                id: r.get(0)?,
+                goal_id: r.get(1)?,
+                task_id: r.get(2)?,
+                key: r.get(3)?,
+                value: r.get(4)?,
+                typ: r.get(5)?,
+                source: r.get(6)?,
+                created_at: r.get(7)?,
+                expires_at: r.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    mapped.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn print_memory_list(out: OutputCtx, memories: &[MemoryRow]) -> Result<(), String> {
+    if out.is_json() {
+        println!(
+            "{}",
+            json!(memories
                 .iter()
-                .map(lesson_to_value)
+                .map(memory_to_value)
                 .collect::<Vec<_>>())
         );
     } else if out.is_toon() {
         let mut t = ToonBuilder::new();
         t.section(
-            "verified_lessons",
-            &["id", "what_went_wrong", "correct_behavior", "verified_by", "created_at"],
-            lessons
+            "memories",
+            &["id", "goal_id", "task_id", "key", "value", "type", "expires_at"],
+            memories
                 .iter()
-                .map(|l| {
+                .map(|m| {
                     vec![
-                        l.id.clone(),
-                        l.what_went_wrong.clone(),
-                        l.correct_behavior.clone(),
-                        l.verified_by.clone(),
-                        l.created_at.to_string(),
+                        m.id.clone(),
+                        m.goal_id.clone().unwrap_or_default(),
+                        m.task_id.clone().unwrap_or_default(),
+                        m.key.clone(),
+                        m.value.clone(),
+                        m.typ.clone(),
+                        m.expires_at.map(|t| t.to_string()).unwrap_or_default(),
                     ]
                 })
                 .collect(),
         );
         print!("{}", t.finish());
-    } else if lessons.is_empty() {
-        println!("No lessons.");
+    } else if memories.is_empty() {
+        println!("No memories.");
     } else {
-        for l in lessons {
-            println!(
-                "- {}\n  correct behavior: {}\n  verified by: {} ({} ago)\n",
-                l.what_went_wrong,
-                l.correct_behavior,
-                l.verified_by,
-                ago(l.created_at)
-            );
+        let now = now_ts();
+        for m in memories {
+            let expiry = match m.expires_at {
+                Some(at) if at <= now => format!("  (expired {})", format_date(at)),
+                Some(at) => format!("  (expires {})", format_date(at)),
+                None => String::new(),
+            };
+            println!("[{}] {} = {}{expiry}", m.typ, m.key, m.value);
         }
     }
     Ok(())
 }
 
-fn cmd_decide(
-    conn: &Connection,
-    out: OutputCtx,
-    what: String,
-    why: String,
-    affects: Option<String>,
-) -> Result<(), String> {
-    let now = now_ts();
-    let id = gen_id();
+const COMPACTABLE_MEMORY_TYPES: &str = "'lifecycle', 'checkpoint'";
+
+/// Move one memory row into `memory_archive`.
+fn archive_memory(conn: &Connection, id: &str, reason: &str, digest_id: Option<&str>, now: i64) -> Result<(), String> {
     conn.execute(
-        "INSERT INTO decisions (id, what, why, affects, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![id, what, why, affects.unwrap_or_default(), now],
+        "INSERT INTO memory_archive (id, goal_id, task_id, key, value, type, reasoning, source, created_at, expires_at, archived_at, reason, digest_id)
+         SELECT id, goal_id, task_id, key, value, type, reasoning, source, created_at, expires_at, ?2, ?3, ?4
+         FROM memories WHERE id=?1",
+        params![id, now, reason, digest_id],
     )
     .map_err(|e| e.to_string())?;
-
-    emit_simple_ok(out, "Decision recorded")
+    conn.execute("DELETE FROM memories WHERE id=?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
 }
 
-fn cmd_log(conn: &Connection, out: OutputCtx, note: String) -> Result<(), String> {
-    if note.trim().is_empty() {
-        return Err("note is required".to_string());
+/// One-line summary of every lifecycle and checkpoint row archived for a task.
+fn task_digest_text(conn: &Connection, task_id: &str) -> Result<(String, i64), String> {
+    let rows: Vec<(String, String, String, i64)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT key, value, COALESCE(source,''), COALESCE(created_at,0) FROM memory_archive
+                 WHERE task_id=?1 AND reason='compacted'
+                 ORDER BY COALESCE(created_at,0)",
+            )
+            .map_err(|e| e.to_string())?;
+        let mapped = stmt
+            .query_map(params![task_id], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+            .map_err(|e| e.to_string())?;
+        mapped.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+    };
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    let mut agents: Vec<String> = Vec::new();
+    for (key, _, source, _) in &rows {
+        match counts.iter_mut().find(|(k, _)| k == key) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((key.clone(), 1)),
+        }
+        if !source.is_empty() && source != "imi" && !agents.contains(source) {
+            agents.push(source.clone());
+        }
+    }
+    let first = rows.first().map(|r| r.3).unwrap_or(0);
+    let last = rows.last().map(|r| r.3).unwrap_or(0);
+    let mut text = format!(
+        "{} lifecycle row(s) from {} to {}: {}",
+        rows.len(),
+        format_date(first),
+        format_date(last),
+        counts.iter().map(|(k, n)| format!("{k} ×{n}")).collect::<Vec<_>>().join(", ")
+    );
+    if !agents.is_empty() {
+        text.push_str(&format!("; agents: {}", agents.join(", ")));
     }
+    if let Some((_, value, _, _)) = rows.iter().rev().find(|r| r.0 == "checkpoint") {
+        text.push_str(&format!("; last checkpoint: {value}"));
+    }
+    Ok((text, last))
+}
+
+fn cmd_memory_compact(
+    conn: &mut Connection,
+    out: OutputCtx,
+    cfg: &Config,
+    goal: Option<String>,
+    older_than: Option<String>,
+    dry_run: bool,
+) -> Result<(), String> {
+    let age = match older_than {
+        Some(v) => parse_duration_secs(&v)?,
+        None => cfg.duration_secs("memory.compact_after"),
+    };
+    let goal_id = match goal {
+        Some(prefix) => Some(
+            resolve_id_prefix(conn, "goals", &prefix)?.ok_or_else(|| format!("goal not found: {prefix}"))?,
+        ),
+        None => None,
+    };
     let now = now_ts();
-    let id = gen_id();
-    let author = current_agent(None);
-    conn.execute(
-        "INSERT INTO direction_notes (id, content, author, created_at) VALUES (?1, ?2, ?3, ?4)",
-        params![id, note, author, now],
-    )
-    .map_err(|e| e.to_string())?;
-    emit_simple_ok(out, "Direction note added")
+    let cutoff = now - age;
+
+    // Lifecycle noise of tasks nobody is working on, grouped per task.
+    let lifecycle: Vec<(String, String)> = {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT m.id, m.task_id FROM memories m JOIN tasks t ON t.id = m.task_id
+                 WHERE m.type IN ({COMPACTABLE_MEMORY_TYPES}) AND t.status != 'in_progress'
+                   AND COALESCE(m.created_at,0) < ?1 AND (?2 IS NULL OR t.goal_id = ?2)
+                 ORDER BY m.task_id, COALESCE(m.created_at,0)"
+            ))
+            .map_err(|e| e.to_string())?;
+        let mapped = stmt
+            .query_map(params![cutoff, goal_id], |r| Ok((r.get(0)?, r.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        mapped.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+    };
+    // Older copies of a value already stored under the same key and owner; the newest copy stays.
+    let duplicates: Vec<String> = {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT m.id FROM memories m
+                 WHERE m.type NOT IN ({COMPACTABLE_MEMORY_TYPES}, 'digest')
+                   AND COALESCE(m.created_at,0) < ?1
+                   AND (?2 IS NULL OR m.goal_id = ?2 OR m.task_id IN (SELECT id FROM tasks WHERE goal_id = ?2))
+                   AND EXISTS (
+                     SELECT 1 FROM memories n
+                     WHERE n.id != m.id AND n.key = m.key AND n.value = m.value
+                       AND COALESCE(n.goal_id,'') = COALESCE(m.goal_id,'')
+                       AND COALESCE(n.task_id,'') = COALESCE(m.task_id,'')
+                       AND (COALESCE(n.created_at,0), n.rowid) > (COALESCE(m.created_at,0), m.rowid)
+                   )"
+            ))
+            .map_err(|e| e.to_string())?;
+        let mapped = stmt
+            .query_map(params![cutoff, goal_id], |r| r.get(0))
+            .map_err(|e| e.to_string())?;
+        mapped.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+    };
+    let mut task_ids: Vec<String> = lifecycle.iter().map(|(_, t)| t.clone()).collect();
+    task_ids.dedup();
+
+    if !dry_run {
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        for task_id in &task_ids {
+            // A task compacted before gets its digest rebuilt from the whole archive.
+            tx.execute(
+                "DELETE FROM memories WHERE task_id=?1 AND key='task_digest'",
+                params![task_id],
+            )
+            .map_err(|e| e.to_string())?;
+            let digest_id = gen_typed_id("m");
+            for (id, _) in lifecycle.iter().filter(|(_, t)| t == task_id) {
+                archive_memory(&tx, id, "compacted", Some(&digest_id), now)?;
+            }
+            tx.execute(
+                "UPDATE memory_archive SET digest_id=?2 WHERE task_id=?1 AND reason='compacted'",
+                params![task_id, digest_id],
+            )
+            .map_err(|e| e.to_string())?;
+            let (text, last_at) = task_digest_text(&tx, task_id)?;
+            tx.execute(
+                "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
+                 SELECT ?1, goal_id, id, 'task_digest', ?2, 'digest', 'imi memory compact', 'imi', ?3
+                 FROM tasks WHERE id=?4",
+                params![digest_id, text, last_at, task_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        for id in &duplicates {
+            archive_memory(&tx, id, "duplicate", None, now)?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    let archived = lifecycle.len() + duplicates.len();
+    if out.is_json() {
+        println!(
+            "{}",
+            json!({
+                "ok": true,
+                "dry_run": dry_run,
+                "older_than_secs": age,
+                "tasks_digested": task_ids.len(),
+                "lifecycle_archived": lifecycle.len(),
+                "duplicates_archived": duplicates.len(),
+                "archived": archived
+            })
+        );
+    } else if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section(
+            "compact",
+            &["dry_run", "tasks_digested", "lifecycle_archived", "duplicates_archived"],
+            vec![vec![
+                dry_run.to_string(),
+                task_ids.len().to_string(),
+                lifecycle.len().to_string(),
+                duplicates.len().to_string(),
+            ]],
+        );
+        print!("{}", t.finish());
+    } else if archived == 0 {
+        println!("Nothing to compact (rows older than {}).", format_duration(age));
+    } else {
+        let verb = if dry_run { "Would fold" } else { "Folded" };
+        println!(
+            "{verb} {} lifecycle/checkpoint row(s) into {} task digest(s) and archive {} duplicate(s).",
+            lifecycle.len(),
+            task_ids.len(),
+            duplicates.len()
+        );
+        if !dry_run {
+            println!("Archived rows stay in `imi memory list --archived` and in `imi history`.");
+        }
+    }
+    Ok(())
+}
+
+fn cmd_memory_archive_list(conn: &Connection, out: OutputCtx) -> Result<(), String> {
+    let rows: Vec<(MemoryRow, String, i64)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, goal_id, task_id, key, value, COALESCE(type,'learning'), COALESCE(source,'agent'),
+                        COALESCE(created_at,0), expires_at, reason, archived_at
+                 FROM memory_archive
+                 ORDER BY archived_at DESC, COALESCE(created_at,0) DESC
+                 LIMIT 200",
+            )
+            .map_err(|e| e.to_string())?;
+        let mapped = stmt
+            .query_map([], |r| {
+                Ok((
+                    MemoryRow {
+                        id: r.get(0)?,
+                        goal_id: r.get(1)?,
+                        task_id: r.get(2)?,
+                        key: r.get(3)?,
+                        value: r.get(4)?,
+                        typ: r.get(5)?,
+                        source: r.get(6)?,
+                        created_at: r.get(7)?,
+                        expires_at: r.get(8)?,
+                    },
+                    r.get(9)?,
+                    r.get(10)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        mapped.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+    };
+    if out.is_json() {
+        let items: Vec<Value> = rows
+            .iter()
+            .map(|(m, reason, archived_at)| {
+                let mut v = memory_to_value(m);
+                v["reason"] = json!(reason);
+                v["archived_at"] = json!(archived_at);
+                v
+            })
+            .collect();
+        println!("{}", json!(items));
+    } else if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section(
+            "memory_archive",
+            &["id", "task_id", "key", "value", "type", "reason"],
+            rows.iter()
+                .map(|(m, reason, _)| {
+                    vec![
+                        m.id.clone(),
+                        m.task_id.clone().unwrap_or_default(),
+                        m.key.clone(),
+                        m.value.clone(),
+                        m.typ.clone(),
+                        reason.clone(),
+                    ]
+                })
+                .collect(),
+        );
+        print!("{}", t.finish());
+    } else if rows.is_empty() {
+        println!("No archived memories.");
+    } else {
+        for (m, reason, _) in rows {
+            println!("[{}] {} = {}  ({reason})", m.typ, m.key, m.value);
+        }
+    }
+    Ok(())
+}
+
+fn cmd_lesson(
+    conn: &Connection,
+    out: OutputCtx,
+    mut args: Vec<String>,
+    correct_behavior: Option<String>,
+    verified_by: Option<String>,
+) -> Result<(), String> {
+    if args.first().map(|s| s.eq_ignore_ascii_case("add")).unwrap_or(false) {
+        let _ = args.remove(0);
+    }
+    let what_went_wrong = args.join(" ").trim().to_string();
+    if what_went_wrong.is_empty() {
+        return Err("lesson text is required".to_string());
+    }
+
+    let correct_behavior = match correct_behavior {
+        Some(v) if !v.trim().is_empty() => v.trim().to_string(),
+        _ => {
+            let v = ops_read_line("Correct behavior: ")?;
+            if v.trim().is_empty() {
+                return Err("correct behavior is required".to_string());
+            }
+            v
+        }
+    };
+    let verified_by = verified_by
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "human".to_string());
+
+    conn.execute(
+        "INSERT INTO lessons (id, what_went_wrong, correct_behavior, verified_by, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![gen_typed_id("l"), what_went_wrong, correct_behavior, verified_by, now_ts()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    emit_simple_ok(out, "Lesson added")
+}
+
+fn cmd_lessons(conn: &Connection, out: OutputCtx) -> Result<(), String> {
+    let lessons = query_lessons(conn, 200)?;
+    if out.is_json() {
+        println!(
+            "{}",
+            json!(lessons
+                .iter()
+                .map(lesson_to_value)
+                .collect::<Vec<_>>())
+        );
+    } else if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section(
+            "verified_lessons",
+            &["id", "what_went_wrong", "correct_behavior", "verified_by", "created_at"],
+            lessons
+                .iter()
+                .map(|l| {
+                    vec![
+                        l.id.clone(),
+                        l.what_went_wrong.clone(),
+                        l.correct_behavior.clone(),
+                        l.verified_by.clone(),
+                        l.created_at.to_string(),
+                    ]
+                })
+                .collect(),
+        );
+        print!("{}", t.finish());
+    } else if lessons.is_empty() {
+        println!("No lessons.");
+    } else {
+        for l in lessons {
+            println!(
+                "- {}\n  correct behavior: {}\n  verified by: {} ({} ago)\n",
+                l.what_went_wrong,
+                l.correct_behavior,
+                l.verified_by,
+                ago(l.created_at)
+            );
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_decide(
+    conn: &mut Connection,
+    out: OutputCtx,
+    what: String,
+    why: String,
+    affects: Option<String>,
+    goals: Vec<String>,
+    tasks: Vec<String>,
+    supersedes: Option<String>,
+    revisit_after: Option<String>,
+) -> Result<(), String> {
+    let (goal_ids, task_ids) = resolve_link_targets(conn, &goals, &tasks)?;
+    let revisit_after = revisit_after.as_deref().map(parse_date_arg).transpose()?;
+    let superseded = match supersedes {
+        Some(prefix) => Some(resolve_active_decision(conn, &prefix)?),
+        None => None,
+    };
+    let now = now_ts();
+    let id = gen_typed_id("d");
+    // One transaction, so a failure can't leave the old decision superseded
+    // by a replacement that was never written.
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO decisions (id, what, why, affects, revisit_after, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![id, what, why, affects.unwrap_or_default(), revisit_after, now],
+    )
+    .map_err(|e| e.to_string())?;
+    for g in &goal_ids {
+        link_decision(&tx, &id, Some(g), None, "explicit")?;
+    }
+    for t in &task_ids {
+        link_decision(&tx, &id, None, Some(t), "explicit")?;
+    }
+    if let Some(old) = &superseded {
+        tx.execute(
+            "UPDATE decisions SET status='superseded', superseded_by=?1, updated_at=?2 WHERE id=?3",
+            params![id, now, old],
+        )
+        .map_err(|e| e.to_string())?;
+        // The replacement governs whatever the old call governed.
+        let (old_goals, old_tasks) = decision_link_ids(&tx, old)?;
+        for g in &old_goals {
+            link_decision(&tx, &id, Some(g), None, "explicit")?;
+        }
+        for t in &old_tasks {
+            link_decision(&tx, &id, None, Some(t), "explicit")?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let mut message = "Decision recorded".to_string();
+    if !goal_ids.is_empty() || !task_ids.is_empty() {
+        message.push_str(&format!(" and linked to {} goal(s), {} task(s)", goal_ids.len(), task_ids.len()));
+    }
+    if let Some(old) = &superseded {
+        message.push_str(&format!("; supersedes {old}"));
+    }
+    emit_simple_ok(out, &message)
+}
+
+fn resolve_active_decision(conn: &Connection, prefix: &str) -> Result<String, String> {
+    let id = resolve_id_prefix(conn, "decisions", prefix)?.ok_or_else(|| format!("Decision not found: {prefix}"))?;
+    let status: String = conn
+        .query_row("SELECT status FROM decisions WHERE id=?1", params![id], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    if status != "active" {
+        return Err(format!("decision {id} is already {status}"));
+    }
+    Ok(id)
+}
+
+fn resolve_link_targets(conn: &Connection, goals: &[String], tasks: &[String]) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut goal_ids = Vec::new();
+    for g in goals.iter().map(|g| g.trim()).filter(|g| !g.is_empty()) {
+        goal_ids.push(resolve_id_prefix(conn, "goals", g)?.ok_or_else(|| format!("Goal not found: {g}"))?);
+    }
+    let mut task_ids = Vec::new();
+    for t in tasks.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        task_ids.push(resolve_task(conn, t)?.id);
+    }
+    Ok((goal_ids, task_ids))
+}
+
+/// Case-insensitive whole-word containment, so a goal called "api" doesn't match "rapid".
+fn mentions_phrase(text: &str, phrase: &str) -> bool {
+    let (text, phrase) = (text.to_lowercase(), phrase.trim().to_lowercase());
+    if phrase.is_empty() {
+        return false;
+    }
+    text.match_indices(&phrase).any(|(i, m)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + m.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+fn cmd_decision(conn: &Connection, out: OutputCtx, action: DecisionAction) -> Result<(), String> {
+    match action {
+        DecisionAction::Link {
+            decision_id,
+            goal,
+            task,
+            remove,
+        } => {
+            let id = resolve_id_prefix(conn, "decisions", &decision_id)?
+                .ok_or_else(|| format!("Decision not found: {decision_id}"))?;
+            let (goal_ids, task_ids) = resolve_link_targets(conn, &goal, &task)?;
+            if goal_ids.is_empty() && task_ids.is_empty() {
+                return Err("pass at least one --goal or --task".to_string());
+            }
+            let mut changed = 0usize;
+            for g in &goal_ids {
+                changed += if remove {
+                    conn.execute("DELETE FROM decision_links WHERE decision_id=?1 AND goal_id=?2", params![id, g])
+                        .map_err(|e| e.to_string())?
+                } else {
+                    link_decision(conn, &id, Some(g), None, "explicit")? as usize
+                };
+            }
+            for t in &task_ids {
+                changed += if remove {
+                    conn.execute("DELETE FROM decision_links WHERE decision_id=?1 AND task_id=?2", params![id, t])
+                        .map_err(|e| e.to_string())?
+                } else {
+                    link_decision(conn, &id, None, Some(t), "explicit")? as usize
+                };
+            }
+            let verb = if remove { "removed" } else { "added" };
+            emit_simple_ok(out, &format!("{changed} decision link(s) {verb}"))
+        }
+        DecisionAction::Revoke { decision_id, why } => {
+            let why = why.join(" ");
+            if why.trim().is_empty() {
+                return Err("say why the decision is revoked: imi decision revoke <id> \"why\"".to_string());
+            }
+            let id = resolve_active_decision(conn, &decision_id)?;
+            conn.execute(
+                "UPDATE decisions SET status='revoked', status_reason=?1, updated_at=?2 WHERE id=?3",
+                params![why, now_ts(), id],
+            )
+            .map_err(|e| e.to_string())?;
+            emit_simple_ok(out, &format!("Decision {id} revoked"))
+        }
+        DecisionAction::List { all } => {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, what, why, status, COALESCE(status_reason,''), COALESCE(superseded_by,''), revisit_after, COALESCE(created_at,0)
+                     FROM decisions WHERE ?1 OR status='active'
+                     ORDER BY COALESCE(created_at,0) DESC, id",
+                )
+                .map_err(|e| e.to_string())?;
+            #[allow(clippy::type_complexity)]
+            let rows: Vec<(String, String, String, String, String, String, Option<i64>, i64)> = stmt
+                .query_map(params![all], |r| {
+                    Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?, r.get(5)?, r.get(6)?, r.get(7)?))
+                })
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            if out.is_json() {
+                println!(
+                    "{}",
+                    json!(rows
+                        .iter()
+                        .map(|d| json!({
+                            "id": d.0,
+                            "what": d.1,
+                            "why": d.2,
+                            "status": d.3,
+                            "status_reason": d.4,
+                            "superseded_by": d.5,
+                            "revisit_after": d.6,
+                            "revisit_due": revisit_due(d.3.as_str(), d.6),
+                            "created_at": d.7
+                        }))
+                        .collect::<Vec<_>>())
+                );
+            } else if out.is_toon() {
+                let mut t = ToonBuilder::new();
+                t.section(
+                    "decisions",
+                    &["id", "what", "status", "revisit_after", "revisit_due"],
+                    rows.iter()
+                        .map(|d| {
+                            vec![
+                                d.0.clone(),
+                                d.1.clone(),
+                                d.3.clone(),
+                                d.6.map(format_date).unwrap_or_default(),
+                                revisit_due(&d.3, d.6).to_string(),
+                            ]
+                        })
+                        .collect(),
+                );
+                print!("{}", t.finish());
+            } else if rows.is_empty() {
+                println!("No decisions.");
+            } else {
+                for (id, what, why, status, reason, superseded_by, revisit_after, created_at) in &rows {
+                    let tag = match status.as_str() {
+                        "active" => String::new(),
+                        "superseded" => format!("  [superseded by {superseded_by}]"),
+                        other => format!("  [{other}: {reason}]"),
+                    };
+                    println!("  {what}{tag}  {}", paint(out, "2", id));
+                    println!("    why: {why}\n    {} ago{}", ago(*created_at), revisit_label(status, *revisit_after));
+                }
+            }
+            Ok(())
+        }
+        DecisionAction::Backfill { apply } => {
+            let decisions: Vec<(String, String, String)> = {
+                let mut stmt = conn
+                    .prepare("SELECT id, what, COALESCE(affects,'') FROM decisions WHERE TRIM(COALESCE(affects,'')) != '' ORDER BY created_at, id")
+                    .map_err(|e| e.to_string())?;
+                let rows = stmt
+                    .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+                    .map_err(|e| e.to_string())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| e.to_string())?;
+                rows
+            };
+            // (kind, id, label)
+            let targets: Vec<(&str, String, String)> = {
+                let mut stmt = conn
+                    .prepare("SELECT 'goal', id, name FROM goals UNION ALL SELECT 'task', id, title FROM tasks")
+                    .map_err(|e| e.to_string())?;
+                let rows = stmt
+                    .query_map([], |r| {
+                        let kind: String = r.get(0)?;
+                        Ok((if kind == "goal" { "goal" } else { "task" }, r.get(1)?, r.get(2)?))
+                    })
+                    .map_err(|e| e.to_string())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| e.to_string())?;
+                rows
+            };
+
+            // (decision_id, what, kind, target_id, target_label)
+            let mut proposals: Vec<(String, String, &str, String, String)> = Vec::new();
+            for (did, what, affects) in &decisions {
+                for (kind, tid, label) in &targets {
+                    if !mentions_phrase(affects, label) {
+                        continue;
+                    }
+                    let exists: bool = conn
+                        .query_row(
+                            "SELECT EXISTS(SELECT 1 FROM decision_links WHERE decision_id=?1 AND (goal_id=?2 OR task_id=?2))",
+                            params![did, tid],
+                            |r| r.get(0),
+                        )
+                        .map_err(|e| e.to_string())?;
+                    if !exists {
+                        proposals.push((did.clone(), what.clone(), kind, tid.clone(), label.clone()));
+                    }
+                }
+            }
+            if apply {
+                for (did, _, kind, tid, _) in &proposals {
+                    let (g, t) = if *kind == "goal" { (Some(tid.as_str()), None) } else { (None, Some(tid.as_str())) };
+                    link_decision(conn, did, g, t, "backfill")?;
+                }
+            }
+
+            if out.is_json() {
+                println!(
+                    "{}",
+                    json!({
+                        "applied": apply,
+                        "proposals": proposals.iter().map(|(did, what, kind, tid, label)| json!({
+                            "decision_id": did,
+                            "decision": what,
+                            "kind": kind,
+                            "target_id": tid,
+                            "target": label,
+                        })).collect::<Vec<_>>()
+                    })
+                );
+            } else if out.is_toon() {
+                let mut t = ToonBuilder::new();
+                t.section(
+                    "proposed_links",
+                    &["decision_id", "decision", "kind", "target_id", "target"],
+                    proposals
+                        .iter()
+                        .map(|(did, what, kind, tid, label)| vec![did.clone(), what.clone(), kind.to_string(), tid.clone(), label.clone()])
+                        .collect(),
+                );
+                t.section("backfill", &["applied", "count"], vec![vec![apply.to_string(), proposals.len().to_string()]]);
+                print!("{}", t.finish());
+            } else if proposals.is_empty() {
+                println!("No new links to propose — every `affects` mention is already linked.");
+            } else {
+                for (_, what, kind, _, label) in &proposals {
+                    println!("  {} → {kind} \"{label}\"", what);
+                }
+                if apply {
+                    println!("\nLinked {} decision/target pair(s).", proposals.len());
+                } else {
+                    println!("\n{} link(s) proposed. Re-run with --apply to save them.", proposals.len());
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn cmd_log(conn: &Connection, out: OutputCtx, note: String) -> Result<(), String> {
+    if note.trim().is_empty() {
+        return Err("note is required".to_string());
+    }
+    let now = now_ts();
+    let id = gen_id();
+    let author = current_agent(None);
+    conn.execute(
+        "INSERT INTO direction_notes (id, content, author, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![id, note, author, now],
+    )
+    .map_err(|e| e.to_string())?;
+    emit_simple_ok(out, "Direction note added")
+}
+
+const SEARCH_KINDS: &[&str] = &["goal", "task", "decision", "note", "memory", "lesson"];
+
+// Free text is reduced to quoted terms OR-ed together so punctuation in a
+// question can't trip FTS5 query syntax; bm25 then favours rows matching more terms.
+fn fts_match_expr(query: &str) -> String {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| format!("\"{}\"", w.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+fn cmd_search(
+    conn: &Connection,
+    out: OutputCtx,
+    query: String,
+    kinds: Vec<String>,
+    goal: Option<String>,
+    limit: i64,
+) -> Result<(), String> {
+    let expr = fts_match_expr(&query);
+    if expr.is_empty() {
+        return Err("search query is required".to_string());
+    }
+    let mut kinds: Vec<String> = kinds
+        .into_iter()
+        .map(|k| k.trim().to_lowercase())
+        .filter(|k| !k.is_empty())
+        .map(|k| match k.trim_end_matches('s') {
+            "direction" | "direction_note" => "note".to_string(),
+            "memorie" => "memory".to_string(),
+            other => other.to_string(),
+        })
+        .collect();
+    kinds.sort();
+    kinds.dedup();
+    if let Some(bad) = kinds.iter().find(|k| !SEARCH_KINDS.contains(&k.as_str())) {
+        return Err(format!("unknown --type '{}' (expected one of: {})", bad, SEARCH_KINDS.join(", ")));
+    }
+    let goal_id = match goal {
+        Some(g) => Some(resolve_id_prefix(conn, "goals", &g)?.ok_or_else(|| format!("Goal not found: {g}"))?),
+        None => None,
+    };
+
+    let (hl_start, hl_end) = if out.color && !out.is_json() && !out.is_toon() {
+        ("\x1b[1;33m", "\x1b[0m")
+    } else {
+        ("[", "]")
+    };
+    let mut sql = String::from(
+        "SELECT kind, ref_id, goal_id, title, snippet(search_index, -1, ?2, ?3, '…', 16), bm25(search_index, 4.0, 1.0)
+         FROM search_index WHERE search_index MATCH ?1",
+    );
+    let mut args: Vec<rusqlite::types::Value> = vec![expr.into(), hl_start.to_string().into(), hl_end.to_string().into()];
+    if !kinds.is_empty() {
+        let start = args.len() + 1;
+        let holes: Vec<String> = (start..start + kinds.len()).map(|i| format!("?{i}")).collect();
+        sql.push_str(&format!(" AND kind IN ({})", holes.join(",")));
+        args.extend(kinds.iter().map(|k| k.clone().into()));
+    }
+    if let Some(g) = &goal_id {
+        // Only goals, tasks and memories carry a goal_id in the index; decisions
+        // reach a goal through decision_links (directly or via one of its tasks),
+        // memories may hang off a task only, and notes and lessons are project-wide.
+        let n = args.len() + 1;
+        sql.push_str(&format!(
+            " AND (goal_id = ?{n}
+                OR kind IN ('note', 'lesson')
+                OR (kind = 'decision' AND ref_id IN (
+                    SELECT l.decision_id FROM decision_links l LEFT JOIN tasks t ON t.id = l.task_id
+                    WHERE COALESCE(l.goal_id, t.goal_id) = ?{n}))
+                OR (kind = 'memory' AND ref_id IN (
+                    SELECT m.id FROM memories m JOIN tasks t ON t.id = m.task_id WHERE t.goal_id = ?{n})))"
+        ));
+        args.push(g.clone().into());
+    }
+    sql.push_str(&format!(" ORDER BY 6 LIMIT ?{}", args.len() + 1));
+    args.push(limit.max(1).into());
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let hits = stmt
+        .query_map(rusqlite::params_from_iter(args.iter()), |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, Option<String>>(2)?,
+                r.get::<_, String>(3)?,
+                r.get::<_, String>(4)?,
+                r.get::<_, f64>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    if out.is_json() {
+        println!(
+            "{}",
+            json!({
+                "query": query,
+                "results": hits.iter().map(|(kind, id, goal_id, title, snippet, score)| json!({
+                    "kind": kind,
+                    "id": id,
+                    "goal_id": goal_id,
+                    "title": title,
+                    "snippet": snippet,
+                    "score": -score,
+                })).collect::<Vec<_>>()
+            })
+        );
+    } else if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section(
+            "results",
+            &["kind", "id", "goal_id", "title", "snippet"],
+            hits.iter()
+                .map(|(kind, id, goal_id, title, snippet, _)| {
+                    vec![kind.clone(), id.clone(), goal_id.clone().unwrap_or_default(), title.clone(), snippet.clone()]
+                })
+                .collect(),
+        );
+        print!("{}", t.finish());
+    } else if hits.is_empty() {
+        println!("No matches for \"{query}\".");
+    } else {
+        println!("{} match(es) for \"{query}\"\n", hits.len());
+        for (kind, id, _, title, snippet, _) in &hits {
+            let heading = if title.is_empty() { snippet.clone() } else { title.clone() };
+            println!("  {} {}  {}", paint(out, "36", &format!("[{kind}]")), heading, paint(out, "2", id));
+            if !title.is_empty() {
+                println!("      {}", snippet.replace('\n', " "));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn ops_read_line(prompt: &str) -> Result<String, String> {
+    print!("{}", prompt);
+    io::stdout().flush().map_err(|e| e.to_string())?;
+    let mut line = String::new();
+    io::stdin().read_line(&mut line).map_err(|e| e.to_string())?;
+    Ok(line.trim().to_string())
+}
+
+fn cmd_ops(conn: &Connection, out: OutputCtx, cfg: &Config, args: Vec<String>) -> Result<(), String> {
+    if args.is_empty() {
+        return cmd_context(conn, out, cfg, None, None);
+    }
+    conn.execute(
+        "INSERT INTO direction_notes (id, content, author, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![gen_id(), args.join(" "), current_agent(None), now_ts()],
+    )
+    .map_err(|e| e.to_string())?;
+    println!("Direction noted.");
+    Ok(())
+}
+
+fn cmd_delete(conn: &Connection, out: OutputCtx, id: String) -> Result<(), String> {
+    let goal_match = resolve_id_prefix(conn, "goals", &id)?;
+    let task_match = resolve_id_prefix(conn, "tasks", &id)?;
+    if let (Some(goal_id), Some(task_id)) = (&goal_match, &task_match) {
+        return Err(format!(
+            "ambiguous ID prefix '{id}' matches goal {goal_id} and task {task_id}. Use a longer prefix."
+        ));
+    }
+
+    if let Some(goal_id) = goal_match {
+        for table in ["memories", "memory_archive"] {
+            conn.execute(
+                &format!("DELETE FROM {table} WHERE goal_id=?1 OR task_id IN (SELECT id FROM tasks WHERE goal_id=?1)"),
+                params![goal_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        conn.execute("DELETE FROM tasks WHERE goal_id=?1", params![goal_id])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM goals WHERE id=?1", params![goal_id])
+            .map_err(|e| e.to_string())?;
+        emit_simple_ok(out, "Goal deleted")?;
+        return Ok(());
+    }
+
+    if let Some(task_id) = task_match {
+        let goal_id: Option<String> = conn
+            .query_row("SELECT goal_id FROM tasks WHERE id=?1", params![task_id.clone()], |r| {
+                r.get(0)
+            })
+            .optional()
+            .map_err(|e| e.to_string())?
+            .flatten();
+        conn.execute("DELETE FROM memories WHERE task_id=?1", params![task_id.clone()])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM memory_archive WHERE task_id=?1", params![task_id.clone()])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM tasks WHERE id=?1", params![task_id])
+            .map_err(|e| e.to_string())?;
+        if let Some(gid) = goal_id {
+            let _ = sync_goal(conn, &gid);
+        }
+        emit_simple_ok(out, "Task deleted")?;
+        return Ok(());
+    }
+
+    Err("id not found in goals or tasks".to_string())
+}
+
+fn cmd_reset(conn: &Connection, db_path: &Path, out: OutputCtx, force: bool) -> Result<(), String> {
+    if !force {
+        if !io::stdin().is_terminal() {
+            return Err("reset requires --force in non-interactive mode".to_string());
+        }
+        print!("This will delete goals/tasks/memories. Type 'yes' to continue: ");
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        io::stdin()
+            .read_line(&mut line)
+            .map_err(|e| e.to_string())?;
+        if line.trim() != "yes" {
+            emit_simple_ok(out, "Reset cancelled")?;
+            return Ok(());
+        }
+    }
+
+    let backup = backup_db(conn, db_path)?;
+    conn.execute("DELETE FROM memories", []).map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM memory_archive", []).map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM tasks", []).map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM goals", []).map_err(|e| e.to_string())?;
+
+    emit_simple_ok(out, &format!("Reset complete (backup: {}; `imi undo` restores it)", backup.display()))
+}
+
+/// Copy the live database to `<db dir>/backups/state-<ts>.db` with VACUUM INTO,
+/// which gives a consistent snapshot even while other agents hold connections.
+fn backup_db(conn: &Connection, db_path: &Path) -> Result<PathBuf, String> {
+    let dir = db_path.parent().unwrap_or_else(|| Path::new(".")).join("backups");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let ts = now_ts();
+    let mut path = dir.join(format!("state-{ts}.db"));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("state-{ts}-{n}.db"));
+        n += 1;
+    }
+    conn.execute("VACUUM INTO ?1", params![path.to_string_lossy()])
+        .map_err(|e| format!("backup failed: {e}"))?;
+    Ok(path)
+}
+
+/// Every command that can change state is journaled, except heartbeats (too
+/// frequent to be worth undoing), the commands that manage the journal or
+/// schema themselves, and worker sessions: one entry spanning a whole run
+/// would let a single `imi undo` wipe every task outcome the workers produced.
+fn is_journaled(command: &Commands) -> bool {
+    !matches!(
+        command,
+        Commands::Init
+            | Commands::Ping { .. }
+            | Commands::Run { .. }
+            | Commands::Wrap { .. }
+            | Commands::Orchestrate { .. }
+            | Commands::Logs { .. }
+            | Commands::Undo { .. }
+            | Commands::Journal { .. }
+            | Commands::Db { .. }
+    )
+}
+
+/// Tables whose row changes `imi undo` can revert. Analytics events are
+/// append-only and stay out.
+fn journal_tables() -> impl Iterator<Item = &'static str> {
+    EXPORT_TABLES.iter().copied().filter(|t| *t != "events")
+}
+
+/// Install temp triggers that copy the before-image of every row the current
+/// command touches. They live only on this connection, so concurrent agents
+/// journal their own commands independently.
+fn begin_journal(conn: &Connection) -> Result<(), String> {
+    let mut sql = String::from(
+        "CREATE TEMP TABLE IF NOT EXISTS journal_capture (
+  seq INTEGER PRIMARY KEY AUTOINCREMENT, table_name TEXT, row_id INTEGER, op TEXT, before TEXT
+);\n",
+    );
+    for table in journal_tables() {
+        let image = table_columns(conn, table)?
+            .iter()
+            .map(|(c, _)| format!("'{c}', OLD.\"{c}\""))
+            .collect::<Vec<_>>()
+            .join(", ");
+        sql.push_str(&format!(
+            "CREATE TEMP TRIGGER IF NOT EXISTS journal_{table}_ai AFTER INSERT ON main.{table} BEGIN
+  INSERT INTO journal_capture (table_name, row_id, op) VALUES ('{table}', NEW.rowid, 'insert');
+END;
+CREATE TEMP TRIGGER IF NOT EXISTS journal_{table}_bu BEFORE UPDATE ON main.{table} BEGIN
+  INSERT INTO journal_capture (table_name, row_id, op, before) VALUES ('{table}', OLD.rowid, 'update', json_object({image}));
+END;
+CREATE TEMP TRIGGER IF NOT EXISTS journal_{table}_bd BEFORE DELETE ON main.{table} BEGIN
+  INSERT INTO journal_capture (table_name, row_id, op, before) VALUES ('{table}', OLD.rowid, 'delete', json_object({image}));
+END;\n"
+        ));
+    }
+    conn.execute_batch(&sql).map_err(|e| e.to_string())
+}
+
+/// Move this command's captured rows into the journal and stop capturing.
+fn finish_journal(conn: &mut Connection, cfg: &Config, command: &str, args: &str) -> Result<(), String> {
+    let mut drop = String::new();
+    for table in journal_tables() {
+        for suffix in ["ai", "bu", "bd"] {
+            drop.push_str(&format!("DROP TRIGGER IF EXISTS temp.journal_{table}_{suffix};\n"));
+        }
+    }
+    conn.execute_batch(&drop).map_err(|e| e.to_string())?;
+
+    let counts = {
+        let mut stmt = conn
+            .prepare("SELECT table_name, op, COUNT(*) FROM journal_capture GROUP BY table_name, op ORDER BY MIN(seq)")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?, r.get::<_, i64>(2)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+    if counts.is_empty() {
+        return Ok(());
+    }
+    // After-images let `imi undo` notice rows that changed once this command was done.
+    let touched = {
+        let mut stmt = conn
+            .prepare("SELECT DISTINCT table_name, row_id FROM journal_capture")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+    let mut afters = Vec::with_capacity(touched.len());
+    for (table, row_id) in touched {
+        let after = current_row_image(conn, &table, row_id)?;
+        afters.push((table, row_id, after.to_string()));
+    }
+    let summary = counts
+        .iter()
+        .map(|(table, op, n)| {
+            let sign = match op.as_str() {
+                "insert" => "+",
+                "delete" => "-",
+                _ => "~",
+            };
+            format!("{table} {sign}{n}")
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let id = gen_id();
+    let keep = cfg.u64("journal.keep").max(1) as i64;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO journal (id, command, args, summary, agent_id, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![id, command, args, summary, current_agent(None), now_ts()],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO journal_rows (journal_id, seq, table_name, row_id, op, before)
+         SELECT ?1, seq, table_name, row_id, op, before FROM journal_capture",
+        params![id],
+    )
+    .map_err(|e| e.to_string())?;
+    for (table, row_id, after) in &afters {
+        tx.execute(
+            "UPDATE journal_rows SET after=?1 WHERE journal_id=?2 AND table_name=?3 AND row_id=?4",
+            params![after, id, table, row_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.execute("DELETE FROM journal_capture", []).map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM journal WHERE id IN (
+           SELECT id FROM journal ORDER BY COALESCE(created_at,0) DESC, rowid DESC LIMIT -1 OFFSET ?1)",
+        params![keep],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+struct JournalEntry {
+    id: String,
+    command: String,
+    args: String,
+    summary: String,
+    agent_id: String,
+    created_at: i64,
+    undone_at: Option<i64>,
+}
+
+fn query_journal(
+    conn: &Connection,
+    limit: usize,
+    include_undone: bool,
+    agent: Option<&str>,
+) -> Result<Vec<JournalEntry>, String> {
+    let filter = if include_undone { "" } else { "AND undone_at IS NULL" };
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, command, COALESCE(args,''), COALESCE(summary,''), COALESCE(agent_id,''), COALESCE(created_at,0), undone_at
+             FROM journal WHERE (?2 IS NULL OR agent_id = ?2) {filter}
+             ORDER BY COALESCE(created_at,0) DESC, rowid DESC LIMIT ?1"
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![limit as i64, agent], |r| {
+            Ok(JournalEntry {
+                id: r.get(0)?,
+                command: r.get(1)?,
+                args: r.get(2)?,
+                summary: r.get(3)?,
+                agent_id: r.get(4)?,
+                created_at: r.get(5)?,
+                undone_at: r.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn journal_to_value(e: &JournalEntry) -> Value {
+    json!({
+        "id": e.id,
+        "command": e.command,
+        "args": e.args,
+        "summary": e.summary,
+        "agent": e.agent_id,
+        "created_at": e.created_at,
+        "undone_at": e.undone_at,
+    })
+}
+
+fn print_journal(entries: &[JournalEntry], out: OutputCtx, section: &str) {
+    if out.is_json() {
+        let items: Vec<Value> = entries.iter().map(journal_to_value).collect();
+        println!("{}", json!({ section: items }));
+    } else if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section(
+            section,
+            &["id", "command", "args", "summary", "agent", "created_at", "undone_at"],
+            entries
+                .iter()
+                .map(|e| {
+                    vec![
+                        e.id.clone(),
+                        e.command.clone(),
+                        e.args.clone(),
+                        e.summary.clone(),
+                        e.agent_id.clone(),
+                        e.created_at.to_string(),
+                        e.undone_at.map(|v| v.to_string()).unwrap_or_default(),
+                    ]
+                })
+                .collect(),
+        );
+        print!("{}", t.finish());
+    } else {
+        for e in entries {
+            let undone = if e.undone_at.is_some() { "  (undone)" } else { "" };
+            println!("  {}  {}  imi {}{}", e.id, ago(e.created_at), e.args, undone);
+            println!("      {}", e.summary);
+        }
+    }
+}
+
+fn cmd_journal(conn: &Connection, out: OutputCtx, limit: usize, all: bool) -> Result<(), String> {
+    let entries = query_journal(conn, limit, all, None)?;
+    if !out.is_json() && !out.is_toon() {
+        if entries.is_empty() {
+            println!("Journal is empty — nothing to undo.");
+            return Ok(());
+        }
+        println!("Journal (newest first; `imi undo` reverts from the top):");
+    }
+    print_journal(&entries, out, "journal");
+    Ok(())
+}
+
+/// The row as `SELECT *` sees it now, or null once it is gone.
+fn current_row_image(conn: &Connection, table: &str, row_id: i64) -> Result<Value, String> {
+    Ok(dump_row(conn, table, "rowid=?1", &[rusqlite::types::Value::Integer(row_id)])?
+        .map(Value::Object)
+        .unwrap_or(Value::Null))
+}
+
+/// First row `entry` touched that no longer looks the way the command left it,
+/// e.g. because a heartbeat or another agent wrote to it since.
+fn journal_conflict(conn: &Connection, entry: &JournalEntry) -> Result<Option<String>, String> {
+    let afters = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT DISTINCT table_name, row_id, after FROM journal_rows
+                 WHERE journal_id=?1 AND after IS NOT NULL",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![entry.id], |r| Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?, r.get::<_, String>(2)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+    for (table, row_id, after) in afters {
+        let after: Value = serde_json::from_str(&after).map_err(|e| format!("corrupt journal row in {}: {e}", entry.id))?;
+        let now = current_row_image(conn, &table, row_id)?;
+        let unchanged = match (&after, &now) {
+            (Value::Null, Value::Null) => true,
+            (Value::Object(then), Value::Object(now)) => then.iter().all(|(k, v)| now.get(k) == Some(v)),
+            _ => false,
+        };
+        if !unchanged {
+            let id = after.get("id").or_else(|| now.get("id")).and_then(Value::as_str).map(String::from);
+            return Ok(Some(format!("{table} {}", id.unwrap_or_else(|| format!("row {row_id}")))));
+        }
+    }
+    Ok(None)
+}
+
+/// Revert the agent's newest `n` journal entries by replaying their
+/// before-images in reverse. Foreign keys are deferred so cascaded deletes can
+/// be restored child-first.
+fn cmd_undo(conn: &mut Connection, out: OutputCtx, n: usize, agent: Option<String>, force: bool) -> Result<(), String> {
+    if n == 0 {
+        return Err("nothing to undo: n must be at least 1".to_string());
+    }
+    let agent_id = current_agent(agent.as_deref());
+    let entries = query_journal(conn, n, false, Some(&agent_id))?;
+    if entries.is_empty() {
+        return Err(format!("nothing to undo: the journal has no commands from {agent_id}"));
+    }
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.pragma_update(None, "defer_foreign_keys", "ON").map_err(|e| e.to_string())?;
+    let now = now_ts();
+    for entry in &entries {
+        // Newer entries in this batch are already reverted, so each one is
+        // compared against the state it left behind.
+        if !force {
+            if let Some(row) = journal_conflict(&tx, entry)? {
+                return Err(format!(
+                    "cannot undo `imi {}`: {row} has changed since it ran (see `imi journal`; --force restores it anyway)",
+                    entry.args
+                ));
+            }
+        }
+        let rows = {
+            let mut stmt = tx
+                .prepare("SELECT table_name, row_id, op, before FROM journal_rows WHERE journal_id=?1 ORDER BY seq DESC")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![entry.id], |r| {
+                    Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?, r.get::<_, String>(2)?, r.get::<_, Option<String>>(3)?))
+                })
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            rows
+        };
+        for (table, row_id, op, before) in rows {
+            if !journal_tables().any(|t| t == table) {
+                return Err(format!("journal entry {} touches unknown table {table}", entry.id));
+            }
+            if op == "insert" {
+                tx.execute(&format!("DELETE FROM {table} WHERE rowid=?1"), params![row_id])
+                    .map_err(|e| e.to_string())?;
+                continue;
+            }
+            let image: serde_json::Map<String, Value> = serde_json::from_str(before.as_deref().unwrap_or("{}"))
+                .map_err(|e| format!("corrupt journal row in {}: {e}", entry.id))?;
+            let cols: Vec<&String> = image.keys().collect();
+            let mut values: Vec<rusqlite::types::Value> = cols.iter().map(|c| json_to_sql(&image[*c])).collect();
+            values.push(rusqlite::types::Value::Integer(row_id));
+            let sql = if op == "update" {
+                format!(
+                    "UPDATE {table} SET {} WHERE rowid=?{}",
+                    cols.iter().enumerate().map(|(i, c)| format!("\"{c}\"=?{}", i + 1)).collect::<Vec<_>>().join(", "),
+                    cols.len() + 1
+                )
+            } else {
+                format!(
+                    "INSERT INTO {table} ({}, rowid) VALUES ({})",
+                    cols.iter().map(|c| format!("\"{c}\"")).collect::<Vec<_>>().join(", "),
+                    (1..=cols.len() + 1).map(|i| format!("?{i}")).collect::<Vec<_>>().join(", ")
+                )
+            };
+            tx.execute(&sql, rusqlite::params_from_iter(values))
+                .map_err(|e| format!("cannot undo `imi {}`: {e}", entry.args))?;
+        }
+        tx.execute("UPDATE journal SET undone_at=?1 WHERE id=?2", params![now, entry.id])
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| format!("cannot undo: {e}"))?;
+
+    if !out.is_json() && !out.is_toon() {
+        println!("↩️  Undid {} command(s):", entries.len());
+    }
+    print_journal(&entries, out, "undone");
+    Ok(())
+}
+
+/// Every table `imi export` covers, parents before children so foreign keys
+/// resolve on import. New tables must be added here to survive a round trip.
+const EXPORT_TABLES: &[&str] = &[
+    "workspaces",
+    "goals",
+    "tasks",
+    "task_dependencies",
+    "acceptance_checks",
+    "memories",
+    "memory_archive",
+    "lessons",
+    "decisions",
+    "decision_links",
+    "direction_notes",
+    "revisions",
+    "events",
+    "settings",
+];
+
+/// Per-machine settings that must not travel with an export.
+const LOCAL_SETTINGS: &[&str] = &["install_id", "last_update_check"];
+
+/// Column names of `table` with their primary-key position (0 = not part of the key).
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<(String, i64)>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT name, pk FROM pragma_table_info('{table}') ORDER BY cid"))
+        .map_err(|e| e.to_string())?;
+    let mapped = stmt
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+        .map_err(|e| e.to_string())?;
+    mapped
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+fn primary_key_columns(columns: &[(String, i64)]) -> Vec<String> {
+    let mut pk: Vec<&(String, i64)> = columns.iter().filter(|c| c.1 > 0).collect();
+    pk.sort_by_key(|c| c.1);
+    pk.into_iter().map(|c| c.0.clone()).collect()
+}
+
+fn sql_to_json(v: rusqlite::types::ValueRef<'_>) -> Value {
+    use rusqlite::types::ValueRef;
+    match v {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => json!(i),
+        ValueRef::Real(f) => json!(f),
+        ValueRef::Text(t) => json!(String::from_utf8_lossy(t)),
+        ValueRef::Blob(b) => json!({ "$blob": b.iter().map(|x| format!("{x:02x}")).collect::<String>() }),
+    }
+}
+
+/// Inverse of the `{"$blob": "<hex>"}` encoding `sql_to_json` uses for BLOB cells.
+fn decode_blob(v: &Value) -> Option<Vec<u8>> {
+    let obj = v.as_object().filter(|o| o.len() == 1)?;
+    let hex = obj.get("$blob")?.as_str()?;
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn json_to_sql(v: &Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value as SqlValue;
+    match v {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        other => match decode_blob(other) {
+            Some(bytes) => SqlValue::Blob(bytes),
+            None => SqlValue::Text(other.to_string()),
+        },
+    }
+}
+
+fn dump_table(conn: &Connection, table: &str, since: Option<i64>) -> Result<Vec<serde_json::Map<String, Value>>, String> {
+    let columns = table_columns(conn, table)?;
+    if columns.is_empty() {
+        return Ok(Vec::new());
+    }
+    let has = |name: &str| columns.iter().any(|c| c.0 == name);
+    let ts_expr = if has("updated_at") && has("created_at") {
+        Some("COALESCE(updated_at, created_at, 0)")
+    } else if has("created_at") {
+        Some("COALESCE(created_at, 0)")
+    } else {
+        None
+    };
+    let pk = primary_key_columns(&columns);
+    let order = if pk.is_empty() { "rowid".to_string() } else { pk.join(", ") };
+    let mut sql = format!("SELECT * FROM {table}");
+    let filter = since.zip(ts_expr);
+    if let Some((_, expr)) = filter {
+        sql.push_str(&format!(" WHERE {expr} >= ?1"));
+    }
+    sql.push_str(&format!(" ORDER BY {order}"));
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let names: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let mut rows = match filter {
+        Some((ts, _)) => stmt.query(params![ts]),
+        None => stmt.query([]),
+    }
+    .map_err(|e| e.to_string())?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let mut obj = serde_json::Map::new();
+        for (i, name) in names.iter().enumerate() {
+            obj.insert(name.clone(), sql_to_json(row.get_ref(i).map_err(|e| e.to_string())?));
+        }
+        out.push(obj);
+    }
+    Ok(out)
+}
+
+fn cmd_export(
+    conn: &Connection,
+    out: OutputCtx,
+    format: String,
+    since: Option<i64>,
+    output: Option<String>,
+) -> Result<(), String> {
+    let format = format.to_lowercase();
+    if format != "json" && format != "ndjson" {
+        return Err("format must be one of: json, ndjson".to_string());
+    }
+    let header = json!({
+        "imi_export": 1,
+        "imi_version": VERSION,
+        "schema_version": schema_version(conn)?,
+        "exported_at": now_ts(),
+        "since": since
+    });
+
+    let mut dumped = Vec::with_capacity(EXPORT_TABLES.len());
+    for table in EXPORT_TABLES {
+        let mut rows = dump_table(conn, table, since)?;
+        if *table == "settings" {
+            rows.retain(|r| !r.get("key").and_then(Value::as_str).is_some_and(|k| LOCAL_SETTINGS.contains(&k)));
+        }
+        dumped.push((*table, rows));
+    }
+    if since.is_some() {
+        include_referenced_parents(conn, &mut dumped)?;
+    }
+    let total_rows: usize = dumped.iter().map(|(_, rows)| rows.len()).sum();
+
+    let body = if format == "json" {
+        let mut doc = header;
+        let mut tables = serde_json::Map::new();
+        for (table, rows) in dumped {
+            tables.insert(table.to_string(), Value::Array(rows.into_iter().map(Value::Object).collect()));
+        }
+        doc["tables"] = Value::Object(tables);
+        let mut s = serde_json::to_string_pretty(&doc).map_err(|e| e.to_string())?;
+        s.push('\n');
+        s
+    } else {
+        let mut s = String::new();
+        let mut head = header;
+        head["type"] = json!("header");
+        s.push_str(&head.to_string());
+        s.push('\n');
+        for (table, rows) in dumped {
+            for row in rows {
+                s.push_str(&json!({"type": "row", "table": table, "row": row}).to_string());
+                s.push('\n');
+            }
+        }
+        s
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(&path, body).map_err(|e| format!("failed to write {path}: {e}"))?;
+            emit_simple_ok(out, &format!("Exported {total_rows} row(s) to {path}"))
+        }
+        None => {
+            print!("{body}");
+            Ok(())
+        }
+    }
+}
+
+/// `--since` can select a child row (a task, a decision link, ...) whose
+/// parent is older than the window. Pull those parents in as well so the
+/// export still imports into an empty DB.
+fn include_referenced_parents(
+    conn: &Connection,
+    dumped: &mut [(&str, Vec<serde_json::Map<String, Value>>)],
+) -> Result<(), String> {
+    // EXPORT_TABLES lists parents first, so walking it backwards also picks up
+    // grandparents of rows added by an earlier step.
+    for i in (0..dumped.len()).rev() {
+        let mut stmt = conn
+            .prepare(&format!("SELECT \"table\", \"from\", \"to\" FROM pragma_foreign_key_list('{}')", dumped[i].0))
+            .map_err(|e| e.to_string())?;
+        let mapped = stmt
+            .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?, r.get::<_, Option<String>>(2)?)))
+            .map_err(|e| e.to_string())?;
+        let foreign_keys = mapped.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+        for (parent, from, to) in foreign_keys {
+            let Some(p) = dumped.iter().position(|(t, _)| *t == parent) else {
+                continue;
+            };
+            let to = match to {
+                Some(to) => to,
+                None => match primary_key_columns(&table_columns(conn, &parent)?).as_slice() {
+                    [pk] => pk.clone(),
+                    _ => continue,
+                },
+            };
+            let wanted: Vec<Value> = dumped[i]
+                .1
+                .iter()
+                .filter_map(|r| r.get(&from))
+                .filter(|v| !v.is_null())
+                .cloned()
+                .collect();
+            for key in wanted {
+                if dumped[p].1.iter().any(|r| r.get(&to) == Some(&key)) {
+                    continue;
+                }
+                if let Some(row) = dump_row(conn, &parent, &format!("{to}=?1"), &[json_to_sql(&key)])? {
+                    dumped[p].1.push(row);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse either export format into (schema_version, rows grouped by table).
+#[allow(clippy::type_complexity)]
+fn parse_export(text: &str) -> Result<(i64, Vec<(String, Vec<serde_json::Map<String, Value>>)>), String> {
+    let mut grouped: Vec<(String, Vec<serde_json::Map<String, Value>>)> = Vec::new();
+    let mut push = |table: &str, row: serde_json::Map<String, Value>| {
+        if let Some(entry) = grouped.iter_mut().find(|(t, _)| t == table) {
+            entry.1.push(row);
+        } else {
+            grouped.push((table.to_string(), vec![row]));
+        }
+    };
+
+    // NDJSON starts with a `{"type":"header"}` record. A header-only export is
+    // also a valid single JSON document, so the first line decides.
+    let first_line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or_default();
+    let is_ndjson = serde_json::from_str::<Value>(first_line).is_ok_and(|v| v["type"] == "header");
+    let schema = if !is_ndjson {
+        let doc: Value = serde_json::from_str(text).map_err(|e| format!("not an imi export: {e}"))?;
+        let tables = doc["tables"]
+            .as_object()
+            .ok_or_else(|| "not an imi export: missing \"tables\"".to_string())?;
+        for (table, rows) in tables {
+            for row in rows.as_array().into_iter().flatten() {
+                if let Some(obj) = row.as_object() {
+                    push(table, obj.clone());
+                }
+            }
+        }
+        doc["schema_version"].as_i64().unwrap_or(0)
+    } else {
+        let mut schema = None;
+        for (n, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let v: Value = serde_json::from_str(line).map_err(|e| format!("line {}: {e}", n + 1))?;
+            match v["type"].as_str() {
+                Some("header") => schema = v["schema_version"].as_i64(),
+                Some("row") => {
+                    let table = v["table"].as_str().ok_or_else(|| format!("line {}: row without table", n + 1))?;
+                    let row = v["row"].as_object().ok_or_else(|| format!("line {}: row is not an object", n + 1))?;
+                    push(table, row.clone());
+                }
+                _ => return Err(format!("line {}: unknown record type", n + 1)),
+            }
+        }
+        schema.ok_or_else(|| "not an imi export: missing header line".to_string())?
+    };
+    Ok((schema, grouped))
+}
+
+struct ImportConflict {
+    table: String,
+    key: String,
+    reason: String,
+}
+
+fn cmd_import(conn: &mut Connection, out: OutputCtx, file: String, replace: bool) -> Result<(), String> {
+    let text = if file == "-" {
+        let mut buf = String::new();
+        io::stdin().read_to_string(&mut buf).map_err(|e| e.to_string())?;
+        buf
+    } else {
+        fs::read_to_string(&file).map_err(|e| format!("failed to read {file}: {e}"))?
+    };
+    let (export_schema, grouped) = parse_export(&text)?;
+    if export_schema > latest_schema_version() {
+        return Err(format!(
+            "export was written with schema v{export_schema}, newer than this imi supports (v{}) — run `imi update`",
+            latest_schema_version()
+        ));
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    if replace {
+        for table in EXPORT_TABLES.iter().rev() {
+            // install_id and friends belong to this machine, not to the state
+            // being restored, so they survive a replace.
+            let sql = if *table == "settings" {
+                format!(
+                    "DELETE FROM settings WHERE key NOT IN ({})",
+                    LOCAL_SETTINGS.iter().map(|k| format!("'{k}'")).collect::<Vec<_>>().join(", ")
+                )
+            } else {
+                format!("DELETE FROM {table}")
+            };
+            tx.execute(&sql, []).map_err(|e| e.to_string())?;
+        }
+    }
+
+    // (table, inserted, duplicates)
+    let mut counts: Vec<(String, usize, usize)> = Vec::new();
+    let mut conflicts: Vec<ImportConflict> = Vec::new();
+    let ordered = EXPORT_TABLES
+        .iter()
+        .filter_map(|t| grouped.iter().find(|(name, _)| name == t));
+    for (table, rows) in ordered {
+        let columns = table_columns(&tx, table)?;
+        let pk = primary_key_columns(&columns);
+        let (mut inserted, mut duplicates) = (0usize, 0usize);
+        for row in rows {
+            let cols: Vec<&String> = row
+                .keys()
+                .filter(|k| columns.iter().any(|c| &c.0 == *k))
+                .collect();
+            let key = pk
+                .iter()
+                .map(|k| row.get(k).map(|v| v.as_str().map(String::from).unwrap_or_else(|| v.to_string())).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join("/");
+            if pk.is_empty() || pk.iter().any(|k| row.get(k).map(Value::is_null).unwrap_or(true)) {
+                conflicts.push(ImportConflict { table: table.clone(), key, reason: "missing primary key".to_string() });
+                continue;
+            }
+
+            let where_pk = pk
+                .iter()
+                .enumerate()
+                .map(|(i, k)| format!("{k}=?{}", i + 1))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            let pk_values: Vec<rusqlite::types::Value> = pk.iter().map(|k| json_to_sql(&row[k])).collect();
+            let existing = dump_row(&tx, table, &where_pk, &pk_values)?;
+            if let Some(existing) = existing {
+                let differs = cols
+                    .iter()
+                    .any(|c| existing.get(*c).unwrap_or(&Value::Null) != &row[*c]);
+                if differs {
+                    conflicts.push(ImportConflict {
+                        table: table.clone(),
+                        key,
+                        reason: "exists locally with different content (kept local)".to_string(),
+                    });
+                } else {
+                    duplicates += 1;
+                }
+                continue;
+            }
+
+            let sql = format!(
+                "INSERT INTO {table} ({}) VALUES ({})",
+                cols.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", "),
+                (1..=cols.len()).map(|i| format!("?{i}")).collect::<Vec<_>>().join(", ")
+            );
+            let values: Vec<rusqlite::types::Value> = cols.iter().map(|c| json_to_sql(&row[*c])).collect();
+            match tx.execute(&sql, rusqlite::params_from_iter(values)) {
+                Ok(_) => inserted += 1,
+                Err(e) => conflicts.push(ImportConflict { table: table.clone(), key, reason: e.to_string() }),
+            }
+        }
+        counts.push((table.clone(), inserted, duplicates));
+    }
+    let unknown: Vec<&String> = grouped
+        .iter()
+        .map(|(t, _)| t)
+        .filter(|t| !EXPORT_TABLES.contains(&t.as_str()))
+        .collect();
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let mode = if replace { "replace" } else { "merge" };
+    let total_inserted: usize = counts.iter().map(|c| c.1).sum();
+    let total_duplicates: usize = counts.iter().map(|c| c.2).sum();
+
+    if out.is_json() {
+        let tables: serde_json::Map<String, Value> = counts
+            .iter()
+            .map(|(t, i, d)| (t.clone(), json!({"inserted": i, "duplicates": d})))
+            .collect();
+        println!(
+            "{}",
+            json!({
+                "ok": true,
+                "mode": mode,
+                "inserted": total_inserted,
+                "duplicates": total_duplicates,
+                "tables": tables,
+                "conflicts": conflicts
+                    .iter()
+                    .map(|c| json!({"table": c.table, "key": c.key, "reason": c.reason}))
+                    .collect::<Vec<_>>(),
+                "unknown_tables": unknown
+            })
+        );
+        return Ok(());
+    }
+
+    if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section(
+            "import",
+            &["mode", "inserted", "duplicates", "conflicts"],
+            vec![vec![
+                mode.to_string(),
+                total_inserted.to_string(),
+                total_duplicates.to_string(),
+                conflicts.len().to_string(),
+            ]],
+        );
+        t.section(
+            "tables",
+            &["table", "inserted", "duplicates"],
+            counts
+                .iter()
+                .map(|(t, i, d)| vec![t.clone(), i.to_string(), d.to_string()])
+                .collect(),
+        );
+        t.section(
+            "conflicts",
+            &["table", "key", "reason"],
+            conflicts
+                .iter()
+                .map(|c| vec![c.table.clone(), c.key.clone(), c.reason.clone()])
+                .collect(),
+        );
+        print!("{}", t.finish());
+        return Ok(());
+    }
+
+    println!(
+        "Imported {total_inserted} row(s) ({mode}): {total_duplicates} duplicate(s) skipped, {} conflict(s)",
+        conflicts.len()
+    );
+    for (table, inserted, duplicates) in counts.iter().filter(|c| c.1 > 0 || c.2 > 0) {
+        println!("  {table:<16} +{inserted}  ={duplicates}");
+    }
+    if !conflicts.is_empty() {
+        println!("\nConflicts:");
+        for c in &conflicts {
+            println!("  ⚠ {} {} — {}", c.table, c.key, c.reason);
+        }
+    }
+    for t in unknown {
+        println!("  ⚠ skipped unknown table: {t}");
+    }
+    Ok(())
+}
+
+fn dump_row(
+    conn: &Connection,
+    table: &str,
+    where_clause: &str,
+    values: &[rusqlite::types::Value],
+) -> Result<Option<serde_json::Map<String, Value>>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {table} WHERE {where_clause} LIMIT 1"))
+        .map_err(|e| e.to_string())?;
+    let names: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let mut rows = stmt
+        .query(rusqlite::params_from_iter(values.iter()))
+        .map_err(|e| e.to_string())?;
+    let Some(row) = rows.next().map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    let mut obj = serde_json::Map::new();
+    for (i, name) in names.iter().enumerate() {
+        obj.insert(name.clone(), sql_to_json(row.get_ref(i).map_err(|e| e.to_string())?));
+    }
+    Ok(Some(obj))
+}
+
+/// Mirror subdirectory → table. Each row becomes `<dir>/<id>.json`.
+const MIRROR_KINDS: &[(&str, &str)] = &[
+    ("goals", "goals"),
+    ("tasks", "tasks"),
+    ("decisions", "decisions"),
+    ("lessons", "lessons"),
+];
+
+/// Columns that change on every heartbeat; leaving them out keeps mirror diffs
+/// down to edits a reviewer actually cares about.
+const MIRROR_VOLATILE: &[&str] = &["updated_at", "last_ping_at", "lease_expires_at"];
+
+fn mirror_dir(db_path: &Path) -> PathBuf {
+    db_path.parent().unwrap_or_else(|| Path::new(".")).join("mirror")
+}
+
+fn mirror_enabled(conn: &Connection) -> bool {
+    conn.query_row("SELECT value FROM settings WHERE key='mirror_enabled'", [], |r| r.get::<_, String>(0))
+        .map(|v| v == "1")
+        .unwrap_or(false)
+}
+
+fn task_prerequisite_ids(conn: &Connection, task_id: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT depends_on FROM task_dependencies WHERE task_id=?1 ORDER BY depends_on")
+        .map_err(|e| e.to_string())?;
+    let mapped = stmt
+        .query_map(params![task_id], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    mapped
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// (goal ids, task ids) a decision is linked to, sorted for stable mirror output.
+fn decision_link_ids(conn: &Connection, decision_id: &str) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(goal_id,''), COALESCE(task_id,'') FROM decision_links
+             WHERE decision_id=?1 ORDER BY goal_id, task_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<(String, String)> = stmt
+        .query_map(params![decision_id], |r| Ok((r.get(0)?, r.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let goals = rows.iter().filter(|r| !r.0.is_empty()).map(|r| r.0.clone()).collect();
+    let tasks = rows.iter().filter(|r| !r.1.is_empty()).map(|r| r.1.clone()).collect();
+    Ok((goals, tasks))
+}
+
+/// Bring `.imi/mirror/` in line with the DB. Only files whose content changed are
+/// rewritten, so an unchanged DB leaves the working tree clean. Returns
+/// (files written, files removed).
+fn write_mirror(conn: &Connection, db_path: &Path) -> Result<(usize, usize), String> {
+    let root = mirror_dir(db_path);
+    let (mut written, mut removed) = (0usize, 0usize);
+    for (dir, table) in MIRROR_KINDS {
+        let dir_path = root.join(dir);
+        fs::create_dir_all(&dir_path).map_err(|e| format!("failed to create {}: {e}", dir_path.display()))?;
+        let mut keep = Vec::new();
+        for mut row in dump_table(conn, table, None)? {
+            let Some(id) = row.get("id").and_then(Value::as_str).map(String::from) else {
+                continue;
+            };
+            for col in MIRROR_VOLATILE {
+                row.remove(*col);
+            }
+            if *table == "tasks" {
+                row.insert("depends_on".to_string(), json!(task_prerequisite_ids(conn, &id)?));
+            }
+            if *table == "decisions" {
+                let (goals, tasks) = decision_link_ids(conn, &id)?;
+                row.insert("linked_goals".to_string(), json!(goals));
+                row.insert("linked_tasks".to_string(), json!(tasks));
+            }
+            let mut body = serde_json::to_string_pretty(&Value::Object(row)).map_err(|e| e.to_string())?;
+            body.push('\n');
+            let file = dir_path.join(format!("{id}.json"));
+            if fs::read_to_string(&file).ok().as_deref() != Some(body.as_str()) {
+                fs::write(&file, body).map_err(|e| format!("failed to write {}: {e}", file.display()))?;
+                written += 1;
+            }
+            keep.push(format!("{id}.json"));
+        }
+        for entry in fs::read_dir(&dir_path).map_err(|e| e.to_string())?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with(".json") && !keep.contains(&name) {
+                fs::remove_file(entry.path()).map_err(|e| e.to_string())?;
+                removed += 1;
+            }
+        }
+    }
+    Ok((written, removed))
+}
+
+/// Make the mirrored tables match `.imi/mirror/`: upsert every file and drop rows
+/// that no longer have one. Returns (rows upserted, rows deleted).
+fn rebuild_from_mirror(conn: &mut Connection, db_path: &Path) -> Result<(usize, usize), String> {
+    let root = mirror_dir(db_path);
+    if !root.is_dir() {
+        return Err(format!("no mirror at {} — run `imi mirror enable` first", root.display()));
+    }
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let (mut upserted, mut deleted) = (0usize, 0usize);
+    let mut task_deps: Vec<(String, Vec<String>)> = Vec::new();
+    let mut decision_links: Vec<(String, Vec<String>, Vec<String>)> = Vec::new();
+    let mut seen: Vec<(&str, Vec<String>)> = Vec::new();
+
+    for (dir, table) in MIRROR_KINDS {
+        let columns = table_columns(&tx, table)?;
+        let mut files: Vec<PathBuf> = fs::read_dir(root.join(dir))
+            .map(|rd| rd.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default();
+        files.retain(|p| p.extension().and_then(|x| x.to_str()) == Some("json"));
+        files.sort();
+        let mut ids = Vec::new();
+        for file in files {
+            let text = fs::read_to_string(&file).map_err(|e| format!("failed to read {}: {e}", file.display()))?;
+            let row: serde_json::Map<String, Value> = serde_json::from_str(&text)
+                .map_err(|e| format!("{}: {e}", file.display()))?;
+            let id = row
+                .get("id")
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("{}: missing id", file.display()))?
+                .to_string();
+            let cols: Vec<&String> = row.keys().filter(|k| columns.iter().any(|c| &c.0 == *k)).collect();
+            let updates = cols
+                .iter()
+                .filter(|c| c.as_str() != "id")
+                .map(|c| format!("{c}=excluded.{c}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let sql = format!(
+                "INSERT INTO {table} ({}) VALUES ({}) ON CONFLICT(id) DO {}",
+                cols.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", "),
+                (1..=cols.len()).map(|i| format!("?{i}")).collect::<Vec<_>>().join(", "),
+                if updates.is_empty() { "NOTHING".to_string() } else { format!("UPDATE SET {updates}") }
+            );
+            let values: Vec<rusqlite::types::Value> = cols.iter().map(|c| json_to_sql(&row[*c])).collect();
+            tx.execute(&sql, rusqlite::params_from_iter(values))
+                .map_err(|e| format!("{}: {e}", file.display()))?;
+            upserted += 1;
+            if *table == "tasks" {
+                let deps = row
+                    .get("depends_on")
+                    .and_then(Value::as_array)
+                    .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                    .unwrap_or_default();
+                task_deps.push((id.clone(), deps));
+            }
+            if *table == "decisions" {
+                let ids_at = |key: &str| -> Vec<String> {
+                    row.get(key)
+                        .and_then(Value::as_array)
+                        .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                        .unwrap_or_default()
+                };
+                decision_links.push((id.clone(), ids_at("linked_goals"), ids_at("linked_tasks")));
+            }
+            ids.push(id);
+        }
+        seen.push((table, ids));
+    }
+
+    for (task_id, deps) in &task_deps {
+        tx.execute("DELETE FROM task_dependencies WHERE task_id=?1", params![task_id])
+            .map_err(|e| e.to_string())?;
+        for dep in deps {
+            tx.execute(
+                "INSERT OR IGNORE INTO task_dependencies (task_id, depends_on, created_at) VALUES (?1, ?2, ?3)",
+                params![task_id, dep, now_ts()],
+            )
+            .map_err(|e| format!("task {task_id} depends on {dep}: {e}"))?;
+        }
+    }
+
+    // Keep existing link rows (and their source) where the file still lists them.
+    for (decision_id, goals, tasks) in &decision_links {
+        let (local_goals, local_tasks) = decision_link_ids(&tx, decision_id)?;
+        for g in local_goals.iter().filter(|g| !goals.contains(g)) {
+            tx.execute("DELETE FROM decision_links WHERE decision_id=?1 AND goal_id=?2", params![decision_id, g])
+                .map_err(|e| e.to_string())?;
+        }
+        for t in local_tasks.iter().filter(|t| !tasks.contains(t)) {
+            tx.execute("DELETE FROM decision_links WHERE decision_id=?1 AND task_id=?2", params![decision_id, t])
+                .map_err(|e| e.to_string())?;
+        }
+        for g in goals {
+            link_decision(&tx, decision_id, Some(g), None, "explicit")
+                .map_err(|e| format!("decision {decision_id} → goal {g}: {e}"))?;
+        }
+        for t in tasks {
+            link_decision(&tx, decision_id, None, Some(t), "explicit")
+                .map_err(|e| format!("decision {decision_id} → task {t}: {e}"))?;
+        }
+    }
+
+    // Children before parents so ON DELETE actions see a consistent graph.
+    for (table, ids) in seen.iter().rev() {
+        let mut stmt = tx.prepare(&format!("SELECT id FROM {table}")).map_err(|e| e.to_string())?;
+        let local: Vec<String> = stmt
+            .query_map([], |r| r.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        drop(stmt);
+        for id in local.iter().filter(|id| !ids.contains(id)) {
+            deleted += tx
+                .execute(&format!("DELETE FROM {table} WHERE id=?1"), params![id])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok((upserted, deleted))
+}
+
+fn cmd_mirror(conn: &mut Connection, db_path: &Path, out: OutputCtx, action: MirrorAction) -> Result<(), String> {
+    let dir = mirror_dir(db_path);
+    match action {
+        MirrorAction::Enable | MirrorAction::Sync => {
+            if matches!(action, MirrorAction::Enable) {
+                conn.execute(
+                    "INSERT OR REPLACE INTO settings (key, value) VALUES ('mirror_enabled', '1')",
+                    [],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            let (written, removed) = write_mirror(conn, db_path)?;
+            if out.is_json() {
+                println!(
+                    "{}",
+                    json!({"ok": true, "path": dir.display().to_string(), "written": written, "removed": removed, "enabled": mirror_enabled(conn)})
+                );
+            } else if out.is_toon() {
+                let mut t = ToonBuilder::new();
+                t.section(
+                    "mirror",
+                    &["path", "written", "removed"],
+                    vec![vec![dir.display().to_string(), written.to_string(), removed.to_string()]],
+                );
+                print!("{}", t.finish());
+            } else {
+                println!("🪞 Mirror → {}  ({written} written, {removed} removed)", dir.display());
+                if matches!(action, MirrorAction::Enable) {
+                    println!("   Refreshed after every change. Commit it so reviewers see what agents decided.");
+                }
+            }
+            Ok(())
+        }
+        MirrorAction::Disable => {
+            conn.execute("DELETE FROM settings WHERE key='mirror_enabled'", [])
+                .map_err(|e| e.to_string())?;
+            emit_simple_ok(out, "Mirror disabled — existing files left in place")
+        }
+        MirrorAction::Rebuild => {
+            let (upserted, deleted) = rebuild_from_mirror(conn, db_path)?;
+            if out.is_json() {
+                println!("{}", json!({"ok": true, "path": dir.display().to_string(), "upserted": upserted, "deleted": deleted}));
+            } else if out.is_toon() {
+                let mut t = ToonBuilder::new();
+                t.section(
+                    "rebuild",
+                    &["path", "upserted", "deleted"],
+                    vec![vec![dir.display().to_string(), upserted.to_string(), deleted.to_string()]],
+                );
+                print!("{}", t.finish());
+            } else {
+                println!("Rebuilt from {}: {upserted} row(s) loaded, {deleted} removed", dir.display());
+            }
+            Ok(())
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ConfigKind {
+    Text,
+    /// Positive integer
+    Count,
+    /// Non-negative integer where 0 switches the feature off
+    Seconds,
+    /// Span accepted by `parse_duration_secs`, e.g. `30m`
+    Duration,
+}
+
+struct ConfigKey {
+    key: &'static str,
+    default: &'static str,
+    env: &'static str,
+    kind: ConfigKind,
+    /// Older per-project row in the `settings` table, read just below .imi/config.toml.
+    setting: Option<&'static str>,
+    help: &'static str,
+}
+
+const CONFIG_KEYS: &[ConfigKey] = &[
+    ConfigKey { key: "run.model", default: "claude-sonnet-4-5", env: "IMI_MODEL", kind: ConfigKind::Text, setting: None, help: "Model hankweave runs for imi run, wrap and orchestrate" },
+    ConfigKey { key: "worker.ping_secs", default: "300", env: "IMI_PING_SECS", kind: ConfigKind::Seconds, setting: None, help: "Heartbeat interval for wrapped workers" },
+    ConfigKey { key: "worker.backend", default: "hankweave", env: "IMI_WORKER_BACKEND", kind: ConfigKind::Text, setting: None, help: "Worker backend for imi run, wrap and orchestrate; declare more under [backends.<name>]" },
+    ConfigKey { key: "worker.checkpoint_secs", default: "900", env: "IMI_CHECKPOINT_SECS", kind: ConfigKind::Seconds, setting: None, help: "Checkpoint interval for wrapped workers" },
+    ConfigKey { key: "worker.task_timeout", default: "0", env: "IMI_TASK_TIMEOUT", kind: ConfigKind::Seconds, setting: None, help: "Seconds a wrapped or orchestrated worker may run before it is killed and its task failed; 0 for no limit" },
+    ConfigKey { key: "worker.shutdown_grace", default: "10s", env: "IMI_SHUTDOWN_GRACE", kind: ConfigKind::Duration, setting: None, help: "How long workers get to exit after SIGINT/SIGTERM before they are killed; a second signal kills them at once" },
+    ConfigKey { key: "orchestrate.workers", default: "4", env: "IMI_WORKERS", kind: ConfigKind::Count, setting: None, help: "Parallel workers for imi orchestrate" },
+    ConfigKey { key: "locks.lease", default: "30m", env: "IMI_LOCK_LEASE", kind: ConfigKind::Duration, setting: Some("lock_lease"), help: "How long a claimed task stays locked without a ping" },
+    ConfigKey { key: "tasks.max_attempts", default: "3", env: "IMI_MAX_ATTEMPTS", kind: ConfigKind::Count, setting: Some("max_attempts"), help: "Failures before imi fail blocks a task for a human" },
+    ConfigKey { key: "memory.compact_after", default: "30d", env: "IMI_MEMORY_COMPACT_AFTER", kind: ConfigKind::Duration, setting: None, help: "Age at which imi memory compact folds lifecycle rows" },
+    ConfigKey { key: "checks.timeout", default: "10m", env: "IMI_CHECK_TIMEOUT", kind: ConfigKind::Duration, setting: None, help: "How long one acceptance check command may run before it is killed and counted as failed" },
+    ConfigKey { key: "goals.review_after", default: "14d", env: "IMI_GOALS_REVIEW_AFTER", kind: ConfigKind::Duration, setting: None, help: "How long after archiving a goal is due for an outcome review" },
+    ConfigKey { key: "journal.keep", default: "100", env: "IMI_JOURNAL_KEEP", kind: ConfigKind::Count, setting: None, help: "Commands kept in the undo journal" },
+    ConfigKey { key: "context.goals", default: "10", env: "IMI_CONTEXT_GOALS", kind: ConfigKind::Count, setting: None, help: "Active goals shown by imi context" },
+    ConfigKey { key: "context.direction", default: "10", env: "IMI_CONTEXT_DIRECTION", kind: ConfigKind::Count, setting: None, help: "Direction notes from the last week shown by imi context" },
+    ConfigKey { key: "context.goal_memories", default: "30", env: "IMI_CONTEXT_GOAL_MEMORIES", kind: ConfigKind::Count, setting: None, help: "Memories shown by imi context <goal>" },
+    ConfigKey { key: "context.decisions", default: "15", env: "IMI_CONTEXT_DECISIONS", kind: ConfigKind::Count, setting: None, help: "Decisions shown by imi context" },
+    ConfigKey { key: "context.lessons", default: "15", env: "IMI_CONTEXT_LESSONS", kind: ConfigKind::Count, setting: None, help: "Lessons shown by imi context and imi next" },
+    ConfigKey { key: "context.memories", default: "15", env: "IMI_CONTEXT_MEMORIES", kind: ConfigKind::Count, setting: None, help: "Memories shown by imi context and imi next" },
+    ConfigKey { key: "next.decisions", default: "10", env: "IMI_NEXT_DECISIONS", kind: ConfigKind::Count, setting: None, help: "Decisions included when imi next hands out a task" },
+    ConfigKey { key: "next.direction", default: "8", env: "IMI_NEXT_DIRECTION", kind: ConfigKind::Count, setting: None, help: "Direction notes included when imi next hands out a task" },
+];
+
+fn config_key(key: &str) -> Result<&'static ConfigKey, String> {
+    CONFIG_KEYS.iter().find(|k| k.key == key).ok_or_else(|| {
+        let known: Vec<&str> = CONFIG_KEYS.iter().map(|k| k.key).collect();
+        format!("unknown config key '{key}' (known: {})", known.join(", "))
+    })
+}
+
+fn validate_config_value(spec: &ConfigKey, value: &str) -> Result<(), String> {
+    let ok = match spec.kind {
+        ConfigKind::Text => !value.trim().is_empty(),
+        ConfigKind::Count => value.trim().parse::<u64>().is_ok_and(|n| n >= 1),
+        ConfigKind::Seconds => value.trim().parse::<u64>().is_ok(),
+        ConfigKind::Duration => parse_duration_secs(value).is_ok(),
+    };
+    if ok {
+        return Ok(());
+    }
+    let want = match spec.kind {
+        ConfigKind::Text => "a non-empty string",
+        ConfigKind::Count => "a whole number >= 1",
+        ConfigKind::Seconds => "a whole number of seconds (0 disables)",
+        ConfigKind::Duration => "a duration like 90s, 30m, 2h or 1d",
+    };
+    Err(format!("invalid value '{value}' for {}: expected {want}", spec.key))
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    if let Ok(dir) = env::var("XDG_CONFIG_HOME") {
+        if !dir.trim().is_empty() {
+            return Some(PathBuf::from(dir).join("imi").join("config.toml"));
+        }
+    }
+    env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".config").join("imi").join("config.toml"))
+}
+
+/// `config.toml` sits next to `state.db`, so it is `.imi/config.toml` for a normal project.
+fn project_config_path(db_path: &Path) -> PathBuf {
+    db_path.parent().unwrap_or_else(|| Path::new(".")).join("config.toml")
+}
+
+/// Reads the flat subset of TOML imi writes: `[section]` headers and `key = value` lines
+/// holding a string, integer or boolean. Keys come back dotted (`worker.ping_secs`).
+fn parse_config_toml(path: &Path, text: &str) -> Result<BTreeMap<String, String>, String> {
+    let mut section = String::new();
+    let mut map = BTreeMap::new();
+    for (i, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let err = |msg: &str| format!("{}:{}: {msg}", path.display(), i + 1);
+        if let Some(rest) = line.strip_prefix('[') {
+            let name = rest
+                .split('#')
+                .next()
+                .unwrap_or("")
+                .trim_end()
+                .strip_suffix(']')
+                .ok_or_else(|| err("unterminated section header"))?;
+            section = name.trim().to_string();
+            continue;
+        }
+        let (key, value) = line.split_once('=').ok_or_else(|| err("expected `key = value`"))?;
+        let key = key.trim().trim_matches('"');
+        let value = parse_toml_scalar(value.trim())
+            .ok_or_else(|| err("unsupported value (use a quoted string, an integer or a boolean)"))?;
+        let full = if section.is_empty() { key.to_string() } else { format!("{section}.{key}") };
+        map.insert(full, value);
+    }
+    Ok(map)
+}
+
+fn parse_toml_scalar(v: &str) -> Option<String> {
+    let rest_is_comment = |tail: &str| {
+        let tail = tail.trim();
+        tail.is_empty() || tail.starts_with('#')
+    };
+    if let Some(rest) = v.strip_prefix('\'') {
+        let (lit, tail) = rest.split_once('\'')?;
+        return rest_is_comment(tail).then(|| lit.to_string());
+    }
+    if let Some(rest) = v.strip_prefix('"') {
+        let mut out = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return rest_is_comment(chars.as_str()).then_some(out),
+                '\\' => match chars.next()? {
+                    'n' => out.push('\n'),
+                    't' => out.push('\t'),
+                    c => out.push(c),
+                },
+                c => out.push(c),
+            }
+        }
+        return None;
+    }
+    let bare = v.split('#').next().unwrap_or("").trim();
+    let valid = !bare.is_empty() && bare.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    valid.then(|| bare.to_string())
+}
+
+fn format_toml_scalar(spec: &ConfigKey, value: &str) -> String {
+    match spec.kind {
+        ConfigKind::Count | ConfigKind::Seconds => value.trim().to_string(),
+        ConfigKind::Text | ConfigKind::Duration => {
+            format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+    }
+}
+
+fn read_config_file(path: &Path) -> Result<BTreeMap<String, String>, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => parse_config_toml(path, &text),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(format!("failed to read {}: {e}", path.display())),
+    }
+}
+
+/// Rewrite one `key = value` line in place, keeping comments and every other line as written.
+fn write_config_file(path: &Path, spec: &ConfigKey, value: &str) -> Result<(), String> {
+    let (section, name) = spec.key.split_once('.').unwrap_or(("", spec.key));
+    let entry = format!("{name} = {}", format_toml_scalar(spec, value));
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("failed to read {}: {e}", path.display())),
+    };
+    parse_config_toml(path, &text)?;
+
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let mut current = String::new();
+    let mut section_end = None;
+    let mut replaced = false;
+    for (i, raw) in lines.iter_mut().enumerate() {
+        let line = raw.trim().to_string();
+        if let Some(rest) = line.strip_prefix('[') {
+            current = rest.split(']').next().unwrap_or("").trim().to_string();
+            if current == section {
+                section_end = Some(i + 1);
+            }
+            continue;
+        }
+        if current != section || line.is_empty() {
+            continue;
+        }
+        if line.split_once('=').is_some_and(|(k, _)| k.trim().trim_matches('"') == name) {
+            *raw = entry.clone();
+            replaced = true;
+            break;
+        }
+        section_end = Some(i + 1);
+    }
+    if !replaced {
+        match section_end {
+            Some(at) => lines.insert(at, entry),
+            None => {
+                if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.push(format!("[{section}]"));
+                lines.push(entry);
+            }
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    fs::write(path, lines.join("\n") + "\n").map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
+/// Every key's effective value and where it came from, highest layer winning:
+/// built-in default, user config, legacy `settings` row, project config, env var.
+/// CLI flags sit on top of this at each call site.
+fn resolve_config(conn: &Connection) -> Result<Vec<(&'static ConfigKey, String, String)>, String> {
+    let mut files: Vec<(String, BTreeMap<String, String>)> = Vec::new();
+    if let Some(path) = user_config_path() {
+        files.push((format!("user config {}", path.display()), read_config_file(&path)?));
+    }
+    let project = conn.path().filter(|p| !p.is_empty()).map(|p| project_config_path(Path::new(p)));
+
+    let mut resolved = Vec::new();
+    for spec in CONFIG_KEYS {
+        let mut value = spec.default.to_string();
+        let mut origin = "default".to_string();
+        for (file_origin, map) in &files {
+            if let Some(v) = map.get(spec.key) {
+                value = v.clone();
+                origin = file_origin.clone();
+            }
+        }
+        if let Some(setting) = spec.setting {
+            if let Some(v) = conn
+                .query_row("SELECT value FROM settings WHERE key=?1", params![setting], |r| r.get::<_, String>(0))
+                .optional()
+                .map_err(|e| e.to_string())?
+            {
+                value = v;
+                origin = format!("settings table ({setting})");
+            }
+        }
+        resolved.push((spec, value, origin));
+    }
+    if let Some(path) = project {
+        let map = read_config_file(&path)?;
+        for (spec, value, origin) in resolved.iter_mut() {
+            if let Some(v) = map.get(spec.key) {
+                *value = v.clone();
+                *origin = format!("project config {}", path.display());
+            }
+        }
+    }
+    for (spec, value, origin) in resolved.iter_mut() {
+        if let Ok(v) = env::var(spec.env) {
+            if !v.trim().is_empty() {
+                *value = v;
+                *origin = format!("env {}", spec.env);
+            }
+        }
+    }
+    Ok(resolved)
 }
 
-fn ops_read_line(prompt: &str) -> Result<String, String> {
-    print!("{}", prompt);
-    io::stdout().flush().map_err(|e| e.to_string())?;
-    let mut line = String::new();
-    io::stdin().read_line(&mut line).map_err(|e| e.to_string())?;
-    Ok(line.trim().to_string())
+/// Effective configuration, resolved once per command in `main` and passed down
+/// to whatever needs a setting.
+struct Config {
+    resolved: Vec<(&'static ConfigKey, String, String)>,
 }
 
-fn cmd_ops(conn: &Connection, out: OutputCtx, args: Vec<String>) -> Result<(), String> {
-    if args.is_empty() {
-        return cmd_context(conn, out, None);
+impl Config {
+    /// A broken config file falls back to the built-in defaults here; `imi config list`
+    /// reports the problem.
+    fn load(conn: &Connection) -> Self {
+        Self { resolved: resolve_config(conn).unwrap_or_default() }
     }
-    conn.execute(
-        "INSERT INTO direction_notes (id, content, author, created_at) VALUES (?1, ?2, ?3, ?4)",
-        params![gen_id(), args.join(" "), current_agent(None), now_ts()],
-    )
-    .map_err(|e| e.to_string())?;
-    println!("Direction noted.");
-    Ok(())
-}
 
-fn cmd_delete(conn: &Connection, out: OutputCtx, id: String) -> Result<(), String> {
-    if let Some(goal_id) = resolve_id_prefix(conn, "goals", &id)? {
-        conn.execute(
-            "DELETE FROM memories WHERE goal_id=?1 OR task_id IN (SELECT id FROM tasks WHERE goal_id=?1)",
-            params![goal_id],
-        )
-        .map_err(|e| e.to_string())?;
-        conn.execute("DELETE FROM tasks WHERE goal_id=?1", params![goal_id])
-            .map_err(|e| e.to_string())?;
-        conn.execute("DELETE FROM goals WHERE id=?1", params![goal_id])
-            .map_err(|e| e.to_string())?;
-        emit_simple_ok(out, "Goal deleted")?;
-        return Ok(());
+    /// Effective value of a known key. An invalid value falls back to the built-in default.
+    fn value(&self, key: &str) -> String {
+        match self.resolved.iter().find(|(spec, _, _)| spec.key == key) {
+            Some((spec, value, _)) if validate_config_value(spec, value).is_ok() => value.trim().to_string(),
+            Some((spec, _, _)) => spec.default.to_string(),
+            None => config_key(key).map(|spec| spec.default.to_string()).unwrap_or_default(),
+        }
     }
 
-    if let Some(task_id) = resolve_id_prefix(conn, "tasks", &id)? {
-        let goal_id: Option<String> = conn
-            .query_row("SELECT goal_id FROM tasks WHERE id=?1", params![task_id.clone()], |r| {
-                r.get(0)
-            })
-            .optional()
-            .map_err(|e| e.to_string())?
-            .flatten();
-        conn.execute("DELETE FROM memories WHERE task_id=?1", params![task_id.clone()])
-            .map_err(|e| e.to_string())?;
-        conn.execute("DELETE FROM tasks WHERE id=?1", params![task_id])
-            .map_err(|e| e.to_string())?;
-        if let Some(gid) = goal_id {
-            let _ = sync_goal(conn, &gid);
-        }
-        emit_simple_ok(out, "Task deleted")?;
-        return Ok(());
+    fn u64(&self, key: &str) -> u64 {
+        self.value(key).parse().unwrap_or(0)
     }
 
-    Err("id not found in goals or tasks".to_string())
+    fn limit(&self, key: &str) -> i64 {
+        self.u64(key) as i64
+    }
+
+    fn duration_secs(&self, key: &str) -> i64 {
+        parse_duration_secs(&self.value(key)).unwrap_or(1800)
+    }
+
+    /// Lease length for new claims (`locks.lease`, default 30m).
+    fn lease_secs(&self) -> i64 {
+        self.duration_secs("locks.lease")
+    }
+
+    /// Failures a task may accumulate before `imi fail` blocks it (`tasks.max_attempts`, default 3).
+    fn max_attempts(&self) -> i64 {
+        self.limit("tasks.max_attempts")
+    }
 }
 
-fn cmd_reset(conn: &Connection, out: OutputCtx, force: bool) -> Result<(), String> {
-    if !force {
-        if !io::stdin().is_terminal() {
-            return Err("reset requires --force in non-interactive mode".to_string());
+fn cmd_config(conn: &Connection, db_path: &Path, out: OutputCtx, action: ConfigAction) -> Result<(), String> {
+    match action {
+        ConfigAction::Set { key, value, global } => {
+            let spec = config_key(&key)?;
+            validate_config_value(spec, &value)?;
+            let path = if global {
+                user_config_path().ok_or_else(|| "cannot locate the user config: HOME is not set".to_string())?
+            } else {
+                project_config_path(db_path)
+            };
+            write_config_file(&path, spec, value.trim())?;
+            let shadowed = env::var(spec.env).ok().filter(|v| !v.trim().is_empty()).is_some();
+            if out.is_json() {
+                println!("{}", json!({"ok": true, "key": spec.key, "value": value.trim(), "path": path.display().to_string()}));
+            } else if out.is_toon() {
+                let mut t = ToonBuilder::new();
+                t.section("config", &["key", "value", "path"], vec![vec![spec.key.to_string(), value.trim().to_string(), path.display().to_string()]]);
+                print!("{}", t.finish());
+            } else {
+                println!("{} = {}  → {}", spec.key, value.trim(), path.display());
+                if shadowed {
+                    println!("⚠ {} is set in the environment and still wins over this file", spec.env);
+                }
+            }
+            Ok(())
         }
-        print!("This will delete goals/tasks/memories. Type 'yes' to continue: ");
-        let _ = io::stdout().flush();
-        let mut line = String::new();
-        io::stdin()
-            .read_line(&mut line)
-            .map_err(|e| e.to_string())?;
-        if line.trim() != "yes" {
-            emit_simple_ok(out, "Reset cancelled")?;
-            return Ok(());
+        ConfigAction::Get { key, show_origin } => {
+            config_key(&key)?;
+            let (spec, value, origin) = resolve_config(conn)?
+                .into_iter()
+                .find(|(spec, _, _)| spec.key == key)
+                .ok_or_else(|| format!("unknown config key '{key}'"))?;
+            validate_config_value(spec, &value).map_err(|e| format!("{e} (from {origin})"))?;
+            if out.is_json() {
+                println!("{}", json!({"key": spec.key, "value": value, "origin": origin}));
+            } else if out.is_toon() {
+                let mut t = ToonBuilder::new();
+                t.section("config", &["key", "value", "origin"], vec![vec![spec.key.to_string(), value, origin]]);
+                print!("{}", t.finish());
+            } else if show_origin {
+                println!("{value}\t{origin}");
+            } else {
+                println!("{value}");
+            }
+            Ok(())
+        }
+        ConfigAction::List { show_origin } => {
+            let resolved = resolve_config(conn)?;
+            if out.is_json() {
+                let items: Vec<Value> = resolved
+                    .iter()
+                    .map(|(spec, value, origin)| {
+                        json!({
+                            "key": spec.key,
+                            "value": value,
+                            "origin": origin,
+                            "env": spec.env,
+                            "description": spec.help,
+                            "valid": validate_config_value(spec, value).is_ok(),
+                        })
+                    })
+                    .collect();
+                println!("{}", json!({"config": items}));
+            } else if out.is_toon() {
+                let rows = resolved
+                    .iter()
+                    .map(|(spec, value, origin)| vec![spec.key.to_string(), value.clone(), origin.clone()])
+                    .collect();
+                let mut t = ToonBuilder::new();
+                t.section("config", &["key", "value", "origin"], rows);
+                print!("{}", t.finish());
+            } else {
+                for (spec, value, origin) in &resolved {
+                    let warn = match validate_config_value(spec, value) {
+                        Ok(()) => String::new(),
+                        Err(_) => format!("  {}", paint(out, "31", &format!("⚠ invalid, using {}", spec.default))),
+                    };
+                    if show_origin {
+                        println!("{} = {value}  {}{warn}", spec.key, paint(out, "2", &format!("({origin})")));
+                    } else {
+                        println!("{} = {value}  {}{warn}", spec.key, paint(out, "2", &format!("# {}", spec.help)));
+                    }
+                }
+            }
+            Ok(())
         }
     }
-
-    conn.execute("DELETE FROM memories", []).map_err(|e| e.to_string())?;
-    conn.execute("DELETE FROM tasks", []).map_err(|e| e.to_string())?;
-    conn.execute("DELETE FROM goals", []).map_err(|e| e.to_string())?;
-
-    emit_simple_ok(out, "Reset complete")
 }
 
-fn cmd_stats(conn: &Connection, out: OutputCtx) -> Result<(), String> {
+fn cmd_stats(conn: &Connection, out: OutputCtx, cfg: &Config) -> Result<(), String> {
     let now = now_ts();
     let week_ago = now - 7 * 24 * 3600;
 
@@ -3407,8 +8478,8 @@ fn cmd_stats(conn: &Connection, out: OutputCtx) -> Result<(), String> {
         .unwrap_or(0);
     let stale_count: i64 = conn
         .query_row(
-            "SELECT COUNT(*) FROM tasks WHERE status='in_progress' AND COALESCE(last_ping_at,updated_at,created_at,0) < ?1",
-            params![now - 1800],
+            &format!("SELECT COUNT(*) FROM tasks WHERE status='in_progress' AND {LEASE_EXPIRY_SQL} < ?1"),
+            params![now, cfg.lease_secs()],
             |r| r.get(0),
         )
         .unwrap_or(0);
@@ -3510,6 +8581,83 @@ fn cmd_instructions(out: OutputCtx, target: Option<String>) -> Result<(), String
     Ok(())
 }
 
+fn cmd_db(conn: &Connection, db_path: &Path, out: OutputCtx, action: DbAction) -> Result<(), String> {
+    let current = schema_version(conn)?;
+    let latest = latest_schema_version();
+    let pending = pending_migrations(conn)?;
+
+    let (dry_run, applying) = match action {
+        DbAction::Status => (true, false),
+        DbAction::Migrate { dry_run } => (dry_run, true),
+    };
+    if !dry_run {
+        for migration in &pending {
+            apply_migration(conn, migration)?;
+        }
+    }
+    let version_after = schema_version(conn)?;
+
+    if out.is_json() {
+        let pending_json: Vec<Value> = pending
+            .iter()
+            .map(|m| json!({"version": m.version, "name": m.name}))
+            .collect();
+        let key = if applying && !dry_run { "applied" } else { "pending" };
+        println!(
+            "{}",
+            json!({
+                "ok": true,
+                "db_path": db_path.display().to_string(),
+                "schema_version": version_after,
+                "previous_version": current,
+                "latest_version": latest,
+                "dry_run": dry_run,
+                key: pending_json
+            })
+        );
+        return Ok(());
+    }
+
+    if out.is_toon() {
+        let mut t = ToonBuilder::new();
+        t.section(
+            "schema",
+            &["db_path", "version", "latest"],
+            vec![vec![db_path.display().to_string(), version_after.to_string(), latest.to_string()]],
+        );
+        t.section(
+            if applying && !dry_run { "applied" } else { "pending" },
+            &["version", "name"],
+            pending
+                .iter()
+                .map(|m| vec![m.version.to_string(), m.name.to_string()])
+                .collect(),
+        );
+        print!("{}", t.finish());
+        return Ok(());
+    }
+
+    println!("DB: {}", db_path.display());
+    println!("Schema: v{version_after} (latest v{latest})");
+    if pending.is_empty() {
+        println!("Up to date — no pending migrations.");
+    } else if applying && !dry_run {
+        println!("Applied {} migration(s):", pending.len());
+        for m in &pending {
+            println!("  ✓ v{}  {}", m.version, m.name);
+        }
+    } else {
+        println!("{} pending migration(s):", pending.len());
+        for m in &pending {
+            println!("  · v{}  {}", m.version, m.name);
+        }
+        if applying {
+            println!("Dry run — nothing applied. Run `imi db migrate` to apply.");
+        }
+    }
+    Ok(())
+}
+
 fn emit_simple_ok(out: OutputCtx, message: &str) -> Result<(), String> {
     if out.is_json() {
         println!("{}", json!({"ok": true, "message": message}));
@@ -3578,7 +8726,96 @@ fn open_connection(path: &Path) -> Result<Connection, String> {
     Ok(conn)
 }
 
+/// One step in the schema history. Versions are stored in `PRAGMA user_version`
+/// and must be strictly increasing; never edit a migration once it has shipped —
+/// append a new one instead.
+struct Migration {
+    version: i64,
+    name: &'static str,
+    apply: fn(&Connection) -> Result<(), String>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "baseline schema", apply: migrate_baseline },
+    Migration { version: 2, name: "tasks.last_ping_at", apply: migrate_last_ping_at },
+    Migration { version: 3, name: "task dependencies", apply: migrate_task_dependencies },
+    Migration { version: 4, name: "full-text search index", apply: migrate_search_index },
+    Migration { version: 5, name: "decision links", apply: migrate_decision_links },
+    Migration { version: 6, name: "decision lifecycle", apply: migrate_decision_lifecycle },
+    Migration { version: 7, name: "task retry budget", apply: migrate_retry_budget },
+    Migration { version: 8, name: "task lock leases", apply: migrate_lock_leases },
+    Migration { version: 9, name: "memory archive", apply: migrate_memory_archive },
+    Migration { version: 10, name: "memory expiry", apply: migrate_memory_expiry },
+    Migration { version: 11, name: "edit revisions", apply: migrate_revisions },
+    Migration { version: 12, name: "undo journal", apply: migrate_journal },
+    Migration { version: 13, name: "review gate", apply: migrate_review_gate },
+    Migration { version: 14, name: "acceptance checks", apply: migrate_acceptance_checks },
+    Migration { version: 15, name: "goal outcomes", apply: migrate_goal_outcomes },
+    Migration { version: 16, name: "task runtime limit", apply: migrate_task_max_runtime },
+    Migration { version: 17, name: "journal after-images", apply: migrate_journal_after },
+];
+
+fn latest_schema_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+fn schema_version(conn: &Connection) -> Result<i64, String> {
+    conn.query_row("PRAGMA user_version", [], |r| r.get(0))
+        .map_err(|e| e.to_string())
+}
+
+/// Migrations not yet applied to this DB. Refuses to continue if the DB was
+/// written by a newer imi, since older code would silently misread new columns.
+fn pending_migrations(conn: &Connection) -> Result<Vec<&'static Migration>, String> {
+    let current = schema_version(conn)?;
+    let latest = latest_schema_version();
+    if current > latest {
+        return Err(format!(
+            "database schema is v{current} but this imi (v{VERSION}) only understands up to v{latest} — run `imi update`"
+        ));
+    }
+    Ok(MIGRATIONS.iter().filter(|m| m.version > current).collect())
+}
+
+fn apply_migration(conn: &Connection, migration: &Migration) -> Result<(), String> {
+    let tx = rusqlite::Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    // Another process may have migrated while we waited for the write lock.
+    if schema_version(&tx)? >= migration.version {
+        return Ok(());
+    }
+    (migration.apply)(&tx)
+        .map_err(|e| format!("migration {} ({}) failed: {e}", migration.version, migration.name))?;
+    tx.pragma_update(None, "user_version", migration.version)
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
 fn run_schema(conn: &Connection) -> Result<(), String> {
+    for migration in pending_migrations(conn)? {
+        apply_migration(conn, migration)?;
+    }
+    Ok(())
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<(), String> {
+    let exists: Option<i64> = conn
+        .query_row(
+            &format!("SELECT 1 FROM pragma_table_info('{table}') WHERE name=?1 LIMIT 1"),
+            params![column],
+            |r| r.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if exists.is_none() {
+        conn.execute(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"), [])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Pre-migration DBs already have these tables, so everything here stays IF NOT EXISTS.
+fn migrate_baseline(conn: &Connection) -> Result<(), String> {
     conn.execute_batch(
         "CREATE TABLE IF NOT EXISTS goals (
   id TEXT PRIMARY KEY, name TEXT NOT NULL, description TEXT NOT NULL,
@@ -3641,22 +8878,267 @@ CREATE INDEX IF NOT EXISTS memories_created_at_idx ON memories(created_at);
 CREATE INDEX IF NOT EXISTS lessons_created_at_idx ON lessons(created_at);
 CREATE INDEX IF NOT EXISTS decisions_created_at_idx ON decisions(created_at);",
     )
+    .map_err(|e| e.to_string())
+}
+
+fn migrate_last_ping_at(conn: &Connection) -> Result<(), String> {
+    add_column_if_missing(conn, "tasks", "last_ping_at", "INTEGER")
+}
+
+fn migrate_task_dependencies(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS task_dependencies (
+  task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+  depends_on TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+  created_at INTEGER,
+  PRIMARY KEY (task_id, depends_on)
+);
+CREATE INDEX IF NOT EXISTS task_dependencies_depends_on_idx ON task_dependencies(depends_on);",
+    )
+    .map_err(|e| e.to_string())
+}
+
+// One FTS5 row per searchable record, kept current by triggers on each source
+// table. The trailing INSERT ... SELECT backfills rows that predate the index.
+fn migrate_search_index(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+  kind UNINDEXED, ref_id UNINDEXED, goal_id UNINDEXED, title, body,
+  tokenize = 'porter unicode61'
+);
+CREATE TRIGGER IF NOT EXISTS goals_search_ai AFTER INSERT ON goals BEGIN
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('goal', new.id, new.id, new.name, COALESCE(new.description,'') || ' ' || COALESCE(new.why,'') || ' ' || COALESCE(new.for_who,'') || ' ' || COALESCE(new.success_signal,'') || ' ' || COALESCE(new.context,''));
+END;
+CREATE TRIGGER IF NOT EXISTS goals_search_au AFTER UPDATE OF name, description, why, for_who, success_signal, context ON goals BEGIN
+  DELETE FROM search_index WHERE kind='goal' AND ref_id=old.id;
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('goal', new.id, new.id, new.name, COALESCE(new.description,'') || ' ' || COALESCE(new.why,'') || ' ' || COALESCE(new.for_who,'') || ' ' || COALESCE(new.success_signal,'') || ' ' || COALESCE(new.context,''));
+END;
+CREATE TRIGGER IF NOT EXISTS goals_search_ad AFTER DELETE ON goals BEGIN
+  DELETE FROM search_index WHERE kind='goal' AND ref_id=old.id;
+END;
+INSERT INTO search_index (kind, ref_id, goal_id, title, body)
+  SELECT 'goal', r.id, r.id, r.name, COALESCE(r.description,'') || ' ' || COALESCE(r.why,'') || ' ' || COALESCE(r.for_who,'') || ' ' || COALESCE(r.success_signal,'') || ' ' || COALESCE(r.context,'') FROM goals r;
+CREATE TRIGGER IF NOT EXISTS tasks_search_ai AFTER INSERT ON tasks BEGIN
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('task', new.id, new.goal_id, new.title, COALESCE(new.description,'') || ' ' || COALESCE(new.why,'') || ' ' || COALESCE(new.context,'') || ' ' || COALESCE(new.acceptance_criteria,'') || ' ' || COALESCE(new.summary,''));
+END;
+CREATE TRIGGER IF NOT EXISTS tasks_search_au AFTER UPDATE OF title, description, why, context, acceptance_criteria, summary, goal_id ON tasks BEGIN
+  DELETE FROM search_index WHERE kind='task' AND ref_id=old.id;
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('task', new.id, new.goal_id, new.title, COALESCE(new.description,'') || ' ' || COALESCE(new.why,'') || ' ' || COALESCE(new.context,'') || ' ' || COALESCE(new.acceptance_criteria,'') || ' ' || COALESCE(new.summary,''));
+END;
+CREATE TRIGGER IF NOT EXISTS tasks_search_ad AFTER DELETE ON tasks BEGIN
+  DELETE FROM search_index WHERE kind='task' AND ref_id=old.id;
+END;
+INSERT INTO search_index (kind, ref_id, goal_id, title, body)
+  SELECT 'task', r.id, r.goal_id, r.title, COALESCE(r.description,'') || ' ' || COALESCE(r.why,'') || ' ' || COALESCE(r.context,'') || ' ' || COALESCE(r.acceptance_criteria,'') || ' ' || COALESCE(r.summary,'') FROM tasks r;
+CREATE TRIGGER IF NOT EXISTS decisions_search_ai AFTER INSERT ON decisions BEGIN
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('decision', new.id, NULL, new.what, COALESCE(new.why,'') || ' ' || COALESCE(new.affects,''));
+END;
+CREATE TRIGGER IF NOT EXISTS decisions_search_au AFTER UPDATE OF what, why, affects ON decisions BEGIN
+  DELETE FROM search_index WHERE kind='decision' AND ref_id=old.id;
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('decision', new.id, NULL, new.what, COALESCE(new.why,'') || ' ' || COALESCE(new.affects,''));
+END;
+CREATE TRIGGER IF NOT EXISTS decisions_search_ad AFTER DELETE ON decisions BEGIN
+  DELETE FROM search_index WHERE kind='decision' AND ref_id=old.id;
+END;
+INSERT INTO search_index (kind, ref_id, goal_id, title, body)
+  SELECT 'decision', r.id, NULL, r.what, COALESCE(r.why,'') || ' ' || COALESCE(r.affects,'') FROM decisions r;
+CREATE TRIGGER IF NOT EXISTS direction_notes_search_ai AFTER INSERT ON direction_notes BEGIN
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('note', new.id, NULL, '', new.content);
+END;
+CREATE TRIGGER IF NOT EXISTS direction_notes_search_au AFTER UPDATE OF content ON direction_notes BEGIN
+  DELETE FROM search_index WHERE kind='note' AND ref_id=old.id;
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('note', new.id, NULL, '', new.content);
+END;
+CREATE TRIGGER IF NOT EXISTS direction_notes_search_ad AFTER DELETE ON direction_notes BEGIN
+  DELETE FROM search_index WHERE kind='note' AND ref_id=old.id;
+END;
+INSERT INTO search_index (kind, ref_id, goal_id, title, body)
+  SELECT 'note', r.id, NULL, '', r.content FROM direction_notes r;
+CREATE TRIGGER IF NOT EXISTS memories_search_ai AFTER INSERT ON memories BEGIN
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('memory', new.id, new.goal_id, new.key, new.value);
+END;
+CREATE TRIGGER IF NOT EXISTS memories_search_au AFTER UPDATE OF key, value, goal_id ON memories BEGIN
+  DELETE FROM search_index WHERE kind='memory' AND ref_id=old.id;
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('memory', new.id, new.goal_id, new.key, new.value);
+END;
+CREATE TRIGGER IF NOT EXISTS memories_search_ad AFTER DELETE ON memories BEGIN
+  DELETE FROM search_index WHERE kind='memory' AND ref_id=old.id;
+END;
+INSERT INTO search_index (kind, ref_id, goal_id, title, body)
+  SELECT 'memory', r.id, r.goal_id, r.key, r.value FROM memories r;
+CREATE TRIGGER IF NOT EXISTS lessons_search_ai AFTER INSERT ON lessons BEGIN
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('lesson', new.id, NULL, new.what_went_wrong, new.correct_behavior);
+END;
+CREATE TRIGGER IF NOT EXISTS lessons_search_au AFTER UPDATE OF what_went_wrong, correct_behavior ON lessons BEGIN
+  DELETE FROM search_index WHERE kind='lesson' AND ref_id=old.id;
+  INSERT INTO search_index (kind, ref_id, goal_id, title, body) VALUES ('lesson', new.id, NULL, new.what_went_wrong, new.correct_behavior);
+END;
+CREATE TRIGGER IF NOT EXISTS lessons_search_ad AFTER DELETE ON lessons BEGIN
+  DELETE FROM search_index WHERE kind='lesson' AND ref_id=old.id;
+END;
+INSERT INTO search_index (kind, ref_id, goal_id, title, body)
+  SELECT 'lesson', r.id, NULL, r.what_went_wrong, r.correct_behavior FROM lessons r;",
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn migrate_decision_links(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS decision_links (
+  id TEXT PRIMARY KEY,
+  decision_id TEXT NOT NULL REFERENCES decisions(id) ON DELETE CASCADE,
+  goal_id TEXT REFERENCES goals(id) ON DELETE CASCADE,
+  task_id TEXT REFERENCES tasks(id) ON DELETE CASCADE,
+  source TEXT NOT NULL DEFAULT 'explicit',
+  created_at INTEGER,
+  CHECK ((goal_id IS NULL) != (task_id IS NULL))
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_decision_links_target
+  ON decision_links(decision_id, COALESCE(goal_id,''), COALESCE(task_id,''));
+CREATE INDEX IF NOT EXISTS idx_decision_links_goal ON decision_links(goal_id);
+CREATE INDEX IF NOT EXISTS idx_decision_links_task ON decision_links(task_id);",
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn migrate_decision_lifecycle(conn: &Connection) -> Result<(), String> {
+    add_column_if_missing(conn, "decisions", "status", "TEXT NOT NULL DEFAULT 'active'")?;
+    add_column_if_missing(conn, "decisions", "superseded_by", "TEXT")?;
+    add_column_if_missing(conn, "decisions", "status_reason", "TEXT")?;
+    add_column_if_missing(conn, "decisions", "revisit_after", "INTEGER")?;
+    add_column_if_missing(conn, "decisions", "updated_at", "INTEGER")?;
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_decisions_status ON decisions(status);")
+        .map_err(|e| e.to_string())
+}
+
+fn migrate_retry_budget(conn: &Connection) -> Result<(), String> {
+    add_column_if_missing(conn, "tasks", "attempts", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "tasks", "max_attempts", "INTEGER")
+}
+
+fn migrate_memory_archive(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS memory_archive (
+  id TEXT PRIMARY KEY,
+  goal_id TEXT REFERENCES goals(id) ON DELETE SET NULL,
+  task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL,
+  key TEXT NOT NULL, value TEXT NOT NULL,
+  type TEXT NOT NULL DEFAULT 'learning', reasoning TEXT,
+  source TEXT NOT NULL DEFAULT 'agent', created_at INTEGER,
+  archived_at INTEGER NOT NULL,
+  reason TEXT NOT NULL,
+  digest_id TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_memory_archive_task ON memory_archive(task_id);
+CREATE INDEX IF NOT EXISTS idx_memory_archive_goal ON memory_archive(goal_id);",
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn migrate_memory_expiry(conn: &Connection) -> Result<(), String> {
+    add_column_if_missing(conn, "memories", "expires_at", "INTEGER")?;
+    add_column_if_missing(conn, "memory_archive", "expires_at", "INTEGER")?;
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_memories_type ON memories(type);")
+        .map_err(|e| e.to_string())
+}
+
+// tasks.max_runtime is in seconds; NULL leaves the limit to `--task-timeout`.
+fn migrate_task_max_runtime(conn: &Connection) -> Result<(), String> {
+    add_column_if_missing(conn, "tasks", "max_runtime", "INTEGER")
+}
+
+// Entries journaled before this have no after-image and undo without a conflict check.
+fn migrate_journal_after(conn: &Connection) -> Result<(), String> {
+    add_column_if_missing(conn, "journal_rows", "after", "TEXT")
+}
+
+// Goals archived before archived_at existed use their last update as the archive time.
+fn migrate_goal_outcomes(conn: &Connection) -> Result<(), String> {
+    add_column_if_missing(conn, "goals", "archived_at", "INTEGER")?;
+    add_column_if_missing(conn, "goals", "outcome", "TEXT")?;
+    add_column_if_missing(conn, "goals", "outcome_evidence", "TEXT")?;
+    add_column_if_missing(conn, "goals", "outcome_reviewed_by", "TEXT")?;
+    add_column_if_missing(conn, "goals", "outcome_reviewed_at", "INTEGER")?;
+    conn.execute(
+        "UPDATE goals SET archived_at = COALESCE(updated_at, created_at) WHERE status='archived' AND archived_at IS NULL",
+        [],
+    )
     .map_err(|e| e.to_string())?;
+    Ok(())
+}
 
-    let has_last_ping: Option<i64> = conn
-        .query_row(
-            "SELECT 1 FROM pragma_table_info('tasks') WHERE name='last_ping_at' LIMIT 1",
-            [],
-            |r| r.get(0),
-        )
-        .optional()
-        .map_err(|e| e.to_string())?;
-    if has_last_ping.is_none() {
-        conn.execute("ALTER TABLE tasks ADD COLUMN last_ping_at INTEGER", [])
-            .map_err(|e| e.to_string())?;
-    }
+fn migrate_acceptance_checks(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS acceptance_checks (
+  id TEXT PRIMARY KEY,
+  task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+  kind TEXT NOT NULL,
+  target TEXT NOT NULL,
+  expected TEXT,
+  last_status TEXT,
+  last_output TEXT,
+  last_run_at INTEGER,
+  created_at INTEGER
+);
+CREATE INDEX IF NOT EXISTS idx_acceptance_checks_task ON acceptance_checks(task_id);",
+    )
+    .map_err(|e| e.to_string())
+}
+
+// tasks.requires_review is NULL when the task just follows its goal's setting.
+fn migrate_review_gate(conn: &Connection) -> Result<(), String> {
+    add_column_if_missing(conn, "goals", "requires_review", "INTEGER DEFAULT 0")?;
+    add_column_if_missing(conn, "tasks", "requires_review", "INTEGER")?;
+    add_column_if_missing(conn, "tasks", "reviewed_by", "TEXT")?;
+    add_column_if_missing(conn, "tasks", "reviewed_at", "INTEGER")
+}
+
+// Local undo state: deliberately not in EXPORT_TABLES, before-images only make
+// sense against the database they were taken from.
+fn migrate_journal(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS journal (
+  id TEXT PRIMARY KEY,
+  command TEXT NOT NULL,
+  args TEXT,
+  summary TEXT,
+  agent_id TEXT,
+  created_at INTEGER,
+  undone_at INTEGER
+);
+CREATE TABLE IF NOT EXISTS journal_rows (
+  journal_id TEXT NOT NULL REFERENCES journal(id) ON DELETE CASCADE,
+  seq INTEGER NOT NULL,
+  table_name TEXT NOT NULL,
+  row_id INTEGER NOT NULL,
+  op TEXT NOT NULL,
+  before TEXT,
+  PRIMARY KEY (journal_id, seq)
+);",
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn migrate_revisions(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS revisions (
+  id TEXT PRIMARY KEY,
+  entity TEXT NOT NULL,
+  entity_id TEXT NOT NULL,
+  field TEXT NOT NULL,
+  old_value TEXT,
+  new_value TEXT,
+  agent_id TEXT,
+  created_at INTEGER
+);
+CREATE INDEX IF NOT EXISTS idx_revisions_entity ON revisions(entity, entity_id);",
+    )
+    .map_err(|e| e.to_string())
+}
 
-    Ok(())
+fn migrate_lock_leases(conn: &Connection) -> Result<(), String> {
+    add_column_if_missing(conn, "tasks", "lease_seconds", "INTEGER")?;
+    add_column_if_missing(conn, "tasks", "lease_expires_at", "INTEGER")
 }
 
 fn register_workspace(conn: &Connection, cwd: &Path) -> Result<(), String> {
@@ -3818,14 +9300,17 @@ fn query_direction(
     }
 }
 
-fn query_decisions(conn: &Connection, limit: i64) -> Result<Vec<(String, String, String, i64)>, String> {
+/// Active decisions, newest first, as (what, why, affects, created_at, revisit_after).
+#[allow(clippy::type_complexity)]
+fn query_decisions(conn: &Connection, limit: i64) -> Result<Vec<(String, String, String, i64, Option<i64>)>, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT what, why, COALESCE(affects,''), COALESCE(created_at,0) FROM decisions ORDER BY COALESCE(created_at,0) DESC LIMIT ?1",
+            "SELECT what, why, COALESCE(affects,''), COALESCE(created_at,0), revisit_after FROM decisions
+             WHERE status='active' ORDER BY COALESCE(created_at,0) DESC LIMIT ?1",
         )
         .map_err(|e| e.to_string())?;
     let mapped = stmt
-        .query_map(params![limit], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+        .query_map(params![limit], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)))
         .map_err(|e| e.to_string())?;
     let rows = mapped
         .collect::<Result<Vec<_>, _>>()
@@ -3833,6 +9318,120 @@ fn query_decisions(conn: &Connection, limit: i64) -> Result<Vec<(String, String,
     Ok(rows)
 }
 
+fn decision_to_value(d: &(String, String, String, i64, Option<i64>)) -> Value {
+    json!({
+        "what": d.0,
+        "why": d.1,
+        "affects": d.2,
+        "created_at": d.3,
+        "revisit_after": d.4,
+        "revisit_due": revisit_due("active", d.4)
+    })
+}
+
+fn revisit_due(status: &str, revisit_after: Option<i64>) -> bool {
+    status == "active" && revisit_after.is_some_and(|ts| ts <= now_ts())
+}
+
+fn revisit_label(status: &str, revisit_after: Option<i64>) -> String {
+    match revisit_after {
+        Some(ts) if revisit_due(status, revisit_after) => format!("  ⏰ revisit due (since {})", format_date(ts)),
+        Some(ts) if status == "active" => format!("  revisit after {}", format_date(ts)),
+        _ => String::new(),
+    }
+}
+
+/// Decisions that govern a goal and/or task: explicit `decision_links` first, then
+/// — only for decisions nobody has linked yet — the old substring match of the goal
+/// name against `affects`. With no task, links on any of the goal's tasks count too.
+/// Rows are (what, why, affects, created_at, linked).
+#[allow(clippy::type_complexity)]
+fn query_linked_decisions(
+    conn: &Connection,
+    goal_id: Option<&str>,
+    task_id: Option<&str>,
+    limit: i64,
+) -> Result<Vec<(String, String, String, i64, bool)>, String> {
+    let goal_name: String = match goal_id {
+        Some(gid) => conn
+            .query_row("SELECT COALESCE(name,'') FROM goals WHERE id=?1", params![gid], |r| r.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default(),
+        None => String::new(),
+    };
+    let mut stmt = conn
+        .prepare(
+            "SELECT what, why, COALESCE(affects,''), COALESCE(created_at,0)
+             FROM decisions d
+             WHERE d.status = 'active' AND EXISTS (
+               SELECT 1 FROM decision_links l
+               WHERE l.decision_id = d.id
+                 AND (l.goal_id = ?1 OR l.task_id = ?2
+                      OR (?2 IS NULL AND l.task_id IN (SELECT id FROM tasks WHERE goal_id = ?1)))
+             )
+             ORDER BY 4 DESC
+             LIMIT ?3",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut rows: Vec<(String, String, String, i64, bool)> = stmt
+        .query_map(params![goal_id, task_id, limit], |r| {
+            Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, true))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if goal_name.trim().is_empty() || rows.len() as i64 >= limit {
+        return Ok(rows);
+    }
+
+    // Unlinked decisions fall back to their free-text `affects`, matched on whole
+    // words like `imi decision backfill`; LIKE only narrows the scan.
+    let mut stmt = conn
+        .prepare(
+            "SELECT what, why, COALESCE(affects,''), COALESCE(created_at,0)
+             FROM decisions d
+             WHERE d.status = 'active'
+               AND NOT EXISTS (SELECT 1 FROM decision_links l WHERE l.decision_id = d.id)
+               AND LOWER(COALESCE(affects,'')) LIKE '%' || LOWER(?1) || '%'
+             ORDER BY 4 DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let unlinked = stmt
+        .query_map(params![goal_name.trim()], |r| {
+            Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?, r.get::<_, String>(2)?, r.get::<_, i64>(3)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    rows.extend(
+        unlinked
+            .into_iter()
+            .filter(|d| mentions_phrase(&d.2, &goal_name))
+            .map(|(what, why, affects, at)| (what, why, affects, at, false))
+            .take((limit as usize).saturating_sub(rows.len())),
+    );
+    Ok(rows)
+}
+
+/// Returns false when the link already existed.
+fn link_decision(
+    conn: &Connection,
+    decision_id: &str,
+    goal_id: Option<&str>,
+    task_id: Option<&str>,
+    source: &str,
+) -> Result<bool, String> {
+    let n = conn
+        .execute(
+            "INSERT OR IGNORE INTO decision_links (id, decision_id, goal_id, task_id, source, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![gen_id(), decision_id, goal_id, task_id, source, now_ts()],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(n > 0)
+}
+
 fn query_active_goals(conn: &Connection, limit: i64) -> Result<Vec<GoalRow>, String> {
     let mut stmt = conn
         .prepare(
@@ -3914,18 +9513,26 @@ struct TaskRowWithGoal {
     goal_name: Option<String>,
 }
 
+// Memories past their --ttl drop out of every context view; `imi memory list --expired` still shows them.
+const MEMORY_LIVE_SQL: &str = "(expires_at IS NULL OR expires_at > CAST(strftime('%s','now') AS INTEGER))";
+
 fn query_memories(conn: &Connection, goal_id: Option<&str>, limit: i64) -> Result<Vec<MemoryRow>, String> {
     let sql = if goal_id.is_some() {
-        "SELECT id, goal_id, task_id, key, value, COALESCE(type,'learning'), COALESCE(source,'agent'), COALESCE(created_at,0)
-         FROM memories
-         WHERE goal_id=?1 OR task_id IN (SELECT id FROM tasks WHERE goal_id=?1)
-         ORDER BY COALESCE(created_at,0) DESC LIMIT ?2"
+        format!(
+            "SELECT id, goal_id, task_id, key, value, COALESCE(type,'learning'), COALESCE(source,'agent'), COALESCE(created_at,0), expires_at
+             FROM memories
+             WHERE (goal_id=?1 OR task_id IN (SELECT id FROM tasks WHERE goal_id=?1)) AND {MEMORY_LIVE_SQL}
+             ORDER BY COALESCE(created_at,0) DESC LIMIT ?2"
+        )
     } else {
-        "SELECT id, goal_id, task_id, key, value, COALESCE(type,'learning'), COALESCE(source,'agent'), COALESCE(created_at,0)
-         FROM memories
-         ORDER BY COALESCE(created_at,0) DESC LIMIT ?1"
+        format!(
+            "SELECT id, goal_id, task_id, key, value, COALESCE(type,'learning'), COALESCE(source,'agent'), COALESCE(created_at,0), expires_at
+             FROM memories
+             WHERE {MEMORY_LIVE_SQL}
+             ORDER BY COALESCE(created_at,0) DESC LIMIT ?1"
+        )
     };
-    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
     if let Some(gid) = goal_id {
         let mapped = stmt.query_map(params![gid, limit], |r| {
             Ok(MemoryRow {
@@ -3937,6 +9544,7 @@ fn query_memories(conn: &Connection, goal_id: Option<&str>, limit: i64) -> Resul
                 typ: r.get(5)?,
                 source: r.get(6)?,
                 created_at: r.get(7)?,
+                expires_at: r.get(8)?,
             })
         })
         .map_err(|e| e.to_string())?;
@@ -3955,6 +9563,7 @@ fn query_memories(conn: &Connection, goal_id: Option<&str>, limit: i64) -> Resul
                 typ: r.get(5)?,
                 source: r.get(6)?,
                 created_at: r.get(7)?,
+                expires_at: r.get(8)?,
             })
         })
         .map_err(|e| e.to_string())?;
@@ -3995,19 +9604,20 @@ fn query_lessons(conn: &Connection, limit: i64) -> Result<Vec<LessonRow>, String
 // the agent's view of what's actively being worked on.
 fn query_active_memories(conn: &Connection, limit: i64) -> Result<Vec<MemoryRow>, String> {
     let mut stmt = conn
-        .prepare(
+        .prepare(&format!(
             "SELECT m.id, m.goal_id, m.task_id, m.key, m.value,
-                    COALESCE(m.type,'learning'), COALESCE(m.source,'agent'), COALESCE(m.created_at,0)
+                    COALESCE(m.type,'learning'), COALESCE(m.source,'agent'), COALESCE(m.created_at,0), m.expires_at
              FROM memories m
-             WHERE m.goal_id IS NULL
-                OR m.goal_id IN (SELECT id FROM goals WHERE status != 'done' AND status != 'archived')
+             WHERE (m.goal_id IS NULL
+                OR m.goal_id IN (SELECT id FROM goals WHERE status != 'done' AND status != 'archived'))
+               AND {MEMORY_LIVE_SQL}
              ORDER BY
                 CASE WHEN m.goal_id IN (
                     SELECT DISTINCT goal_id FROM tasks WHERE status='in_progress' AND goal_id IS NOT NULL
                 ) THEN 1 ELSE 0 END DESC,
                 COALESCE(m.created_at,0) DESC
-             LIMIT ?1",
-        )
+             LIMIT ?1"
+        ))
         .map_err(|e| e.to_string())?;
     let mapped = stmt
         .query_map(params![limit], |r| {
@@ -4020,6 +9630,7 @@ fn query_active_memories(conn: &Connection, limit: i64) -> Result<Vec<MemoryRow>
                 typ: r.get(5)?,
                 source: r.get(6)?,
                 created_at: r.get(7)?,
+                expires_at: r.get(8)?,
             })
         })
         .map_err(|e| e.to_string())?;
@@ -4053,28 +9664,229 @@ fn resolve_task(conn: &Connection, prefix: &str) -> Result<TaskRow, String> {
     .map_err(|e| e.to_string())
 }
 
+/// Type tags on new IDs (`t_3kf…`), so a prefix can never match across tables.
+/// Rows created before tagging keep their bare IDs and still resolve.
+const ID_TAGS: &[(&str, &str, &str)] = &[
+    ("goals", "g", "name"),
+    ("tasks", "t", "title"),
+    ("decisions", "d", "what"),
+    ("lessons", "l", "what_went_wrong"),
+    ("memories", "m", "key"),
+];
+
+/// Characters after the tag that `imi status`-style listings always show, even
+/// when fewer would already be unique.
+const MIN_ID_PREFIX: usize = 4;
+
+fn id_tag(table: &str) -> Option<(&'static str, &'static str)> {
+    ID_TAGS.iter().find(|(t, _, _)| *t == table).map(|(_, tag, label)| (*tag, *label))
+}
+
+/// Resolve a full ID or a unique prefix of one. The tag may be left off
+/// (`imi complete 3kf` finds `t_3kf…`). Errors with every candidate when the
+/// prefix is ambiguous instead of picking whichever row sorts first.
 fn resolve_id_prefix(conn: &Connection, table: &str, prefix: &str) -> Result<Option<String>, String> {
-    let sql = format!(
-        "SELECT id FROM {table} WHERE id = ?1 OR id LIKE ?2 ORDER BY CASE WHEN id=?1 THEN 0 ELSE 1 END LIMIT 1"
-    );
-    let pattern = format!("{prefix}%");
-    conn.query_row(&sql, params![prefix, pattern], |r| r.get(0))
+    let prefix = prefix.trim();
+    if prefix.is_empty() {
+        return Ok(None);
+    }
+    let exact: Option<String> = conn
+        .query_row(&format!("SELECT id FROM {table} WHERE id=?1"), params![prefix], |r| r.get(0))
         .optional()
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    if exact.is_some() {
+        return Ok(exact);
+    }
+    let (tag, label) = id_tag(table).unwrap_or(("", "id"));
+    if let Some((other, _)) = prefix.split_once('_') {
+        if other != tag && ID_TAGS.iter().any(|(_, t, _)| *t == other) {
+            return Ok(None);
+        }
+    }
+    let tagged = format!("{tag}_{prefix}");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, COALESCE({label},'') FROM {table}
+             WHERE substr(id, 1, length(?1)) = ?1 OR (?3 != '' AND substr(id, 1, length(?2)) = ?2)
+             ORDER BY id LIMIT 11"
+        ))
+        .map_err(|e| e.to_string())?;
+    let candidates = stmt
+        .query_map(params![prefix, tagged, tag], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    match candidates.len() {
+        0 => Ok(None),
+        1 => Ok(candidates.into_iter().next().map(|c| c.0)),
+        n => {
+            let kind = table.trim_end_matches('s');
+            let more = if n > 10 { "\n  …and more" } else { "" };
+            let list = candidates
+                .iter()
+                .take(10)
+                .map(|(id, label)| format!("  {id}  {label}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            Err(format!("ambiguous {kind} ID prefix '{prefix}' matches:\n{list}{more}\nUse a longer prefix."))
+        }
+    }
 }
 
-fn release_stale_locks(conn: &Connection) -> Result<usize, String> {
-    let now = now_ts();
+/// Shortest prefix of each ID in `table` that `resolve_id_prefix` maps back to
+/// it uniquely, never shorter than `MIN_ID_PREFIX` characters after the tag.
+fn unique_id_prefixes(conn: &Connection, table: &str) -> Result<BTreeMap<String, String>, String> {
+    let mut stmt = conn.prepare(&format!("SELECT id FROM {table}")).map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([], |r| r.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let tag = id_tag(table).map(|(t, _)| format!("{t}_")).unwrap_or_default();
+    // A bare prefix also matches tagged IDs by the part after the tag, so those
+    // tails compete with untagged IDs for uniqueness.
+    let mut keys: Vec<(&str, usize)> = Vec::with_capacity(ids.len() * 2);
+    for (i, id) in ids.iter().enumerate() {
+        keys.push((id.as_str(), i));
+        if let Some(tail) = id.strip_prefix(tag.as_str()).filter(|_| !tag.is_empty()) {
+            keys.push((tail, i));
+        }
+    }
+    keys.sort();
+    let common = |a: &str, b: &str| a.bytes().zip(b.bytes()).take_while(|(x, y)| x == y).count();
+    let mut prefixes = BTreeMap::new();
+    for (pos, (key, owner)) in keys.iter().enumerate() {
+        let id = &ids[*owner];
+        if *key != id.as_str() {
+            continue;
+        }
+        let before = keys[..pos].iter().rev().find(|k| k.1 != *owner).map_or(0, |k| common(key, k.0));
+        let after = keys[pos + 1..].iter().find(|k| k.1 != *owner).map_or(0, |k| common(key, k.0));
+        let floor = if id.starts_with(tag.as_str()) && !tag.is_empty() { tag.len() + MIN_ID_PREFIX } else { MIN_ID_PREFIX };
+        let len = (before.max(after) + 1).max(floor).min(id.len());
+        prefixes.insert(id.clone(), id[..len].to_string());
+    }
+    Ok(prefixes)
+}
+
+/// Record that `task_id` cannot start until `depends_on` is done. Rejects
+/// self-dependencies and any edge that would close a cycle.
+fn add_task_dependency(conn: &Connection, task_id: &str, depends_on: &str) -> Result<(), String> {
+    if task_id == depends_on {
+        return Err("a task cannot depend on itself".to_string());
+    }
+    // Walk everything `depends_on` already (transitively) waits on; if that
+    // includes `task_id`, the new edge would close a loop.
+    let creates_cycle: Option<i64> = conn
+        .query_row(
+            "WITH RECURSIVE upstream(id) AS (
+                SELECT depends_on FROM task_dependencies WHERE task_id=?1
+                UNION
+                SELECT d.depends_on FROM task_dependencies d JOIN upstream u ON d.task_id=u.id
+             )
+             SELECT 1 FROM upstream WHERE id=?2 LIMIT 1",
+            params![depends_on, task_id],
+            |r| r.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if creates_cycle.is_some() {
+        return Err(format!(
+            "dependency cycle: {depends_on} already depends on {task_id} (directly or transitively)"
+        ));
+    }
     conn.execute(
-        "UPDATE tasks
-         SET status='todo', agent_id=NULL, updated_at=?1
-         WHERE status='in_progress' AND COALESCE(last_ping_at, updated_at, created_at, 0) < ?2",
-        params![now, now - 1800],
+        "INSERT OR IGNORE INTO task_dependencies (task_id, depends_on, created_at) VALUES (?1, ?2, ?3)",
+        params![task_id, depends_on, now_ts()],
     )
-    .map_err(|e| e.to_string())
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Prerequisites of `task_id` that are not done yet, as (id, title, status).
+fn query_task_blockers(conn: &Connection, task_id: &str) -> Result<Vec<(String, String, String)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.id, p.title, COALESCE(p.status,'todo')
+             FROM task_dependencies d
+             JOIN tasks p ON p.id = d.depends_on
+             WHERE d.task_id=?1 AND COALESCE(p.status,'todo') != 'done'
+             ORDER BY COALESCE(p.created_at,0) ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let mapped = stmt
+        .query_map(params![task_id], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+        .map_err(|e| e.to_string())?;
+    mapped
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+fn blockers_to_value(blockers: &[(String, String, String)]) -> Value {
+    json!(blockers
+        .iter()
+        .map(|b| json!({"id": b.0, "title": b.1, "status": b.2}))
+        .collect::<Vec<_>>())
+}
+
+fn blockers_label(task_ids: &BTreeMap<String, String>, blockers: &[(String, String, String)]) -> String {
+    blockers
+        .iter()
+        .map(|b| format!("{} ({})", b.1, short_id(task_ids, &b.0)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Tasks claimed before leases existed have no lease_expires_at; they fall back to
+// the last heartbeat plus the default lease.
+const LEASE_EXPIRY_SQL: &str = "COALESCE(lease_expires_at, COALESCE(last_ping_at, updated_at, created_at, 0) + ?2)";
+
+fn release_stale_locks(conn: &Connection, cfg: &Config) -> Result<usize, String> {
+    let now = now_ts();
+    let default_lease = cfg.lease_secs();
+    let stale: Vec<(String, Option<String>, Option<String>, i64)> = {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT id, goal_id, agent_id, {LEASE_EXPIRY_SQL} FROM tasks
+                 WHERE status='in_progress' AND {LEASE_EXPIRY_SQL} < ?1"
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![now, default_lease], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+    let mut released = 0;
+    for (task_id, goal_id, agent_id, expired_at) in &stale {
+        let n = conn
+            .execute(
+                "UPDATE tasks SET status='todo', agent_id=NULL, lease_expires_at=NULL, updated_at=?1
+                 WHERE id=?2 AND status='in_progress'",
+                params![now, task_id],
+            )
+            .map_err(|e| e.to_string())?;
+        if n == 0 {
+            continue;
+        }
+        released += n;
+        let owner = agent_id.as_deref().filter(|a| !a.is_empty()).unwrap_or("unknown agent");
+        let note = format!(
+            "Lock held by {owner} expired {} ago without a ping; task returned to todo",
+            format_duration((now - expired_at).max(1))
+        );
+        conn.execute(
+            "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
+             VALUES (?1, ?2, ?3, 'lock_expired', ?4, 'lifecycle', ?4, 'imi', ?5)",
+            params![gen_typed_id("m"), goal_id, task_id, note, now],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(released)
 }
 
-fn claim_next_task(conn: &mut Connection, goal_id: Option<&str>, agent: &str) -> Result<ClaimResult, String> {
+fn claim_next_task(conn: &mut Connection, goal_id: Option<&str>, agent: &str, lease_secs: i64) -> Result<ClaimResult, String> {
     let now = now_ts();
     let tx = conn
         .transaction_with_behavior(TransactionBehavior::Immediate)
@@ -4088,6 +9900,10 @@ fn claim_next_task(conn: &mut Connection, goal_id: Option<&str>, agent: &str) ->
              WHERE status='todo'
                AND goal_id=?1
                AND goal_id IN (SELECT id FROM goals WHERE status!='archived')
+               AND NOT EXISTS (
+                   SELECT 1 FROM task_dependencies d JOIN tasks p ON p.id = d.depends_on
+                   WHERE d.task_id = tasks.id AND COALESCE(p.status,'todo') != 'done'
+               )
              ORDER BY CASE priority
                 WHEN 'critical' THEN 4
                 WHEN 'high' THEN 3
@@ -4121,6 +9937,10 @@ fn claim_next_task(conn: &mut Connection, goal_id: Option<&str>, agent: &str) ->
              FROM tasks
              WHERE status='todo'
                AND (goal_id IS NULL OR goal_id IN (SELECT id FROM goals WHERE status!='archived'))
+               AND NOT EXISTS (
+                   SELECT 1 FROM task_dependencies d JOIN tasks p ON p.id = d.depends_on
+                   WHERE d.task_id = tasks.id AND COALESCE(p.status,'todo') != 'done'
+               )
              ORDER BY CASE priority
                 WHEN 'critical' THEN 4
                 WHEN 'high' THEN 3
@@ -4156,8 +9976,10 @@ fn claim_next_task(conn: &mut Connection, goal_id: Option<&str>, agent: &str) ->
 
     let updated = tx
         .execute(
-            "UPDATE tasks SET status='in_progress', agent_id=?1, updated_at=?2, last_ping_at=?2 WHERE id=?3 AND status='todo'",
-            params![agent, now, candidate.id],
+            "UPDATE tasks SET status='in_progress', agent_id=?1, updated_at=?2, last_ping_at=?2,
+                    lease_seconds=?4, lease_expires_at=?2 + ?4
+             WHERE id=?3 AND status='todo'",
+            params![agent, now, candidate.id, lease_secs],
         )
         .map_err(|e| e.to_string())?;
 
@@ -4186,7 +10008,7 @@ fn claim_next_task(conn: &mut Connection, goal_id: Option<&str>, agent: &str) ->
                 "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
                  VALUES (?1, ?2, ?3, 'task_claimed', ?4, 'lifecycle', ?4, ?5, ?6)",
                 params![
-                    gen_id(),
+                    gen_typed_id("m"),
                     candidate.goal_id.clone(),
                     candidate.id.clone(),
                     note,
@@ -4264,6 +10086,61 @@ fn now_ts() -> i64 {
         .unwrap_or(0)
 }
 
+// Civil-date conversions (Howard Hinnant's algorithm) so `--revisit-after` can take
+// a plain YYYY-MM-DD without pulling in a date crate.
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn format_date(ts: i64) -> String {
+    let z = ts.div_euclid(86400) + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+fn format_timestamp(ts: i64) -> String {
+    let secs = ts.rem_euclid(86400);
+    format!("{} {:02}:{:02}:{:02}", format_date(ts), secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+/// Accepts YYYY-MM-DD (UTC midnight) or a relative `30d` / `6w` from now.
+fn parse_date_arg(s: &str) -> Result<i64, String> {
+    let s = s.trim();
+    let bad = || format!("invalid date '{s}' (use YYYY-MM-DD, or e.g. 30d / 6w)");
+    if let Some(n) = s.strip_suffix('d').and_then(|n| n.parse::<i64>().ok()) {
+        return Ok(now_ts() + n * 86400);
+    }
+    if let Some(n) = s.strip_suffix('w').and_then(|n| n.parse::<i64>().ok()) {
+        return Ok(now_ts() + n * 7 * 86400);
+    }
+    let parts: Vec<i64> = s.split('-').map(|p| p.parse::<i64>()).collect::<Result<_, _>>().map_err(|_| bad())?;
+    match parts[..] {
+        [y, m, d] if (1..=12).contains(&m) && (1..=days_in_month(y, m)).contains(&d) => Ok(days_from_civil(y, m, d) * 86400),
+        _ => Err(bad()),
+    }
+}
+
+fn days_in_month(y: i64, m: i64) -> i64 {
+    match m {
+        2 if (y % 4 == 0 && y % 100 != 0) || y % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
 fn ago(ts: i64) -> String {
     let diff = (now_ts() - ts).max(0);
     if diff < 60 {
@@ -4330,6 +10207,20 @@ fn rand_u8() -> u8 {
     (nanos & 0xff) as u8
 }
 
+fn short_id<'a>(prefixes: &'a BTreeMap<String, String>, id: &'a str) -> &'a str {
+    prefixes.get(id).map_or(id, String::as_str)
+}
+
+/// `short_id` for a single ID in a one-off message.
+fn display_id(conn: &Connection, table: &str, id: &str) -> Result<String, String> {
+    let prefixes = unique_id_prefixes(conn, table)?;
+    Ok(short_id(&prefixes, id).to_string())
+}
+
+fn gen_typed_id(tag: &str) -> String {
+    format!("{tag}_{}", gen_id())
+}
+
 fn gen_id() -> String {
     let ts_ms = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -4379,7 +10270,8 @@ fn memory_to_value(m: &MemoryRow) -> Value {
         "value": m.value,
         "type": m.typ,
         "source": m.source,
-        "created_at": m.created_at
+        "created_at": m.created_at,
+        "expires_at": m.expires_at
     })
 }
 
@@ -4457,17 +10349,28 @@ fn instructions_windsurf() -> &'static str {
     "# IMI Ops for Windsurf\n\nBoot:\nimi status\nimi context\n\nExecution loop:\nimi next\nimi start <task_id>\nimi complete <task_id> \"summary\"\nimi memory add <goal_id> <key> \"insight\""
 }
 
-fn cmd_check(conn: &Connection, out: OutputCtx, task_id: Option<String>) -> Result<(), String> {
+fn cmd_check(conn: &Connection, out: OutputCtx, cfg: &Config, task_id: Option<String>) -> Result<(), String> {
     if let Some(task_id) = task_id {
-        return cmd_verify(conn, out, task_id);
+        return cmd_verify(conn, out, cfg, task_id, false, Vec::new(), false);
     }
     cmd_audit(conn, out)
 }
 
-fn cmd_verify(conn: &Connection, out: OutputCtx, task_prefix: String) -> Result<(), String> {
+fn cmd_verify(conn: &Connection, out: OutputCtx, cfg: &Config, task_prefix: String, run: bool, add: Vec<String>, clear: bool) -> Result<(), String> {
     let task_id = resolve_id_prefix(conn, "tasks", &task_prefix)?
         .ok_or_else(|| format!("No task with ID '{task_prefix}' — run `imi tasks` to list available tasks"))?;
 
+    if clear {
+        conn.execute("DELETE FROM acceptance_checks WHERE task_id=?1", params![task_id])
+            .map_err(|e| e.to_string())?;
+    }
+    for spec in &add {
+        let (kind, target, expected) = parse_check_spec(spec)?;
+        add_acceptance_check(conn, &task_id, &kind, &target, &expected)?;
+    }
+    let results = if run { Some(run_acceptance_checks(conn, cfg, &task_id, &current_agent(None))?) } else { None };
+    let checks = query_acceptance_checks(conn, &task_id)?;
+
     let (title, status, description, acceptance_criteria, relevant_files, why): (String, String, String, Option<String>, String, String) = conn
         .query_row(
             "SELECT title, status, description, acceptance_criteria, relevant_files, why FROM tasks WHERE id=?1",
@@ -4526,11 +10429,13 @@ fn cmd_verify(conn: &Connection, out: OutputCtx, task_prefix: String) -> Result<
             "description": description,
             "relevant_files": relevant_files,
             "why": why,
+            "checks": checks.iter().map(acceptance_check_to_value).collect::<Vec<_>>(),
+            "checks_passed": results.as_ref().map(|r| r.iter().all(|x| x.1)),
         }));
         return Ok(());
     }
 
-    println!("Verify: {} [{}]", title, task_id);
+    println!("Verify: {} [{}]", title, display_id(conn, "tasks", &task_id)?);
     println!("Status: {}", status);
     println!();
 
@@ -4581,8 +10486,36 @@ fn cmd_verify(conn: &Connection, out: OutputCtx, task_prefix: String) -> Result<
         }
     }
 
+    if !checks.is_empty() {
+        println!();
+        println!("Acceptance checks:");
+        for c in &checks {
+            let mark = match c.last_status.as_deref() {
+                Some("pass") => "✓",
+                Some("fail") => "✗",
+                _ => "·",
+            };
+            let when = c.last_run_at.map(|t| format!("  (last run {})", ago(t))).unwrap_or_default();
+            println!("  {mark} {}{when}", describe_check(&c.kind, &c.target, &c.expected));
+            if c.last_status.as_deref() == Some("fail") && !c.last_output.is_empty() {
+                for line in c.last_output.lines().take(5) {
+                    println!("      {line}");
+                }
+            }
+        }
+    }
+
     println!();
-    if unverified {
+    if let Some(results) = &results {
+        let passed = results.iter().filter(|r| r.1).count();
+        if results.is_empty() {
+            println!("No acceptance checks to run — add some with `imi verify {task_id} --add run:<cmd>`.");
+        } else if passed == results.len() {
+            println!("PASSED — {passed}/{} acceptance checks passed.", results.len());
+        } else {
+            println!("FAILED — {passed}/{} acceptance checks passed.", results.len());
+        }
+    } else if unverified {
         println!("UNVERIFIED — agent should check if this work actually exists in the codebase.");
     } else {
         println!("Verifiable — criteria and summary present. Agent should confirm criteria is met.");
@@ -4591,6 +10524,261 @@ fn cmd_verify(conn: &Connection, out: OutputCtx, task_prefix: String) -> Result<
     Ok(())
 }
 
+struct AcceptanceCheck {
+    id: String,
+    kind: String,
+    target: String,
+    expected: String,
+    last_status: Option<String>,
+    last_output: String,
+    last_run_at: Option<i64>,
+}
+
+/// Longest output excerpt kept per check; the tail is what usually explains a failure.
+const CHECK_EXCERPT_CHARS: usize = 600;
+
+/// Parse `run:<cmd>`, `run=<exit>:<cmd>`, `exists:<path>`, `contains:<path>:<text>`
+/// or `grep:<path>:<regex>` into (kind, target, expected).
+fn parse_check_spec(spec: &str) -> Result<(String, String, String), String> {
+    let usage = "checks look like run:<cmd>, run=<exit>:<cmd>, exists:<path>, contains:<path>:<text>, grep:<path>:<regex>";
+    let (head, rest) = spec.split_once(':').ok_or_else(|| format!("invalid check '{spec}': {usage}"))?;
+    let rest = rest.trim();
+    if rest.is_empty() {
+        return Err(format!("invalid check '{spec}': {usage}"));
+    }
+    let split_path = |what: &str| -> Result<(String, String, String), String> {
+        let (path, arg) = rest
+            .split_once(':')
+            .filter(|(p, a)| !p.trim().is_empty() && !a.is_empty())
+            .ok_or_else(|| format!("invalid check '{spec}': {what} needs <path>:<{}>", if what == "grep" { "regex" } else { "text" }))?;
+        Ok((what.to_string(), path.trim().to_string(), arg.to_string()))
+    };
+    match head.trim() {
+        "run" => Ok(("command".to_string(), rest.to_string(), "0".to_string())),
+        h if h.starts_with("run=") => {
+            let code: i32 = h[4..].trim().parse().map_err(|_| format!("invalid exit code in check '{spec}'"))?;
+            Ok(("command".to_string(), rest.to_string(), code.to_string()))
+        }
+        "exists" => Ok(("file_exists".to_string(), rest.to_string(), String::new())),
+        "contains" => split_path("contains").map(|(_, p, a)| ("file_contains".to_string(), p, a)),
+        "grep" => split_path("grep"),
+        _ => Err(format!("unknown check kind '{head}': {usage}")),
+    }
+}
+
+fn describe_check(kind: &str, target: &str, expected: &str) -> String {
+    match kind {
+        "command" if expected == "0" => format!("run: {target}"),
+        "command" => format!("run: {target} (expect exit {expected})"),
+        "file_exists" => format!("exists: {target}"),
+        "file_contains" => format!("contains: {target} has \"{expected}\""),
+        "grep" => format!("grep: /{expected}/ in {target}"),
+        _ => format!("{kind}: {target}"),
+    }
+}
+
+fn add_acceptance_check(conn: &Connection, task_id: &str, kind: &str, target: &str, expected: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO acceptance_checks (id, task_id, kind, target, expected, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![gen_id(), task_id, kind, target, expected, now_ts()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn query_acceptance_checks(conn: &Connection, task_id: &str) -> Result<Vec<AcceptanceCheck>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, kind, target, COALESCE(expected,''), last_status, COALESCE(last_output,''), last_run_at
+             FROM acceptance_checks WHERE task_id=?1 ORDER BY COALESCE(created_at,0), rowid",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![task_id], |r| {
+            Ok(AcceptanceCheck {
+                id: r.get(0)?,
+                kind: r.get(1)?,
+                target: r.get(2)?,
+                expected: r.get(3)?,
+                last_status: r.get(4)?,
+                last_output: r.get(5)?,
+                last_run_at: r.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn acceptance_check_to_value(c: &AcceptanceCheck) -> Value {
+    json!({
+        "id": c.id,
+        "kind": c.kind,
+        "target": c.target,
+        "expected": c.expected,
+        "description": describe_check(&c.kind, &c.target, &c.expected),
+        "last_status": c.last_status,
+        "last_output": c.last_output,
+        "last_run_at": c.last_run_at,
+    })
+}
+
+fn output_excerpt(text: &str) -> String {
+    let text = text.trim();
+    let count = text.chars().count();
+    if count <= CHECK_EXCERPT_CHARS {
+        return text.to_string();
+    }
+    let tail: String = text.chars().skip(count - CHECK_EXCERPT_CHARS).collect();
+    format!("…{tail}")
+}
+
+/// Run a command check in its own process group, killing the group if it outlives
+/// `timeout_secs`. Returns the exit status, combined output and whether it timed out.
+fn run_check_command(command: &str, dir: &Path, timeout_secs: i64) -> Result<(std::process::ExitStatus, String, bool), String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .process_group(0)
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    // Drain both pipes while waiting so a chatty check can't block on a full pipe.
+    fn drain(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    let (status, timed_out) = wait_for_worker(&mut child, &Shutdown::default(), 0, Some(timeout_secs))?;
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&stdout.join().unwrap_or_default()),
+        String::from_utf8_lossy(&stderr.join().unwrap_or_default())
+    );
+    Ok((status, text, timed_out))
+}
+
+/// Run one check from `dir`, returning whether it passed and an output excerpt.
+fn run_acceptance_check(check: &AcceptanceCheck, dir: &Path, timeout_secs: i64) -> (bool, String) {
+    match check.kind.as_str() {
+        "command" => match run_check_command(&check.target, dir, timeout_secs) {
+            Ok((_, text, true)) => {
+                let excerpt = output_excerpt(&text);
+                let note = format!("timed out after {} and was killed", format_duration(timeout_secs));
+                (false, if excerpt.is_empty() { note } else { format!("{note}\n{excerpt}") })
+            }
+            Ok((status, text, false)) => {
+                let code = status.code().unwrap_or(-1);
+                let passed = code.to_string() == check.expected;
+                let excerpt = output_excerpt(&text);
+                let note = if passed { format!("exit {code}") } else { format!("exit {code}, expected {}", check.expected) };
+                (passed, if excerpt.is_empty() { note } else { format!("{note}\n{excerpt}") })
+            }
+            Err(e) => (false, format!("could not run: {e}")),
+        },
+        "file_exists" => {
+            let passed = dir.join(&check.target).exists();
+            (passed, if passed { "found".to_string() } else { "missing".to_string() })
+        }
+        "file_contains" => match fs::read_to_string(dir.join(&check.target)) {
+            Ok(body) if body.contains(&check.expected) => (true, "found".to_string()),
+            Ok(_) => (false, "text not found".to_string()),
+            Err(e) => (false, format!("cannot read {}: {e}", check.target)),
+        },
+        "grep" => match Command::new("grep")
+            .args(["-rnE", "-m", "3", "--", &check.expected, &check.target])
+            .current_dir(dir)
+            .output()
+        {
+            Ok(output) => {
+                let matches = String::from_utf8_lossy(&output.stdout).to_string();
+                match output.status.code() {
+                    Some(0) => (true, output_excerpt(&matches)),
+                    Some(1) => (false, "no match".to_string()),
+                    _ => (false, output_excerpt(&String::from_utf8_lossy(&output.stderr))),
+                }
+            }
+            Err(e) => (false, format!("could not run grep: {e}")),
+        },
+        other => (false, format!("unknown check kind: {other}")),
+    }
+}
+
+fn check_result_line(result: &(AcceptanceCheck, bool, String)) -> String {
+    let (check, passed, excerpt) = result;
+    let mark = if *passed { "✓" } else { "✗" };
+    let first = excerpt.lines().next().unwrap_or("");
+    format!("  {mark} {} — {first}", describe_check(&check.kind, &check.target, &check.expected))
+}
+
+/// Run every check for a task in its workspace, record each result on the
+/// check row and save the run as one memory (type failure if anything failed,
+/// so the next claimer sees it as last_failure).
+fn run_acceptance_checks(conn: &Connection, cfg: &Config, task_id: &str, agent: &str) -> Result<Vec<(AcceptanceCheck, bool, String)>, String> {
+    let checks = query_acceptance_checks(conn, task_id)?;
+    if checks.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (goal_id, workspace): (Option<String>, String) = conn
+        .query_row(
+            "SELECT goal_id, COALESCE(workspace_path,'') FROM tasks WHERE id=?1",
+            params![task_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    let dir = if workspace.trim().is_empty() {
+        env::current_dir().map_err(|e| e.to_string())?
+    } else {
+        PathBuf::from(&workspace)
+    };
+    if !dir.is_dir() {
+        return Err(format!("task workspace {} does not exist", dir.display()));
+    }
+
+    let now = now_ts();
+    let timeout = cfg.duration_secs("checks.timeout");
+    let mut results = Vec::with_capacity(checks.len());
+    for check in checks {
+        let (passed, excerpt) = run_acceptance_check(&check, &dir, timeout);
+        conn.execute(
+            "UPDATE acceptance_checks SET last_status=?1, last_output=?2, last_run_at=?3 WHERE id=?4",
+            params![if passed { "pass" } else { "fail" }, excerpt, now, check.id],
+        )
+        .map_err(|e| e.to_string())?;
+        results.push((check, passed, excerpt));
+    }
+
+    let passed = results.iter().filter(|r| r.1).count();
+    let mut report = format!("{passed}/{} acceptance checks passed", results.len());
+    for result in &results {
+        report.push('\n');
+        report.push_str(&check_result_line(result));
+        if !result.1 {
+            for line in result.2.lines().skip(1).take(5) {
+                report.push_str(&format!("\n      {line}"));
+            }
+        }
+    }
+    let typ = if passed == results.len() { "outcome" } else { "failure" };
+    conn.execute(
+        "INSERT INTO memories (id, goal_id, task_id, key, value, type, reasoning, source, created_at)
+         VALUES (?1, ?2, ?3, 'acceptance_checks', ?4, ?5, ?4, ?6, ?7)",
+        params![gen_typed_id("m"), goal_id, task_id, report, typ, agent, now],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(results)
+}
+
 fn cmd_audit(conn: &Connection, out: OutputCtx) -> Result<(), String> {
     // Find tasks that are done but missing acceptance_criteria or completion_summary
     let mut stmt = conn.prepare(
@@ -4659,20 +10847,22 @@ fn cmd_audit(conn: &Connection, out: OutputCtx) -> Result<(), String> {
         println!("All done tasks have acceptance criteria and completion summaries.");
     } else {
         println!("## Needs verification ({})", unverified.len());
+        let task_ids = unique_id_prefixes(conn, "tasks")?;
         for r in &unverified {
             let flags = format!("{}{}",
                 if !r.has_criteria { " missing acceptance criteria" } else { "" },
                 if !r.has_summary { " no completion summary" } else { "" },
             );
-            println!("  ⚠  {} [{}]{}", r.title, r.id, flags);
+            println!("  ⚠  {} [{}]{}", r.title, short_id(&task_ids, &r.id), flags);
         }
     }
 
     if !verified.is_empty() {
         println!();
         println!("## Verified ({})", verified.len());
+        let task_ids = unique_id_prefixes(conn, "tasks")?;
         for r in &verified {
-            println!("  ✓  {} [{}]", r.title, r.id);
+            println!("  ✓  {} [{}]", r.title, short_id(&task_ids, &r.id));
         }
     }
 
@@ -4742,6 +10932,7 @@ fn build_think_context(conn: &Connection) -> Result<String, String> {
          FROM tasks t LEFT JOIN goals g ON t.goal_id=g.id
          ORDER BY t.status, t.priority DESC"
     ).map_err(|e| e.to_string())?;
+    #[allow(clippy::type_complexity)]
     let tasks: Vec<(String, String, String, String, String, String, String, String)> = stmt
         .query_map([], |r| Ok((
             r.get(0)?,
@@ -4763,23 +10954,35 @@ fn build_think_context(conn: &Connection) -> Result<String, String> {
         if !summary.is_empty() { out.push_str(&format!("  done summary: {}\n", &summary[..summary.len().min(300)])); }
     }
 
+    // Shipped goals nobody checked against their success signal
+    let unchecked = query_goals_due_for_review(conn, None)?;
+    if !unchecked.is_empty() {
+        out.push_str("\n## Shipped goals never checked against their success signal\n");
+        for (id, name, signal, archived_at) in &unchecked {
+            let signal = if signal.is_empty() { "(none set)" } else { signal.as_str() };
+            out.push_str(&format!("- {} ({}) archived {} — success signal: {}\n", name, id, ago(*archived_at), signal));
+        }
+    }
+
     // Decisions
     out.push_str("\n## Decisions\n");
     let mut stmt = conn.prepare(
-        "SELECT what, why, affects FROM decisions ORDER BY created_at DESC LIMIT 10"
+        "SELECT what, why, affects, revisit_after FROM decisions WHERE status='active' ORDER BY created_at DESC LIMIT 10"
     ).map_err(|e| e.to_string())?;
-    let decisions: Vec<(String, String, String)> = stmt
+    let decisions: Vec<(String, String, String, Option<i64>)> = stmt
         .query_map([], |r| Ok((
             r.get(0)?,
             r.get(1)?,
             r.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            r.get(3)?,
         )))
         .map_err(|e| e.to_string())?
         .filter_map(|r| r.ok())
         .collect();
-    for (what, why, affects) in &decisions {
+    for (what, why, affects, revisit_after) in &decisions {
         out.push_str(&format!("- {} — {}", what, why));
         if !affects.is_empty() { out.push_str(&format!(" (affects: {})", affects)); }
+        out.push_str(&revisit_label("active", *revisit_after));
         out.push('\n');
     }
 
@@ -4799,9 +11002,9 @@ fn build_think_context(conn: &Connection) -> Result<String, String> {
 
     // Recent memories
     out.push_str("\n## Recent Memories (last 10)\n");
-    let mut stmt = conn.prepare(
-        "SELECT key, value, type FROM memories ORDER BY created_at DESC LIMIT 10"
-    ).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT key, value, type FROM memories WHERE {MEMORY_LIVE_SQL} ORDER BY created_at DESC LIMIT 10"
+    )).map_err(|e| e.to_string())?;
     let mems: Vec<(String, String, String)> = stmt
         .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
         .map_err(|e| e.to_string())?
@@ -148,6 +148,41 @@ fi
 run init
 assert_exit "imi init (idempotent)" 0
 
+# ═════════════════════════════════════════════════════════════
+# 2B. SCHEMA MIGRATIONS
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 2B. Schema migrations ───────────────────────────────"
+
+run db status
+assert_exit     "db status exits 0"        0
+assert_contains "db status is up to date"  "up to date"
+
+MIGRATE_DB="$TEST_DIR/migrate.db"
+IMI_DB="$MIGRATE_DB" run db migrate --dry-run
+assert_exit     "db migrate --dry-run exits 0"      0
+assert_contains "db migrate --dry-run lists pending" "baseline schema"
+DB_OUT=$(sqlite3 "$MIGRATE_DB" "PRAGMA user_version;" 2>&1) || true
+if [[ "$DB_OUT" == "0" ]]; then
+  pass "db migrate --dry-run leaves user_version untouched"
+else
+  fail "db migrate --dry-run changed user_version" "user_version: $DB_OUT"
+fi
+
+IMI_DB="$MIGRATE_DB" run db migrate
+assert_exit "db migrate exits 0" 0
+DB_OUT=$(sqlite3 "$MIGRATE_DB" "SELECT COUNT(*) FROM sqlite_master WHERE name='task_dependencies';" 2>&1) || true
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "db migrate applies pending migrations"
+else
+  fail "db migrate did not create task_dependencies" "DB said: $DB_OUT"
+fi
+
+sqlite3 "$MIGRATE_DB" "PRAGMA user_version = 9999;"
+IMI_DB="$MIGRATE_DB" run plan
+assert_exit     "newer schema is refused (exit=1)" 1
+assert_contains "newer schema error"               "only understands"
+
 # ═════════════════════════════════════════════════════════════
 # 3. ADD-GOAL
 # ═════════════════════════════════════════════════════════════
@@ -159,7 +194,7 @@ assert_exit     "add-goal exits 0"        0
 assert_contains "add-goal prints id"      "goal"
 
 # Extract goal_id from output (look for something like goal[xxxx] or a UUID-like id)
-GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z0-9]{14,}' | head -1)
+GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
 if [[ -z "$GOAL_ID" ]]; then
   # Fallback: try to get from DB
   db_query "SELECT id FROM goals ORDER BY rowid DESC LIMIT 1;"
@@ -201,7 +236,7 @@ run add-task "$GOAL_ID" "Implement JWT" "Add JWT token signing and verification"
 assert_exit     "add-task exits 0"     0
 assert_contains "add-task prints id"   "task"
 
-TASK_ID_JWT=$(echo "$CMD_OUT" | grep -oE '[a-z0-9]{14,}' | head -1)
+TASK_ID_JWT=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
 if [[ -z "$TASK_ID_JWT" ]]; then
   db_query "SELECT id FROM tasks ORDER BY rowid DESC LIMIT 1;"
   TASK_ID_JWT="$DB_OUT"
@@ -216,7 +251,7 @@ fi
 
 # Add a second task for richer testing
 run add-task "$GOAL_ID" "Write auth tests" "Unit and integration tests for auth module"
-TASK_ID_TESTS=$(echo "$CMD_OUT" | grep -oE '[a-z0-9]{14,}' | head -1)
+TASK_ID_TESTS=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
 if [[ -z "$TASK_ID_TESTS" ]]; then
   db_query "SELECT id FROM tasks ORDER BY rowid DESC LIMIT 1;"
   TASK_ID_TESTS="$DB_OUT"
@@ -244,7 +279,7 @@ assert_exit       "next --toon exits 0"       0
 assert_starts_with "next --toon starts with task[" "task["
 
 # Capture the task_id returned by next
-NEXT_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z0-9]{14,}' | head -1)
+NEXT_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
 if [[ -n "$NEXT_TASK_ID" ]]; then
   pass "next --toon returned task_id: $NEXT_TASK_ID"
 else
@@ -293,7 +328,7 @@ echo ""
 echo "── 9B. Wrap ────────────────────────────────────────────"
 
 run add-task "$GOAL_ID" "Autopilot wrapper success" "Run a command under IMI wrapper"
-WRAP_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z0-9]{14,}' | head -1)
+WRAP_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
 if [[ -z "$WRAP_TASK_ID" ]]; then
   db_query "SELECT id FROM tasks ORDER BY rowid DESC LIMIT 1;"
   WRAP_TASK_ID="$DB_OUT"
@@ -317,7 +352,7 @@ else
 fi
 
 run add-task "$GOAL_ID" "Autopilot wrapper failure" "Fail command under wrapper"
-WRAP_FAIL_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z0-9]{14,}' | head -1)
+WRAP_FAIL_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
 if [[ -z "$WRAP_FAIL_TASK_ID" ]]; then
   db_query "SELECT id FROM tasks ORDER BY rowid DESC LIMIT 1;"
   WRAP_FAIL_TASK_ID="$DB_OUT"
@@ -350,7 +385,7 @@ echo ""
 echo "── 9C. Orchestrate ─────────────────────────────────────"
 
 run add-goal "Orchestrate goal" "Parallel execution loop"
-ORCH_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z0-9]{14,}' | head -1)
+ORCH_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
 if [[ -z "$ORCH_GOAL_ID" ]]; then
   db_query "SELECT id FROM goals ORDER BY rowid DESC LIMIT 1;"
   ORCH_GOAL_ID="$DB_OUT"
@@ -376,6 +411,531 @@ else
   fail "orchestrate: completion summaries missing" "DB said: $DB_OUT"
 fi
 
+run add-goal "Orchestrate logs goal" "Worker output is kept per attempt"
+LOGS_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run add-task "$LOGS_GOAL_ID" "noisy worker" "prints on both streams"
+LOGS_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run orchestrate "$LOGS_GOAL_ID" --workers 1 --quiet --ping-secs 0 --checkpoint-secs 0 -- bash -c "echo hello-from-stdout; echo oops-from-stderr >&2"
+assert_exit         "orchestrate --quiet exits 0"                 0
+assert_not_contains "orchestrate --quiet hides worker output"     "hello-from-stdout"
+assert_contains     "orchestrate --quiet prints a status line"    "noisy worker"
+if [[ -f "$TEST_DIR/runs/$LOGS_TASK_ID/1/stderr.log" ]]; then
+  pass "orchestrate keeps per-attempt stderr.log"
+else
+  fail "orchestrate stderr.log missing" "$(ls -R "$TEST_DIR/runs/$LOGS_TASK_ID" 2>&1)"
+fi
+run logs "$LOGS_TASK_ID"
+assert_exit     "logs exits 0"                                0
+assert_contains "logs shows stdout"                           "hello-from-stdout"
+assert_contains "logs shows stderr"                           "oops-from-stderr"
+run logs "$LOGS_TASK_ID" --json
+assert_contains "logs --json tags the stream"                 '"stream":"stderr"'
+run logs "$LOGS_TASK_ID" --attempt 9
+assert_exit     "logs rejects an unknown attempt"             1
+run logs "$LOGS_TASK_ID" --follow
+assert_exit     "logs --follow returns once the task is done" 0
+run orchestrate "$LOGS_GOAL_ID" --tui < /dev/null
+assert_exit     "orchestrate --tui without a terminal exits 1" 1
+assert_contains "orchestrate --tui explains why"             "interactive terminal"
+
+# Drive the dashboard through a pty: pause claiming, kill the selected worker, quit.
+if command -v script >/dev/null 2>&1; then
+  run add-goal "Orchestrate dashboard goal" "Keys steer running workers"
+  TUI_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+  run add-task "$TUI_GOAL_ID" "tui first" "killed from the dashboard"
+  TUI_KILLED_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+  run add-task "$TUI_GOAL_ID" "tui second" "runs to completion"
+  run add-task "$TUI_GOAL_ID" "tui third" "never claimed while paused"
+  TUI_PAUSED_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+  TUI_SCREEN="$TEST_DIR/tui-screen.txt"
+  ( sleep 2; printf p; sleep 1; printf k; sleep 2; printf q ) \
+    | timeout 30 script -qfec "$IMI_BIN orchestrate $TUI_GOAL_ID --tui --workers 2 --ping-secs 0 --checkpoint-secs 0 -- sleep 6" /dev/null \
+    > "$TUI_SCREEN" 2>&1 || true
+  if grep -aq "claiming paused" "$TUI_SCREEN"; then
+    pass "dashboard p pauses claiming"
+  else
+    fail "dashboard did not pause" "$(tail -c 400 "$TUI_SCREEN")"
+  fi
+  db_query "SELECT COUNT(*) FROM memories WHERE task_id='$TUI_KILLED_ID' AND value LIKE '%orchestrate dashboard%';"
+  if [[ "$DB_OUT" -ge 1 ]]; then
+    pass "dashboard k kills the selected worker and fails its task"
+  else
+    fail "dashboard kill was not recorded" "$(tail -c 400 "$TUI_SCREEN")"
+  fi
+  db_query "SELECT status FROM tasks WHERE id='$TUI_PAUSED_ID';"
+  if [[ "$DB_OUT" == "todo" ]]; then
+    pass "paused dashboard does not claim into the freed slot"
+  else
+    fail "paused dashboard claimed another task" "DB said: $DB_OUT"
+  fi
+  run delete "$TUI_GOAL_ID"
+fi
+
+run add-goal "Orchestrate shutdown goal" "Signals release claimed tasks"
+SIG_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run add-task "$SIG_GOAL_ID" "polite worker" "exits on SIGTERM"
+run add-task "$SIG_GOAL_ID" "stubborn worker" "ignores SIGTERM until killed"
+IMI_SHUTDOWN_GRACE=1s "$IMI_BIN" orchestrate "$SIG_GOAL_ID" --workers 2 --ping-secs 0 --checkpoint-secs 0 \
+  -- bash -c 'if [[ "$IMI_TASK_TITLE" == stubborn* ]]; then trap "" TERM; fi; sleep 30' > "$TEST_DIR/sig.out" 2>&1 &
+SIG_PID=$!
+sleep 2
+kill -TERM "$SIG_PID"
+SIG_EXIT=0
+wait "$SIG_PID" || SIG_EXIT=$?
+CMD_OUT=$(cat "$TEST_DIR/sig.out")
+CMD_EXIT=$SIG_EXIT
+assert_exit     "orchestrate exits 1 after SIGTERM"             1
+assert_contains "orchestrate lists the abandoned tasks"         "stubborn worker"
+db_query "SELECT COUNT(*) FROM tasks WHERE goal_id='$SIG_GOAL_ID' AND status='todo' AND attempts=0;"
+if [[ "$DB_OUT" == "2" ]]; then
+  pass "SIGTERM releases every claimed task to todo without spending an attempt"
+else
+  fail "SIGTERM left tasks claimed" "DB said: $DB_OUT"
+fi
+db_query "SELECT COUNT(*) FROM memories WHERE key='interrupted' AND task_id IN (SELECT id FROM tasks WHERE goal_id='$SIG_GOAL_ID');"
+if [[ "${DB_OUT:-0}" -ge 2 ]]; then
+  pass "SIGTERM stores an interrupted memory per task"
+else
+  fail "interrupted memories missing" "DB said: $DB_OUT"
+fi
+
+run add-task "$SIG_GOAL_ID" "wrapped and interrupted" "wrap hands the task back on SIGTERM"
+SIG_WRAP_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+"$IMI_BIN" wrap "$SIG_WRAP_TASK_ID" --ping-secs 0 --checkpoint-secs 0 -- sleep 30 > "$TEST_DIR/sig.out" 2>&1 &
+SIG_PID=$!
+sleep 1
+kill -TERM "$SIG_PID"
+SIG_EXIT=0
+wait "$SIG_PID" || SIG_EXIT=$?
+db_query "SELECT status FROM tasks WHERE id='$SIG_WRAP_TASK_ID';"
+if [[ "$SIG_EXIT" == "1" && "$DB_OUT" == "todo" ]]; then
+  pass "wrap releases its task on SIGTERM"
+else
+  fail "wrap did not release its task on SIGTERM" "exit=$SIG_EXIT status=$DB_OUT $(cat "$TEST_DIR/sig.out")"
+fi
+
+run add-task "$SIG_GOAL_ID" "stubborn and forced" "a second signal skips the grace period"
+SIG_FORCE_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+IMI_SHUTDOWN_GRACE=60s "$IMI_BIN" wrap "$SIG_FORCE_TASK_ID" --ping-secs 0 --checkpoint-secs 0 \
+  -- bash -c 'trap "" TERM; sleep 60' > "$TEST_DIR/sig.out" 2>&1 &
+SIG_PID=$!
+sleep 1
+SIG_START=$(date +%s)
+kill -TERM "$SIG_PID"
+sleep 1
+kill -TERM "$SIG_PID"
+SIG_EXIT=0
+wait "$SIG_PID" || SIG_EXIT=$?
+SIG_ELAPSED=$(( $(date +%s) - SIG_START ))
+db_query "SELECT status FROM tasks WHERE id='$SIG_FORCE_TASK_ID';"
+if [[ "$SIG_ELAPSED" -lt 10 && "$DB_OUT" == "todo" ]]; then
+  pass "a second SIGTERM kills the worker without waiting out the grace period (${SIG_ELAPSED}s)"
+else
+  fail "second SIGTERM did not force the kill" "took ${SIG_ELAPSED}s status=$DB_OUT $(cat "$TEST_DIR/sig.out")"
+fi
+run delete "$SIG_GOAL_ID"
+
+run add-goal "Worker limits goal" "Hung workers get killed"
+LIMIT_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run add-task "$LIMIT_GOAL_ID" "hung worker" "never finishes"
+HUNG_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run wrap "$HUNG_TASK_ID" --task-timeout 1s --ping-secs 1 --checkpoint-secs 0 -- sleep 30
+assert_exit     "wrap --task-timeout exits 1"                   1
+assert_contains "wrap --task-timeout reports the timeout"       "timeout:"
+db_query "SELECT status || ' ' || attempts FROM tasks WHERE id='$HUNG_TASK_ID';"
+if [[ "$DB_OUT" == "todo 1" ]]; then
+  pass "timeout fails the task and spends an attempt"
+else
+  fail "timeout did not fail the task" "DB said: $DB_OUT"
+fi
+db_query "SELECT COUNT(*) FROM memories WHERE task_id='$HUNG_TASK_ID' AND key='timeout';"
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "timeout is recorded under its own memory key"
+else
+  fail "timeout memory missing" "DB said: $DB_OUT"
+fi
+CMD_EXIT=0
+CMD_OUT=$(IMI_TASK_TIMEOUT=1 "$IMI_BIN" wrap "$HUNG_TASK_ID" --ping-secs 0 --checkpoint-secs 0 -- sleep 30 2>&1) || CMD_EXIT=$?
+assert_exit     "worker.task_timeout applies without --task-timeout" 1
+assert_contains "worker.task_timeout is named in the reason"    "worker.task_timeout"
+
+TIMEOUT_MEMORIES="SELECT COUNT(*) FROM memories WHERE task_id IN (SELECT id FROM tasks WHERE goal_id='$LIMIT_GOAL_ID') AND key='timeout';"
+db_query "$TIMEOUT_MEMORIES"
+TIMEOUTS_BEFORE="$DB_OUT"
+ORCH_HUNG_START=$(date +%s)
+run orchestrate "$LIMIT_GOAL_ID" --workers 1 --max-tasks 1 --quiet --task-timeout 1s --ping-secs 0 --checkpoint-secs 0 -- sleep 30
+ORCH_HUNG_ELAPSED=$(( $(date +%s) - ORCH_HUNG_START ))
+db_query "$TIMEOUT_MEMORIES"
+if [[ "$ORCH_HUNG_ELAPSED" -lt 15 && "$DB_OUT" -eq $(( TIMEOUTS_BEFORE + 1 )) ]]; then
+  pass "orchestrate --task-timeout kills a hung worker and records the timeout (${ORCH_HUNG_ELAPSED}s)"
+else
+  fail "orchestrate --task-timeout did not stop the worker" "took ${ORCH_HUNG_ELAPSED}s timeouts=$DB_OUT $CMD_OUT"
+fi
+
+run add-task "$LIMIT_GOAL_ID" "tight task" "its own limit is stricter" --max-runtime 1s
+TIGHT_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run wrap "$TIGHT_TASK_ID" --task-timeout 1h --ping-secs 0 --checkpoint-secs 0 -- sleep 30
+assert_exit     "max_runtime beats a looser --task-timeout"     1
+assert_contains "max_runtime names where the limit came from"   "max_runtime"
+
+run add-task "$LIMIT_GOAL_ID" "capped worker" "runs under ulimit"
+CAPPED_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run wrap "$CAPPED_TASK_ID" --max-memory 64M --max-cpu 30s --ping-secs 0 --checkpoint-secs 0 -- sh -c 'echo "data=$(ulimit -d) vmem=$(ulimit -v) cpu=$(ulimit -t)"'
+assert_exit     "wrap with rlimits exits 0"                     0
+assert_contains "wrap applies the memory and CPU limits"        "data=65536 vmem=unlimited cpu=30"
+# With a terminal, the worker still gets its own group, so a timeout kills the agent that
+# sh started too, not just the sh. HUP is ignored so closing the pty can't do the job instead.
+timeout 30 script -qfec "$IMI_BIN wrap $HUNG_TASK_ID --task-timeout 1s --ping-secs 0 --checkpoint-secs 0 -- sh -c 'trap \"\" HUP; sleep 41; :'" /dev/null >/dev/null 2>&1 || true
+if pgrep -f '^sleep 41$' >/dev/null; then
+  fail "interactive wrap timeout left the agent running" "$(pgrep -af '^sleep 41$')"
+  pkill -f '^sleep 41$'
+else
+  pass "interactive wrap timeout kills the worker's whole process group"
+fi
+run add-task "$LIMIT_GOAL_ID" "interactive worker" "reads from the terminal"
+TTY_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+TTY_OUT=$(timeout 30 script -qfec "$IMI_BIN wrap $TTY_TASK_ID --ping-secs 0 --checkpoint-secs 0 -- sh -c 'ps -o stat= -p \$\$; read x; echo got=\$x'" /dev/null <<<"typed" 2>&1 || true)
+if [[ "$TTY_OUT" =~ [SR]\+ && "$TTY_OUT" == *"got=typed"* ]]; then
+  pass "interactive wrap hands the worker the terminal"
+else
+  fail "interactive worker did not get the terminal" "$TTY_OUT"
+fi
+run wrap "$HUNG_TASK_ID" --max-memory 12Q -- true
+assert_exit     "wrap rejects a bad --max-memory"               1
+run delete "$LIMIT_GOAL_ID"
+
+# ═════════════════════════════════════════════════════════════
+# 9D. DEPENDENCIES (blocked-by graph)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 9D. Dependencies ────────────────────────────────────"
+
+run add-goal "Dependency goal" "Ordered work"
+DEP_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+
+run add-task "$DEP_GOAL_ID" "design the schema" "prerequisite"
+DEP_FIRST_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+
+run add-task "$DEP_GOAL_ID" "wire the API" "needs the schema" --depends-on "$DEP_FIRST_ID"
+assert_exit "add-task --depends-on exits 0" 0
+DEP_SECOND_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+
+db_query "SELECT COUNT(*) FROM task_dependencies WHERE task_id='$DEP_SECOND_ID' AND depends_on='$DEP_FIRST_ID';"
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "add-task --depends-on: dependency persisted"
+else
+  fail "add-task --depends-on: dependency missing" "DB said: $DB_OUT"
+fi
+
+run depend "$DEP_FIRST_ID" "$DEP_SECOND_ID"
+assert_exit     "depend rejects cycles (exit=1)" 1
+assert_contains "depend cycle error"             "cycle"
+
+run plan
+assert_contains "plan shows blocker" "blocked by"
+
+run start "$DEP_SECOND_ID"
+assert_exit     "start refuses a task with unmet prerequisites" 1
+assert_contains "start names the unmet prerequisite"            "design the schema"
+db_query "SELECT status FROM tasks WHERE id='$DEP_SECOND_ID';"
+if [[ "$DB_OUT" == "todo" ]]; then
+  pass "start: task with unmet prerequisites stays todo"
+else
+  fail "start: task with unmet prerequisites was started" "DB said: $DB_OUT"
+fi
+
+run add-task "$DEP_GOAL_ID" "draft the docs" "needs the schema too" --depends-on "$DEP_FIRST_ID"
+DEP_FORCED_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run start "$DEP_FORCED_ID" --force
+assert_exit "start --force overrides unmet prerequisites" 0
+run delete "$DEP_FORCED_ID"
+
+run next "$DEP_GOAL_ID" --toon
+assert_contains     "next claims the prerequisite first" "design the schema"
+assert_not_contains "next skips the blocked task"        "wire the API"
+
+run complete "$DEP_FIRST_ID" "schema designed"
+run next "$DEP_GOAL_ID" --toon
+assert_contains "next claims the unblocked task" "wire the API"
+
+run complete "$DEP_SECOND_ID" "api wired"
+
+# ═════════════════════════════════════════════════════════════
+# 9E. EXPORT / IMPORT
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 9E. Export / Import ─────────────────────────────────"
+
+EXPORT_FILE="$TEST_DIR/export.json"
+run export --output "$EXPORT_FILE"
+assert_exit     "export --output exits 0" 0
+assert_contains "export reports rows"     "Exported"
+
+run export --format ndjson
+assert_exit     "export --format ndjson exits 0" 0
+assert_starts_with "ndjson starts with header" '{"'
+assert_contains "ndjson has row records" '"type":"row"'
+
+IMPORT_DB="$TEST_DIR/import.db"
+IMI_DB="$IMPORT_DB" run import "$EXPORT_FILE"
+assert_exit "import into empty DB exits 0" 0
+DB_OUT=$(sqlite3 "$IMPORT_DB" "SELECT COUNT(*) FROM tasks WHERE id='$DEP_SECOND_ID';" 2>&1) || true
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "import keeps task IDs stable"
+else
+  fail "import lost task IDs" "DB said: $DB_OUT"
+fi
+
+IMI_DB="$IMPORT_DB" run import "$EXPORT_FILE" --merge
+assert_exit     "import --merge twice exits 0"    0
+assert_contains "import --merge skips duplicates" "Imported 0 row"
+
+sqlite3 "$IMPORT_DB" "UPDATE tasks SET title='edited locally' WHERE id='$DEP_SECOND_ID';"
+IMI_DB="$IMPORT_DB" run import "$EXPORT_FILE" --merge
+assert_contains "import --merge reports conflicts" "exists locally with different content"
+
+IMI_DB="$IMPORT_DB" run import "$EXPORT_FILE" --replace
+assert_exit "import --replace exits 0" 0
+DB_OUT=$(sqlite3 "$IMPORT_DB" "SELECT title FROM tasks WHERE id='$DEP_SECOND_ID';" 2>&1) || true
+if [[ "$DB_OUT" == "wire the API" ]]; then
+  pass "import --replace restores exported rows"
+else
+  fail "import --replace kept local edits" "DB said: $DB_OUT"
+fi
+
+sqlite3 "$IMPORT_DB" "INSERT OR REPLACE INTO settings (key, value) VALUES ('install_id', 'this-machine');"
+IMI_DB="$IMPORT_DB" run import "$EXPORT_FILE" --replace
+DB_OUT=$(sqlite3 "$IMPORT_DB" "SELECT value FROM settings WHERE key='install_id';" 2>&1) || true
+if [[ "$DB_OUT" == "this-machine" ]]; then
+  pass "import --replace keeps per-machine settings"
+else
+  fail "import --replace overwrote install_id" "DB said: $DB_OUT"
+fi
+
+IMI_DB="$IMPORT_DB" run export --format ndjson --since 9999999999 --output "$TEST_DIR/empty.ndjson"
+IMI_DB="$TEST_DIR/import-empty.db" run import "$TEST_DIR/empty.ndjson"
+assert_exit "header-only ndjson export re-imports" 0
+
+# A --since window can catch a task whose goal is older; the goal must come along.
+sqlite3 "$IMPORT_DB" "UPDATE tasks SET updated_at=4000000000 WHERE id='$DEP_SECOND_ID'; INSERT OR REPLACE INTO settings (key, value) VALUES ('blob_probe', X'00FF10');"
+IMI_DB="$IMPORT_DB" run export --since 3999999999 --output "$TEST_DIR/since.json"
+IMI_DB="$TEST_DIR/import-since.db" run import "$TEST_DIR/since.json"
+assert_exit "--since export imports into an empty DB" 0
+DB_OUT=$(sqlite3 "$TEST_DIR/import-since.db" "SELECT COUNT(*) FROM tasks t JOIN goals g ON g.id=t.goal_id WHERE t.id='$DEP_SECOND_ID';" 2>&1) || true
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "--since export carries the task's parent goal"
+else
+  fail "--since export dropped the parent goal" "DB said: $DB_OUT"
+fi
+IMI_DB="$IMPORT_DB" run export --output "$TEST_DIR/blob.json"
+IMI_DB="$TEST_DIR/import-blob.db" run import "$TEST_DIR/blob.json"
+DB_OUT=$(sqlite3 "$TEST_DIR/import-blob.db" "SELECT typeof(value) || ' ' || hex(value) FROM settings WHERE key='blob_probe';" 2>&1) || true
+if [[ "$DB_OUT" == "blob 00FF10" ]]; then
+  pass "BLOB values survive an export round trip"
+else
+  fail "BLOB value corrupted by export" "DB said: $DB_OUT"
+fi
+
+# ═════════════════════════════════════════════════════════════
+# 9F. TEXT MIRROR
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 9F. Mirror ──────────────────────────────────────────"
+
+MIRROR_DIR="$TEST_DIR/mirror"
+run mirror enable
+assert_exit "mirror enable exits 0" 0
+if [[ -f "$MIRROR_DIR/tasks/$DEP_SECOND_ID.json" ]]; then
+  pass "mirror enable writes one file per task"
+else
+  fail "mirror enable: task file missing" "$(ls -R "$MIRROR_DIR" 2>&1)"
+fi
+
+run decide "mirror decisions into git" "reviewers never see the sqlite blob"
+MIRROR_DECISIONS=$(ls "$MIRROR_DIR/decisions" 2>/dev/null | wc -l | tr -d ' ')
+if [[ "$MIRROR_DECISIONS" -ge 1 ]]; then
+  pass "mirror refreshes after a mutation"
+else
+  fail "mirror not refreshed after decide" "files: $MIRROR_DECISIONS"
+fi
+
+run mirror sync
+assert_contains "mirror sync is stable when nothing changed" "0 written"
+
+sqlite3 "$IMI_DB" "DELETE FROM decisions WHERE what='mirror decisions into git';"
+run mirror rebuild
+assert_exit "mirror rebuild exits 0" 0
+db_query "SELECT COUNT(*) FROM decisions WHERE what='mirror decisions into git';"
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "mirror rebuild restores rows from files"
+else
+  fail "mirror rebuild did not restore decision" "DB said: $DB_OUT"
+fi
+
+run mirror disable
+assert_exit "mirror disable exits 0" 0
+
+# ═════════════════════════════════════════════════════════════
+# 9G. HISTORY (per-task / per-goal timeline)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 9G. History ─────────────────────────────────────────"
+
+run add-goal "History goal" "Timeline checks"
+HIST_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run add-task "$HIST_GOAL_ID" "flaky migration" "bounces between agents"
+HIST_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+
+run next "$HIST_GOAL_ID" --agent hist-a
+run ping "$HIST_TASK_ID"
+run fail "$HIST_TASK_ID" "lock timeout on staging"
+run next "$HIST_GOAL_ID" --agent hist-b
+run checkpoint "$HIST_TASK_ID" "retrying with smaller batches"
+run complete "$HIST_TASK_ID" "migrated in batches of 500"
+
+run history "$HIST_TASK_ID"
+assert_exit     "history exits 0"                      0
+assert_contains "history shows both claims"            "2 claim(s), 1 failure(s)"
+assert_contains "history lists the agents"             "agents: hist-a, hist-b"
+assert_contains "history includes the failure reason"  "lock timeout on staging"
+assert_contains "history includes pings"               "ping"
+assert_contains "history includes the completion"      "migrated in batches of 500"
+
+run add-task "$HIST_GOAL_ID" "wrapped migration" "heartbeats come from the watchdog"
+HIST_WRAP_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run wrap "$HIST_WRAP_TASK_ID" --agent hist-w --ping-secs 1 --checkpoint-secs 0 -- sleep 3
+run history "$HIST_WRAP_TASK_ID" --json
+HIST_HEARTBEATS=$(echo "$CMD_OUT" | { grep -o '"kind":"ping"' || true; } | wc -l)
+if [[ "$HIST_HEARTBEATS" -ge 2 ]]; then
+  pass "history includes the wrap watchdog's heartbeats ($HIST_HEARTBEATS)"
+else
+  fail "history is missing the wrap watchdog's heartbeats" "$CMD_OUT"
+fi
+
+run history "$HIST_GOAL_ID" --toon
+assert_contains "goal history merges its tasks"        "$HIST_TASK_ID"
+
+run history "$HIST_TASK_ID" --since 2099-01-01 --json
+assert_contains "history --since filters old entries"  "\"claims\":0"
+
+run history "nonexistent-zzz"
+assert_exit     "history rejects unknown ids"          1
+
+# ═════════════════════════════════════════════════════════════
+# 9H. WORKER BACKENDS (config-declared command templates)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 9H. Worker backends ─────────────────────────────────"
+
+# Own database: the project config.toml below would leak into the shared fixture.
+MAIN_IMI_DB="$IMI_DB"
+export IMI_DB="$TEST_DIR/backends/state.db"
+run init
+cat > "$TEST_DIR/backends/config.toml" <<'TOML'
+[backends.scribe]
+command = 'echo {title} > {run_dir}/title.txt && mkdir -p {run_dir}/artifacts && echo ok > {run_dir}/artifacts/report.txt && echo "Built it via scribe for $IMI_TASK_ID" > {run_dir}/summary.md'
+
+[backends.broken]
+command = 'exit 3'
+
+[backends.hang]
+command = 'sleep 37; :'
+
+[backends.slow]
+command = 'sleep 3 && echo "slow but done" > {run_dir}/summary.md'
+
+[backends.limits]
+command = 'echo "data=$(ulimit -d) cpu=$(ulimit -t)" > {run_dir}/summary.md'
+TOML
+run goal "Backend goal" "Exercise worker backends"
+BACKEND_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run task "$BACKEND_GOAL_ID" "it's quoted" "placeholder values are shell-quoted"
+BACKEND_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+
+run run "$BACKEND_TASK_ID" --backend scribe
+assert_exit "run with a config-declared backend exits 0" 0
+if [[ "$(cat "$TEST_DIR/backends/runs/$BACKEND_TASK_ID/title.txt" 2>/dev/null)" == "it's quoted" ]]; then
+  pass "backend template gets {title} shell-quoted"
+else
+  fail "backend template did not see the title" "$(ls "$TEST_DIR/backends/runs/$BACKEND_TASK_ID" 2>&1)"
+fi
+db_query "SELECT value FROM memories WHERE task_id='$BACKEND_TASK_ID' AND key='completion_summary';"
+if [[ "$DB_OUT" == *"Built it via scribe for $BACKEND_TASK_ID"* && "$DB_OUT" == *"artifacts/report.txt"* ]]; then
+  pass "backend summary.md and artifacts land in the completion summary"
+else
+  fail "backend completion summary missing summary.md or artifacts" "DB said: $DB_OUT"
+fi
+
+run task "$BACKEND_GOAL_ID" "broken worker" "backend exits non-zero"
+BROKEN_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run run "$BROKEN_TASK_ID" --backend nope
+assert_exit     "unknown backend exits 1"                      1
+assert_contains "unknown backend lists the declared ones"      "scribe"
+db_query "SELECT status FROM tasks WHERE id='$BROKEN_TASK_ID';"
+if [[ "$DB_OUT" == "todo" ]]; then
+  pass "unknown backend fails before claiming the task"
+else
+  fail "unknown backend claimed the task" "DB said: $DB_OUT"
+fi
+run wrap "$BROKEN_TASK_ID" --backend broken --ping-secs 0 --checkpoint-secs 0
+assert_exit     "failing backend exits 1"                      1
+assert_contains "failing backend reports its exit status"      "worker backend 'broken' exited"
+db_query "SELECT status FROM tasks WHERE id='$BROKEN_TASK_ID';"
+if [[ "$DB_OUT" == "todo" ]]; then
+  pass "failing backend releases the task"
+else
+  fail "failing backend left the task claimed" "DB said: $DB_OUT"
+fi
+
+run task "$BACKEND_GOAL_ID" "hung backend" "never finishes"
+HANG_BACKEND_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+CMD_EXIT=0
+CMD_OUT=$(IMI_TASK_TIMEOUT=1 "$IMI_BIN" run "$HANG_BACKEND_TASK_ID" --backend hang 2>&1) || CMD_EXIT=$?
+assert_exit     "run applies worker.task_timeout"              1
+assert_contains "run reports the timeout"                      "timeout:"
+if pgrep -f '^sleep 37$' >/dev/null; then
+  fail "run timeout left the backend's children running" "$(pgrep -af '^sleep 37$')"
+else
+  pass "run timeout kills the backend's whole process group"
+fi
+
+run task "$BACKEND_GOAL_ID" "slow backend" "outlives a checkpoint"
+SLOW_BACKEND_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+CMD_EXIT=0
+CMD_OUT=$(IMI_PING_SECS=1 IMI_CHECKPOINT_SECS=1 "$IMI_BIN" run "$SLOW_BACKEND_TASK_ID" --backend slow 2>&1) || CMD_EXIT=$?
+assert_exit "run with a slow backend exits 0" 0
+db_query "SELECT COUNT(*) FROM memories WHERE task_id='$SLOW_BACKEND_TASK_ID' AND key='checkpoint';"
+if [[ "$DB_OUT" -ge 1 ]]; then
+  pass "run reads worker.ping_secs and worker.checkpoint_secs"
+else
+  fail "run ignored worker.checkpoint_secs" "DB said: $DB_OUT"
+fi
+
+run task "$BACKEND_GOAL_ID" "capped backend" "runs under ulimit"
+LIMITS_BACKEND_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run run "$LIMITS_BACKEND_TASK_ID" --backend limits --max-memory 64M --max-cpu 30s
+assert_exit "run with rlimits exits 0" 0
+db_query "SELECT value FROM memories WHERE task_id='$LIMITS_BACKEND_TASK_ID' AND key='completion_summary';"
+if [[ "$DB_OUT" == *"data=65536 cpu=30"* ]]; then
+  pass "run applies the memory and CPU limits"
+else
+  fail "run did not apply the rlimits" "DB said: $DB_OUT"
+fi
+
+printf '\n[worker]\nbackend = "scribe"\n' >> "$TEST_DIR/backends/config.toml"
+run goal "Orchestrated backend goal" "worker.backend picks the default"
+ORCH_BACKEND_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run task "$ORCH_BACKEND_GOAL_ID" "orchestrated via scribe" "uses the configured backend"
+run orchestrate "$ORCH_BACKEND_GOAL_ID" --workers 1 --ping-secs 0 --checkpoint-secs 0
+assert_exit "orchestrate with worker.backend exits 0" 0
+db_query "SELECT COUNT(*) FROM tasks WHERE goal_id='$ORCH_BACKEND_GOAL_ID' AND status='done';"
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "orchestrate runs tasks through worker.backend"
+else
+  fail "orchestrate did not complete through worker.backend" "DB said: $DB_OUT"
+fi
+export IMI_DB="$MAIN_IMI_DB"
+
 # ═════════════════════════════════════════════════════════════
 # 10. MEMORY ADD + LIST
 # ═════════════════════════════════════════════════════════════
@@ -396,6 +956,88 @@ assert_exit "memory add (2nd) exits 0" 0
 run memory list
 assert_contains "memory list shows auth_pattern" "auth_pattern"
 
+# ═════════════════════════════════════════════════════════════
+# 10B. MEMORY COMPACT (per-task digest + archive)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 10B. Memory compact ─────────────────────────────────"
+
+run add-goal "Compact goal" "Lifecycle noise"
+COMPACT_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run add-task "$COMPACT_GOAL_ID" "noisy task" "writes lifecycle rows"
+COMPACT_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run next "$COMPACT_GOAL_ID"
+run checkpoint "$COMPACT_TASK_ID" "halfway through the fixture"
+run complete "$COMPACT_TASK_ID" "noisy task finished"
+run memory add "$COMPACT_GOAL_ID" repeated_fact "the same value twice"
+run memory add "$COMPACT_GOAL_ID" repeated_fact "the same value twice"
+db_query "UPDATE memories SET created_at = created_at - 40*86400 WHERE task_id='$COMPACT_TASK_ID' OR goal_id='$COMPACT_GOAL_ID';"
+
+run memory compact --goal "$COMPACT_GOAL_ID" --dry-run
+assert_contains "compact --dry-run reports the plan"      "Would fold"
+db_query "SELECT COUNT(*) FROM memory_archive;"
+if [[ "$DB_OUT" == "0" ]]; then
+  pass "compact --dry-run changes nothing"
+else
+  fail "compact --dry-run should not archive" "DB said: $DB_OUT"
+fi
+
+run memory compact --goal "$COMPACT_GOAL_ID" --older-than 30d
+assert_exit     "memory compact exits 0"                  0
+db_query "SELECT COUNT(*) FROM memories WHERE task_id='$COMPACT_TASK_ID' AND type IN ('lifecycle','checkpoint');"
+if [[ "$DB_OUT" == "0" ]]; then
+  pass "compact removes lifecycle rows from memories"
+else
+  fail "compact left lifecycle rows behind" "DB said: $DB_OUT"
+fi
+db_query "SELECT COUNT(*) FROM memories WHERE task_id='$COMPACT_TASK_ID' AND key='task_digest';"
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "compact writes one digest per task"
+else
+  fail "compact should write one digest" "DB said: $DB_OUT"
+fi
+db_query "SELECT COUNT(*) FROM memories WHERE key='repeated_fact';"
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "compact deduplicates identical values"
+else
+  fail "compact should keep one copy of a duplicate" "DB said: $DB_OUT"
+fi
+run memory list --archived
+assert_contains "archived rows stay listable"             "halfway through the fixture"
+run history "$COMPACT_TASK_ID"
+assert_contains "history still shows compacted rows"      "halfway through the fixture"
+run memory compact --goal "$COMPACT_GOAL_ID" --json
+assert_contains "a second compact finds nothing new"      '"archived":0'
+
+# ═════════════════════════════════════════════════════════════
+# 10C. MEMORY TYPES + TTL (validated kinds, expiry, list filters)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 10C. Memory types + TTL ─────────────────────────────"
+
+run memory add "$GOAL_ID" staging_status "staging is down this week" --ttl 7d
+assert_exit     "memory add --ttl exits 0"                0
+assert_contains "memory add --ttl reports the expiry"     "expires"
+run memory add "$GOAL_ID" flaky_fixture "fixture flaked once" --type failure
+assert_exit     "memory add --type exits 0"               0
+run memory add "$GOAL_ID" bad_kind "nope" --type gossip
+assert_exit     "memory add rejects unknown types"        1
+run memory add "$GOAL_ID" old_news "this fact has expired" --ttl 1d
+db_query "UPDATE memories SET expires_at = strftime('%s','now') - 60 WHERE key='old_news';"
+
+run context --json
+assert_contains "context shows live TTL memories"         "staging is down this week"
+assert_not_contains "context hides expired memories"      "this fact has expired"
+run memory list --type failure
+assert_contains "memory list --type filters by kind"      "fixture flaked once"
+assert_not_contains "memory list --type drops other kinds" "staging is down this week"
+run memory list --goal "$GOAL_ID" --source agent
+assert_contains "memory list --goal/--source filters"     "staging_status"
+run memory list
+assert_not_contains "memory list hides expired by default" "old_news"
+run memory list --expired
+assert_contains "memory list --expired includes them"     "old_news"
+
 # ═════════════════════════════════════════════════════════════
 # 11. DECIDE
 # ═════════════════════════════════════════════════════════════
@@ -413,6 +1055,142 @@ else
   fail "decide: decision not found in DB" "DB said: $DB_OUT"
 fi
 
+# ═════════════════════════════════════════════════════════════
+# 11B. DECISION LINKS (explicit decision → goal/task)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 11B. Decision links ─────────────────────────────────"
+
+run decide "Refresh tokens live 30 days" "fewer forced logins" --goal "$GOAL_ID"
+assert_exit     "decide --goal exits 0"               0
+assert_contains "decide --goal reports the link"      "linked to 1 goal"
+db_query "SELECT COUNT(*) FROM decision_links WHERE goal_id='$GOAL_ID' AND source='explicit';"
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "decide --goal stores an explicit link"
+else
+  fail "decide --goal did not store link" "DB said: $DB_OUT"
+fi
+
+run decide "Audit log every login" "compliance asks for it" "ship auth rollout"
+run decision backfill
+assert_contains "decision backfill proposes affects matches" "Audit log every login"
+assert_contains "decision backfill is a dry run by default"  "Re-run with"
+run decision backfill --apply
+db_query "SELECT COUNT(*) FROM decision_links WHERE goal_id='$GOAL_ID' AND source='backfill';"
+if [[ "$DB_OUT" -ge 1 ]]; then
+  pass "decision backfill --apply stores links"
+else
+  fail "decision backfill --apply stored nothing" "DB said: $DB_OUT"
+fi
+
+sqlite3 "$IMI_DB" "UPDATE goals SET name='Ship authentication' WHERE id='$GOAL_ID';"
+run context "$GOAL_ID"
+assert_contains "linked decisions survive a goal rename" "Refresh tokens live 30 days"
+sqlite3 "$IMI_DB" "UPDATE goals SET name='Ship auth' WHERE id='$GOAL_ID';"
+
+run add-goal "api" "public endpoints"
+API_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run decide "Prototype screens in Figma first" "rapid iteration" "rapid prototyping"
+run decide "Version every endpoint" "clients pin versions" "public api surface"
+run context "$API_GOAL_ID"
+assert_contains     "affects fallback matches whole words"  "Version every endpoint"
+assert_not_contains "affects fallback skips partial words"  "Prototype screens in Figma"
+run delete "$API_GOAL_ID"
+
+run decide "Nothing" "no goal" --goal "nonexistent-goal-zzz"
+assert_exit "decide --goal rejects unknown goal" 1
+
+# ═════════════════════════════════════════════════════════════
+# 11C. DECISION LIFECYCLE (supersede / revoke / revisit)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 11C. Decision lifecycle ─────────────────────────────"
+
+run decide "Store sessions in memcached" "already deployed" --goal "$GOAL_ID"
+db_query "SELECT id FROM decisions WHERE what='Store sessions in memcached';"
+OLD_DECISION_ID="$DB_OUT"
+run decide "Store sessions in redis" "persistence across restarts" --supersedes "$OLD_DECISION_ID" --revisit-after 2000-01-01
+assert_exit     "decide --supersedes exits 0"          0
+db_query "SELECT status FROM decisions WHERE id='$OLD_DECISION_ID';"
+if [[ "$DB_OUT" == "superseded" ]]; then
+  pass "decide --supersedes marks the old decision superseded"
+else
+  fail "old decision not superseded" "DB said: $DB_OUT"
+fi
+
+run context
+assert_contains     "context lists superseded calls under what was killed" "superseded by: Store sessions in redis"
+assert_contains     "context flags decisions past their revisit date"      "revisit due"
+run context --json
+assert_contains     "context --json carries revisit_due"   "\"revisit_due\":true"
+run context "$GOAL_ID"
+assert_contains     "replacement inherits the old decision's links" "Store sessions in redis"
+assert_not_contains "goal context hides superseded decisions"       "memcached"
+
+run decide "Rate-limit logins per IP" "stops credential stuffing"
+db_query "SELECT id FROM decisions WHERE what='Rate-limit logins per IP';"
+REVOKE_ID="$DB_OUT"
+run decision revoke "$REVOKE_ID" "moved to the WAF"
+assert_exit     "decision revoke exits 0"              0
+run decision revoke "$REVOKE_ID" "again"
+assert_exit     "decision revoke refuses non-active decisions" 1
+run decision list
+assert_not_contains "decision list hides revoked by default" "Rate-limit logins"
+run decision list --all
+assert_contains "decision list --all shows revoked reason"  "moved to the WAF"
+
+run decide "Bad date" "x" --revisit-after "someday"
+assert_exit     "decide rejects unparseable --revisit-after" 1
+run decide "Bad date" "x" --revisit-after 2026-02-31
+assert_exit     "decide rejects impossible dates"          1
+run decide "Bad date" "x" --revisit-after 2027-02-29
+assert_exit     "decide rejects Feb 29 outside leap years" 1
+
+# ═════════════════════════════════════════════════════════════
+# 11D. EDIT (in-place changes with revision history)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 11D. Edit ───────────────────────────────────────────"
+
+run add-goal "Edit target goal" "Receives a moved task"
+EDIT_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run edit task "$HIST_TASK_ID" --title "flaky schema migration" --acceptance-criteria "runs twice cleanly"
+assert_exit     "edit task exits 0"                        0
+assert_contains "edit task names the changed fields"      "title, acceptance_criteria"
+db_query "SELECT title FROM tasks WHERE id='$HIST_TASK_ID';"
+if [[ "$DB_OUT" == "flaky schema migration" ]]; then
+  pass "edit task updates the row in place"
+else
+  fail "edit task did not update the title" "DB said: $DB_OUT"
+fi
+db_query "SELECT old_value FROM revisions WHERE entity='task' AND entity_id='$HIST_TASK_ID' AND field='title';"
+if [[ "$DB_OUT" == "flaky migration" ]]; then
+  pass "edit task keeps the old title as a revision"
+else
+  fail "edit task revision missing" "DB said: $DB_OUT"
+fi
+
+run edit task "$HIST_TASK_ID" --goal "$EDIT_GOAL_ID"
+db_query "SELECT COUNT(*) FROM memories WHERE task_id='$HIST_TASK_ID' AND goal_id != '$EDIT_GOAL_ID';"
+if [[ "$DB_OUT" == "0" ]]; then
+  pass "edit task --goal moves the task's memories along"
+else
+  fail "edit task --goal left memories behind" "DB said: $DB_OUT"
+fi
+run edit task "$HIST_TASK_ID" --title ""
+assert_exit     "edit task rejects an empty title"         1
+run edit task "$HIST_TASK_ID"
+assert_contains "edit with no flags lists revisions"       "flaky migration"
+run history "$HIST_TASK_ID"
+assert_contains "history shows edits"                      "edited"
+
+run edit goal "$EDIT_GOAL_ID" --success-signal "moved tasks land here" --json
+assert_contains "edit goal reports the change as JSON"     '"field":"success_signal"'
+run edit decision "$OLD_DECISION_ID" --why "rotation is easier and audited"
+assert_exit     "edit decision exits 0"                    0
+run edit lesson nosuchlesson --verified-by "x"
+assert_exit     "edit lesson on unknown id fails"          1
+
 # ═════════════════════════════════════════════════════════════
 # 12. LOG
 # ═════════════════════════════════════════════════════════════
@@ -422,6 +1200,37 @@ echo "── 12. Log ───────────────────
 run log "direction note about architecture"
 assert_exit "log exits 0" 0
 
+# ═════════════════════════════════════════════════════════════
+# 12B. SEARCH (FTS5 index kept current by triggers)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 12B. Search ─────────────────────────────────────────"
+
+run search "did we decide anything about RS256?"
+assert_exit     "search exits 0"                      0
+assert_contains "search finds the decision"           "[decision]"
+assert_contains "search finds the memory"             "[memory]"
+
+run search architecture --type note --json
+assert_contains "search --type note returns the note" "\"kind\":\"note\""
+assert_not_contains "search --type filters out other kinds" "\"kind\":\"decision\""
+
+run search RS256 --goal "$GOAL_ID" --toon
+assert_contains "search --goal keeps goal memories"   "memory,"
+run decide "rotate signing keys every 90 days" "audit asked for it" --goal "$GOAL_ID"
+run search rotate signing --goal "$GOAL_ID" --type decision
+assert_contains "search --goal finds linked decisions" "rotate signing keys"
+
+run decide "sign local dev tokens with HS256" "no key pair needed on laptops"
+sqlite3 "$IMI_DB" "UPDATE decisions SET what='sign local dev tokens with ES256' WHERE what LIKE '%HS256%';"
+run search ES256 --type decision
+assert_contains "search index follows updates"        "ES256"
+run search HS256 --type decision
+assert_contains "search index drops stale text"       "No matches"
+
+run search --type bogus anything
+assert_exit     "search rejects unknown --type"       1
+
 # ═════════════════════════════════════════════════════════════
 # 13. CONTEXT
 # ═════════════════════════════════════════════════════════════
@@ -433,6 +1242,36 @@ assert_exit     "context --toon exits 0"           0
 # Context should surface decisions and/or memories
 assert_contains "context shows decision content"   "RS256"
 
+# ═════════════════════════════════════════════════════════════
+# 13B. CONTEXT BUDGET (--budget packs sections by priority)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 13B. Context budget ─────────────────────────────────"
+
+run add-goal "Budget goal" "Context that has to fit"
+BUDGET_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+for i in 1 2 3 4 5 6; do
+  run memory add "$BUDGET_GOAL_ID" "budget_note_$i" "a long memory about the budget fixture that repeats itself so it costs many tokens, entry $i"
+done
+
+run context "$BUDGET_GOAL_ID" --budget 60 --json
+assert_exit     "context --budget exits 0"                0
+assert_contains "context --budget reports truncation"     '"truncated":'
+assert_contains "context --budget names the cut section"  '"section":"memories"'
+run context "$BUDGET_GOAL_ID" --budget 100000 --json
+assert_contains "a large budget truncates nothing"        '"truncated":'
+assert_not_contains "a large budget keeps every section"  '"dropped"'
+run context --budget 0
+assert_exit     "context rejects a zero budget"           1
+run context "$BUDGET_GOAL_ID"
+assert_not_contains "no budget, no budget footer"         "tokens used"
+
+run add-task "$BUDGET_GOAL_ID" "budgeted task" "gets trimmed context"
+BUDGET_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run next "$BUDGET_GOAL_ID" --budget 80
+assert_contains "next --budget reports what was truncated" "truncated"
+run complete "$BUDGET_TASK_ID" "done within budget"
+
 # ═════════════════════════════════════════════════════════════
 # 14. STATUS
 # ═════════════════════════════════════════════════════════════
@@ -463,7 +1302,7 @@ echo "── 16. Fail (critical) ───────────────
 
 # First, claim the second task so we can fail it
 run next --toon
-FAIL_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z0-9]{14,}' | head -1)
+FAIL_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
 
 if [[ -n "$FAIL_TASK_ID" ]]; then
   # Verify it's in_progress before failing
@@ -495,6 +1334,418 @@ else
   fail "fail: no task available to fail" "$CMD_OUT"
 fi
 
+# ═════════════════════════════════════════════════════════════
+# 16B. RETRY BUDGET (blocked after max_attempts, imi unblock)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 16B. Retry budget ───────────────────────────────────"
+
+run add-goal "Retry goal" "Deterministic failures"
+RETRY_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run add-task "$RETRY_GOAL_ID" "always fails" "broken on purpose" --max-attempts 2
+RETRY_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+
+run next "$RETRY_GOAL_ID"
+run fail "$RETRY_TASK_ID" "segfault in fixture"
+assert_contains "first failure reports the attempt count" "attempt 1/2"
+run next "$RETRY_GOAL_ID"
+run fail "$RETRY_TASK_ID" "segfault in fixture again"
+assert_contains "budget exhausted blocks the task"        "blocked"
+db_query "SELECT status || ':' || attempts FROM tasks WHERE id='$RETRY_TASK_ID';"
+if [[ "$DB_OUT" == "blocked:2" ]]; then
+  pass "fail: task blocked after max_attempts"
+else
+  fail "fail: task should be blocked after max_attempts" "DB said: $DB_OUT"
+fi
+
+run next "$RETRY_GOAL_ID" --toon
+assert_not_contains "next skips blocked tasks"            "always fails"
+run status
+assert_contains "status lists blocked tasks under needs human" "Needs human"
+run context --json
+assert_contains "context --json carries needs_human"      "segfault in fixture again"
+run start "$RETRY_TASK_ID"
+assert_exit     "start refuses a blocked task"            1
+assert_contains "start points blocked tasks at unblock"   "imi unblock"
+run next "$RETRY_TASK_ID"
+assert_exit     "next <task> refuses a blocked task"      1
+db_query "SELECT status FROM tasks WHERE id='$RETRY_TASK_ID';"
+if [[ "$DB_OUT" == "blocked" ]]; then
+  pass "start: blocked task stays blocked"
+else
+  fail "start: blocked task was revived" "DB said: $DB_OUT"
+fi
+
+run unblock "$RETRY_TASK_ID" "fixture rebuilt"
+assert_exit     "unblock exits 0"                         0
+db_query "SELECT status || ':' || attempts FROM tasks WHERE id='$RETRY_TASK_ID';"
+if [[ "$DB_OUT" == "todo:0" ]]; then
+  pass "unblock: task re-queued with a fresh budget"
+else
+  fail "unblock: task not re-queued" "DB said: $DB_OUT"
+fi
+run unblock "$RETRY_TASK_ID" "again"
+assert_exit     "unblock rejects tasks that are not blocked" 1
+
+run next "$RETRY_GOAL_ID"
+run complete "$RETRY_TASK_ID" "fixed after unblock"
+
+# ═════════════════════════════════════════════════════════════
+# 16C. LOCK LEASES (next --lease, ping --extend, lock_expired)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 16C. Lock leases ────────────────────────────────────"
+
+run add-goal "Lease goal" "Locks that expire"
+LEASE_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run add-task "$LEASE_GOAL_ID" "long migration" "takes a while"
+LEASE_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+
+run next "$LEASE_GOAL_ID" --lease nonsense
+assert_exit     "next rejects an invalid lease"           1
+run next "$LEASE_GOAL_ID" --lease 2h --agent lease-owner
+assert_contains "next reports the lease length"           "2h"
+db_query "SELECT lease_seconds, lease_expires_at - last_ping_at FROM tasks WHERE id='$LEASE_TASK_ID';"
+if [[ "$DB_OUT" == "7200|7200" ]]; then
+  pass "next --lease: lease stored at claim time"
+else
+  fail "next --lease: lease not stored" "DB said: $DB_OUT"
+fi
+
+run ping "$LEASE_TASK_ID" --extend 4h
+assert_contains "ping --extend confirms the new lease"    "4h"
+db_query "SELECT lease_seconds, lease_expires_at - last_ping_at FROM tasks WHERE id='$LEASE_TASK_ID';"
+if [[ "$DB_OUT" == "14400|14400" ]]; then
+  pass "ping --extend: lease replaced"
+else
+  fail "ping --extend: lease not replaced" "DB said: $DB_OUT"
+fi
+
+db_query "UPDATE tasks SET lease_expires_at = strftime('%s','now') - 60 WHERE id='$LEASE_TASK_ID';"
+run next "$LEASE_GOAL_ID" --agent lease-rescuer
+assert_contains "expired lease lets another agent claim"  "long migration"
+db_query "SELECT value FROM memories WHERE task_id='$LEASE_TASK_ID' AND key='lock_expired';"
+if [[ "$DB_OUT" == *"lease-owner"* ]]; then
+  pass "lock_expired memory names the previous owner"
+else
+  fail "lock_expired memory should name the previous owner" "DB said: $DB_OUT"
+fi
+run history "$LEASE_TASK_ID"
+assert_contains "history counts the expired lock"         "1 expired lock"
+
+db_query "INSERT OR REPLACE INTO settings (key, value) VALUES ('lock_lease', '45m');"
+run add-task "$LEASE_GOAL_ID" "follow-up" "default lease"
+LEASE_TASK2_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run start "$LEASE_TASK2_ID"
+db_query "SELECT lease_seconds FROM tasks WHERE id='$LEASE_TASK2_ID';"
+if [[ "$DB_OUT" == "2700" ]]; then
+  pass "lock_lease setting sets the default lease"
+else
+  fail "lock_lease setting should set the default lease" "DB said: $DB_OUT"
+fi
+db_query "DELETE FROM settings WHERE key='lock_lease';"
+run complete "$LEASE_TASK2_ID" "done"
+run complete "$LEASE_TASK_ID" "migrated"
+
+# ═════════════════════════════════════════════════════════════
+# 16D. CONFIG (layered defaults, imi config get/set/list)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 16D. Config ─────────────────────────────────────────"
+
+export XDG_CONFIG_HOME="$TEST_DIR/xdg"
+run config get orchestrate.workers --show-origin
+assert_contains "config get falls back to the built-in default" "default"
+run config set orchestrate.workers 0
+assert_exit     "config set validates the value"          1
+run config set nonsense.key 1
+assert_exit     "config set rejects unknown keys"         1
+
+run config set orchestrate.workers 6
+assert_exit     "config set exits 0"                      0
+if [[ -f "$TEST_DIR/config.toml" ]]; then
+  pass "config set writes config.toml next to the DB"
+else
+  fail "config set writes config.toml next to the DB" "missing $TEST_DIR/config.toml"
+fi
+run config get orchestrate.workers --show-origin
+assert_contains "project config overrides the default"    "project config"
+IMI_WORKERS=2 run config get orchestrate.workers
+assert_contains "env var overrides the project config"    "2"
+
+run config set --global run.model claude-test-model
+if [[ -f "$XDG_CONFIG_HOME/imi/config.toml" ]]; then
+  pass "config set --global writes the user config"
+else
+  fail "config set --global writes the user config" "missing $XDG_CONFIG_HOME/imi/config.toml"
+fi
+run config list --show-origin
+assert_contains "config list shows the user layer"        "user config"
+assert_contains "config list shows the value"             "claude-test-model"
+
+run config set tasks.max_attempts 5
+run add-goal "Config goal" "Configured retries"
+CFG_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run add-task "$CFG_GOAL_ID" "configured task" "uses tasks.max_attempts"
+CFG_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run next "$CFG_GOAL_ID"
+run fail "$CFG_TASK_ID" "first try"
+assert_contains "fail reads max_attempts from config"     "attempt 1/5"
+IMI_CONTEXT_GOALS=1 run context --toon
+assert_contains "context reads its goal limit from config" "goals\[1\]"
+run next "$CFG_GOAL_ID"
+run complete "$CFG_TASK_ID" "second try worked"
+run fail "$CFG_TASK_ID" "too late"
+assert_exit     "fail refuses a task that is not in progress" 1
+db_query "SELECT status || ':' || attempts FROM tasks WHERE id='$CFG_TASK_ID';"
+if [[ "$DB_OUT" == "done:1" ]]; then
+  pass "fail on a done task leaves it done"
+else
+  fail "fail reopened a done task" "DB said: $DB_OUT"
+fi
+
+rm -f "$TEST_DIR/config.toml"
+rm -rf "$XDG_CONFIG_HOME"
+unset XDG_CONFIG_HOME
+
+# ═════════════════════════════════════════════════════════════
+# 16E. UNDO JOURNAL (imi journal, imi undo, reset backups)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 16E. Undo journal ───────────────────────────────────"
+
+# Own database: this section runs reset, which would wipe the shared fixture.
+MAIN_IMI_DB="$IMI_DB"
+export IMI_DB="$TEST_DIR/undo/state.db"
+run init
+run goal "Undo goal" "Gets deleted by mistake"
+UNDO_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run task "$UNDO_GOAL_ID" "keep me" "should survive the mistake"
+UNDO_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run memory add "$UNDO_GOAL_ID" undo_fact "written before the delete"
+
+run delete "$UNDO_GOAL_ID"
+run journal
+assert_exit     "journal exits 0"                          0
+assert_contains "journal lists the delete first"           "imi delete $UNDO_GOAL_ID"
+assert_contains "journal summarises touched rows"          "goals -1"
+run undo
+assert_exit     "undo exits 0"                             0
+db_query "SELECT COUNT(*) FROM tasks WHERE id='$UNDO_TASK_ID';"
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "undo restores the deleted task"
+else
+  fail "undo did not restore the task" "DB said: $DB_OUT"
+fi
+run search "written before the delete"
+assert_contains "undo keeps the search index in sync"      "undo_fact"
+
+run next "$UNDO_GOAL_ID"
+run complete "$UNDO_TASK_ID" "done by mistake"
+run undo 2
+db_query "SELECT status FROM tasks WHERE id='$UNDO_TASK_ID';"
+if [[ "$DB_OUT" == "todo" ]]; then
+  pass "undo 2 reverts complete and claim"
+else
+  fail "undo 2 left the task in the wrong state" "DB said: $DB_OUT"
+fi
+
+run next "$UNDO_GOAL_ID"
+run ping "$UNDO_TASK_ID" --extend 2h
+run undo
+assert_exit     "undo refuses rows changed since the command"  1
+assert_contains "undo names the changed row"               "has changed since"
+run undo --force
+assert_exit     "undo --force restores anyway"             0
+db_query "SELECT status FROM tasks WHERE id='$UNDO_TASK_ID';"
+if [[ "$DB_OUT" == "todo" ]]; then
+  pass "undo --force reverts the claim"
+else
+  fail "undo --force left the task claimed" "DB said: $DB_OUT"
+fi
+
+IMI_AGENT_ID=someone-else run goal "Their goal" "Not ours to undo"
+OTHER_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run goal "Our goal" "Ours to undo"
+OUR_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+IMI_AGENT_ID=someone-else run goal "Their second goal" "Newest entry overall"
+run undo
+db_query "SELECT (SELECT COUNT(*) FROM goals WHERE id='$OUR_GOAL_ID') || ':' || (SELECT COUNT(*) FROM goals WHERE id='$OTHER_GOAL_ID');"
+if [[ "$DB_OUT" == "0:1" ]]; then
+  pass "undo only reverts the current agent's commands"
+else
+  fail "undo reverted another agent's command" "DB said: $DB_OUT"
+fi
+
+run reset --force
+assert_contains "reset reports its backup"                 "backups/state-"
+if ls "$TEST_DIR/undo/backups/"state-*.db >/dev/null 2>&1; then
+  pass "reset snapshots the DB to .imi/backups"
+else
+  fail "reset left no backup" "$(ls "$TEST_DIR/undo" 2>&1)"
+fi
+run undo
+db_query "SELECT COUNT(*) FROM goals WHERE id='$UNDO_GOAL_ID';"
+if [[ "$DB_OUT" == "1" ]]; then
+  pass "undo reverts a reset"
+else
+  fail "undo did not revert the reset" "DB said: $DB_OUT"
+fi
+run journal --all --json
+assert_contains "journal --all shows undone entries"       '"undone_at":[0-9]'
+export IMI_DB="$MAIN_IMI_DB"
+
+# ═════════════════════════════════════════════════════════════
+# 16F. REVIEW GATE (requires_review, imi approve / reject)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 16F. Review gate ────────────────────────────────────"
+
+run goal "Review goal" "Humans sign off" --requires-review
+REVIEW_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run task "$REVIEW_GOAL_ID" "rotate signing keys" "needs a second pair of eyes"
+REVIEW_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run next "$REVIEW_GOAL_ID" --agent review-worker
+run complete "$REVIEW_TASK_ID" "rotated keys in staging" --agent review-worker
+assert_contains "complete on a review goal submits for review" "submitted for review"
+db_query "SELECT status FROM tasks WHERE id='$REVIEW_TASK_ID';"
+if [[ "$DB_OUT" == "review" ]]; then
+  pass "complete moves the task to review"
+else
+  fail "complete skipped review" "DB said: $DB_OUT"
+fi
+run context
+assert_contains "context shows the review queue"          "Waiting for review"
+run next "$REVIEW_GOAL_ID" --agent review-other
+assert_not_contains "next never hands out review tasks"   "rotate signing keys"
+
+run reject "$REVIEW_TASK_ID" "also rotate prod" --agent reviewer-1
+assert_exit     "reject exits 0"                          0
+db_query "SELECT status || '|' || reviewed_by FROM tasks WHERE id='$REVIEW_TASK_ID';"
+if [[ "$DB_OUT" == "todo|reviewer-1" ]]; then
+  pass "reject reopens the task and records the reviewer"
+else
+  fail "reject did not reopen" "DB said: $DB_OUT"
+fi
+run next "$REVIEW_GOAL_ID" --agent review-worker
+assert_contains "next shows the rejection reason"         "also rotate prod"
+run complete "$REVIEW_TASK_ID" "rotated staging and prod" --agent review-worker
+run approve "$REVIEW_TASK_ID" --agent reviewer-1
+assert_exit     "approve exits 0"                         0
+db_query "SELECT status || '|' || reviewed_by FROM tasks WHERE id='$REVIEW_TASK_ID';"
+if [[ "$DB_OUT" == "done|reviewer-1" ]]; then
+  pass "approve marks the task done with the reviewer"
+else
+  fail "approve did not finish the task" "DB said: $DB_OUT"
+fi
+run approve "$REVIEW_TASK_ID"
+assert_exit     "approve refuses tasks not in review"     1
+
+# ═════════════════════════════════════════════════════════════
+# 16G. ACCEPTANCE CHECKS (verify --run, complete --verify)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 16G. Acceptance checks ──────────────────────────────"
+
+CHECK_WS="$TEST_DIR/check-ws"
+mkdir -p "$CHECK_WS"
+run goal "Checks goal" "Machine-checked acceptance"
+CHECK_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run task "$CHECK_GOAL_ID" "write the changelog" "release notes" --workspace "$CHECK_WS" \
+  --check "exists:CHANGELOG.md" --check "contains:CHANGELOG.md:## 1.0" --check "run:test -s CHANGELOG.md"
+CHECK_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run task "$CHECK_GOAL_ID" "bad check" "x" --check "nonsense"
+assert_exit     "task rejects malformed --check"          1
+
+run next "$CHECK_GOAL_ID"
+run verify "$CHECK_TASK_ID" --run
+assert_contains "verify --run reports failures"           "FAILED — 0/3"
+run complete "$CHECK_TASK_ID" "wrote it" --verify
+assert_exit     "complete --verify refuses failing checks" 1
+db_query "SELECT status FROM tasks WHERE id='$CHECK_TASK_ID';"
+if [[ "$DB_OUT" == "in_progress" ]]; then
+  pass "failed --verify leaves the task in progress"
+else
+  fail "failed --verify changed the task" "DB said: $DB_OUT"
+fi
+db_query "SELECT type FROM memories WHERE task_id='$CHECK_TASK_ID' AND key='acceptance_checks' ORDER BY rowid DESC LIMIT 1;"
+if [[ "$DB_OUT" == "failure" ]]; then
+  pass "check results are stored as memories"
+else
+  fail "check results missing" "DB said: $DB_OUT"
+fi
+
+printf '# Changelog\n\n## 1.0\n- first release\n' > "$CHECK_WS/CHANGELOG.md"
+run verify "$CHECK_TASK_ID" --add "grep:CHANGELOG.md:first rel(ease)?" --run
+assert_contains "verify --add attaches and runs new checks" "PASSED — 4/4"
+run complete "$CHECK_TASK_ID" "wrote it" --verify
+assert_exit     "complete --verify passes when checks do" 0
+run verify "$CHECK_TASK_ID" --json
+assert_contains "verify --json lists checks"              '"last_status":"pass"'
+
+run task "$CHECK_GOAL_ID" "hanging check" "never returns" --workspace "$CHECK_WS" --check "run:sleep 30"
+HANG_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+HANG_START=$(date +%s)
+CMD_EXIT=0
+CMD_OUT=$(IMI_CHECK_TIMEOUT=1s "$IMI_BIN" verify "$HANG_TASK_ID" --run 2>&1) || CMD_EXIT=$?
+HANG_ELAPSED=$(( $(date +%s) - HANG_START ))
+assert_contains "a hanging check is killed at checks.timeout" "timed out after"
+if [[ "$HANG_ELAPSED" -lt 10 ]]; then
+  pass "hanging check does not block verify (${HANG_ELAPSED}s)"
+else
+  fail "hanging check blocked verify" "took ${HANG_ELAPSED}s"
+fi
+run delete "$HANG_TASK_ID"
+
+# ═════════════════════════════════════════════════════════════
+# 16H. GOAL OUTCOMES (imi goal review / due, think)
+# ═════════════════════════════════════════════════════════════
+echo ""
+echo "── 16H. Goal outcomes ──────────────────────────────────"
+
+run goal "Faster onboarding" "Cut signup steps" high "drop-off" "new users" "signup completion above 60%"
+OUTCOME_GOAL_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run task "$OUTCOME_GOAL_ID" "remove the survey step" "x"
+OUTCOME_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
+run next "$OUTCOME_GOAL_ID"
+run complete "$OUTCOME_TASK_ID" "survey removed"
+db_query "UPDATE goals SET archived_at = strftime('%s','now') - 30*86400 WHERE id='$OUTCOME_GOAL_ID';"
+
+run goal due
+assert_contains "goal due lists old archived goals"       "Faster onboarding"
+assert_contains "goal due shows the success signal"       "signup completion above 60%"
+run goal due --after 60d
+assert_not_contains "goal due --after narrows the window" "Faster onboarding"
+run think
+assert_contains "think lists unchecked success signals"   "never checked against their success signal"
+
+run goal review "$OUTCOME_GOAL_ID" maybe "not sure"
+assert_exit     "goal review rejects unknown outcomes"    1
+run goal review "$OUTCOME_GOAL_ID" partial "completion went to 52%"
+assert_exit     "goal review exits 0"                     0
+db_query "SELECT outcome FROM goals WHERE id='$OUTCOME_GOAL_ID';"
+if [[ "$DB_OUT" == "partial" ]]; then
+  pass "goal review stores the outcome"
+else
+  fail "goal review did not store the outcome" "DB said: $DB_OUT"
+fi
+run goal review "$OUTCOME_GOAL_ID"
+assert_contains "goal review without outcome shows history" "completion went to 52%"
+run goal due
+assert_not_contains "reviewed goals leave the due list"   "Faster onboarding"
+
+for word in review due; do
+  run goal -- "$word" "a goal literally named $word"
+  assert_exit "goal named '$word' is created"            0
+  db_query "SELECT id FROM goals WHERE name='$word';"
+  if [[ -n "$DB_OUT" ]]; then
+    pass "goal named '$word' is stored"
+    run delete "$DB_OUT"
+  else
+    fail "goal named '$word' was parsed as something else" "$CMD_OUT"
+  fi
+done
+
 # ═════════════════════════════════════════════════════════════
 # 17. NEXT with --agent (critical test)
 # ═════════════════════════════════════════════════════════════
@@ -505,7 +1756,7 @@ run next --agent agent-alpha --toon
 assert_exit       "next --agent exits 0"     0
 assert_starts_with "next --agent starts with task[" "task["
 
-AGENT_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z0-9]{14,}' | head -1)
+AGENT_TASK_ID=$(echo "$CMD_OUT" | grep -oE '[a-z]_[a-z0-9]{14,}' | head -1)
 if [[ -n "$AGENT_TASK_ID" ]]; then
   db_query "SELECT agent_id FROM tasks WHERE id='$AGENT_TASK_ID';"
   if [[ "$DB_OUT" == "agent-alpha" ]]; then
@@ -578,6 +1829,36 @@ else
   fail "ping completed task: unexpected exit code" "exit=$CMD_EXIT"
 fi
 
+# Type-tagged IDs and prefix resolution
+if [[ "$GOAL_ID" == g_* && "$NEXT_TASK_ID" == t_* ]]; then
+  pass "goal and task IDs carry type tags"
+else
+  fail "IDs are not type-tagged" "goal=$GOAL_ID task=$NEXT_TASK_ID"
+fi
+run history "t_"
+assert_exit     "ambiguous prefix is rejected"             1
+assert_contains "ambiguous prefix lists candidates"        "$NEXT_TASK_ID"
+run history "${HIST_TASK_ID#t_}"
+assert_exit     "prefix without the tag still resolves"    0
+run start "g_${HIST_TASK_ID#t_}"
+assert_exit     "a goal-tagged prefix never matches a task" 1
+run delete "g_"
+assert_exit     "delete refuses an ambiguous prefix"       1
+run status
+assert_not_contains "status shows short unique prefixes"   "$GOAL_ID"
+db_query "SELECT t.id FROM tasks t JOIN goals g ON g.id = t.goal_id WHERE t.status IN ('todo','in_progress') AND g.status NOT IN ('done','archived') LIMIT 1;"
+OPEN_TASK_ID="$DB_OUT"
+run context
+assert_not_contains "context shows short task prefixes"     "$OPEN_TASK_ID"
+run plan
+assert_not_contains "plan shows short task prefixes"        "$OPEN_TASK_ID"
+db_query "SELECT COUNT(*) FROM memories WHERE id NOT LIKE 'm\_%' ESCAPE '\';"
+if [[ "$DB_OUT" == "0" ]]; then
+  pass "memory IDs carry the m_ tag"
+else
+  fail "untagged memory IDs" "count=$DB_OUT"
+fi
+
 # ═════════════════════════════════════════════════════════════
 # 21. DB INTEGRITY
 # ═════════════════════════════════════════════════════════════